@@ -1,6 +1,7 @@
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
+
+use crate::{Error, Result};
 
-use anyhow::Result;
 use tonic::{
     metadata::{Ascii, MetadataValue},
     service::Interceptor,
@@ -9,11 +10,31 @@ use tonic::{
 #[derive(Clone)]
 pub struct Auth {
     token: MetadataValue<Ascii>,
+    // Ключ подписи пользователя, от имени которого выполняются запросы.
+    // Без него клиент не может действовать от имени пользователя
+    signing_key: Option<Arc<[u8]>>,
+    acting_user: Option<i64>,
 }
 impl Auth {
     pub(crate) fn new(token: String) -> Result<Self> {
-        let token = MetadataValue::from_str(&token)?;
-        Ok(Self { token })
+        let token = MetadataValue::from_str(&token).map_err(|e| Error::Config(e.to_string()))?;
+        Ok(Self {
+            token,
+            signing_key: None,
+            acting_user: None,
+        })
+    }
+    pub(crate) fn with_signing_key(self, signing_key: &str) -> Self {
+        Self {
+            signing_key: Some(signing_key.as_bytes().into()),
+            ..self
+        }
+    }
+    pub(crate) fn acting_as(self, telegram_id: i64) -> Self {
+        Self {
+            acting_user: Some(telegram_id),
+            ..self
+        }
     }
 }
 impl Interceptor for Auth {
@@ -24,6 +45,24 @@ impl Interceptor for Auth {
         request
             .metadata_mut()
             .insert("authorization", self.token.clone());
+        if let Some(telegram_id) = self.acting_user {
+            let signing_key = self
+                .signing_key
+                .as_deref()
+                .ok_or(tonic::Status::unauthenticated(
+                    "signing key is required to act on behalf of a user",
+                ))?;
+            // Подпись действует ограниченное время, поэтому делается для
+            // каждого запроса
+            let signed_at = chrono::Utc::now().timestamp();
+            let signature = grpc::caller::sign(signing_key, telegram_id, signed_at)
+                .parse()
+                .map_err(|_| tonic::Status::internal("malformed acting user signature"))?;
+            let metadata = request.metadata_mut();
+            metadata.insert(grpc::caller::USER, telegram_id.into());
+            metadata.insert(grpc::caller::SIGNED_AT, signed_at.into());
+            metadata.insert(grpc::caller::SIGNATURE, signature);
+        }
         Ok(request)
     }
 }
@@ -0,0 +1,129 @@
+use grpc::errors;
+use shared::models::Status;
+use tonic::Code;
+use tonic_types::StatusExt;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+// Ошибки клиента, восстановленные из ответа сервера
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    // Сущность не найдена (пост, автор, пользователь)
+    #[error("{resource} not found")]
+    NotFound { resource: String },
+    // Сущность уже существует
+    #[error("{resource} already exists")]
+    AlreadyExists { resource: String },
+    // Сервер отклонил поля запроса
+    #[error("{message}")]
+    InvalidArgument {
+        message: String,
+        violations: Vec<FieldViolation>,
+    },
+    // Недопустимый переход статуса поста
+    #[error("{message}")]
+    InvalidTransition {
+        message: String,
+        from: Option<Status>,
+        to: Option<Status>,
+    },
+    // Недостаточно прав для операции
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    // Неверный или отсутствующий токен
+    #[error("unauthenticated: {0}")]
+    Unauthenticated(String),
+    // Состояние сущности изменилось конкурентно
+    #[error("conflict: {0}")]
+    Conflict(String),
+    // Пост нужно отправить на проверку перед планированием публикации
+    #[error("{0}")]
+    ReviewRequired(String),
+    // Сервер временно недоступен
+    #[error("server unavailable: {0}")]
+    Unavailable(String),
+    // Внутренняя ошибка сервера
+    #[error("internal server error: {0}")]
+    Internal(String),
+    // Ответ сервера не удалось преобразовать в модель
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+    // Ошибка подключения к серверу
+    #[error("transport error: {0}")]
+    Transport(#[from] tonic::transport::Error),
+    // Некорректная конфигурация клиента
+    #[error("invalid configuration: {0}")]
+    Config(String),
+}
+
+// Некорректное поле запроса
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldViolation {
+    pub field: String,
+    pub description: String,
+}
+
+impl Error {
+    // Ошибка временная и запрос имеет смысл повторить
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Unavailable(_) | Self::Transport(_))
+    }
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::NotFound { .. })
+    }
+    pub(crate) fn invalid_response(error: anyhow::Error) -> Self {
+        Self::InvalidResponse(format!("{error:#}"))
+    }
+}
+
+impl From<tonic::Status> for Error {
+    fn from(status: tonic::Status) -> Self {
+        let details = status.get_error_details();
+        let message = status.message().to_string();
+        let info = details.error_info().filter(|i| i.domain == errors::DOMAIN);
+        let metadata = |key: &str| info.and_then(|i| i.metadata.get(key)).cloned();
+        let violations = details
+            .bad_request()
+            .map(|b| {
+                b.field_violations
+                    .iter()
+                    .map(|v| FieldViolation {
+                        field: v.field.clone(),
+                        description: v.description.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let resource = metadata(errors::RESOURCE).unwrap_or_else(|| String::from("entity"));
+        match (info.map(|i| i.reason.as_str()), status.code()) {
+            (Some(errors::NOT_FOUND), _) | (None, Code::NotFound) => Self::NotFound { resource },
+            (Some(errors::ALREADY_EXISTS), _) | (None, Code::AlreadyExists) => {
+                Self::AlreadyExists { resource }
+            }
+            (Some(errors::INVALID_ARGUMENT), _)
+            | (None, Code::InvalidArgument | Code::OutOfRange) => Self::InvalidArgument {
+                message,
+                violations,
+            },
+            (Some(errors::REVIEW_REQUIRED), _) => Self::ReviewRequired(message),
+            (Some(errors::INVALID_TRANSITION), _) | (None, Code::FailedPrecondition) => {
+                Self::InvalidTransition {
+                    message,
+                    from: metadata(errors::FROM).map(Status::from),
+                    to: metadata(errors::TO).map(Status::from),
+                }
+            }
+            (Some(errors::PERMISSION_DENIED), _) | (None, Code::PermissionDenied) => {
+                Self::PermissionDenied(message)
+            }
+            (Some(errors::CONFLICT), _) | (None, Code::Aborted) => Self::Conflict(message),
+            (Some(errors::UNAUTHENTICATED), _) | (_, Code::Unauthenticated) => {
+                Self::Unauthenticated(message)
+            }
+            (_, Code::Unavailable | Code::DeadlineExceeded | Code::ResourceExhausted) => {
+                Self::Unavailable(message)
+            }
+            _ => Self::Internal(message),
+        }
+    }
+}
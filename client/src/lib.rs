@@ -1,27 +1,116 @@
 mod auth;
+mod error;
+pub use error::{Error, FieldViolation, Result};
 
-use anyhow::{Result, anyhow};
 use grpc::smm::{
     posts::posts_service_client::PostsServiceClient,
     users::users_service_client::UsersServiceClient,
 };
-use shared::models::{Post, Status, User};
-use tonic::{service::interceptor::InterceptedService, transport::Channel};
+use shared::models::{Delivery, Post, PostEvent, PostRevision, PostSortField, Status, User};
+use std::{collections::HashMap, pin::Pin};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    service::interceptor::InterceptedService,
+    transport::{Certificate, Channel, ClientTlsConfig, Identity},
+};
 use tracing::{info, instrument};
 use uuid::Uuid;
 
+// Страница списка постов
+#[derive(Debug, Clone, Default)]
+pub struct PostsPage {
+    pub posts: Vec<Post>,
+    // Имена авторов постов страницы по UUID
+    pub authors: HashMap<Uuid, String>,
+    // Токен следующей страницы (None, если страница последняя)
+    pub next_page_token: Option<String>,
+}
+impl PostsPage {
+    pub fn has_next(&self) -> bool {
+        self.next_page_token.is_some()
+    }
+}
+
+// Страница истории правок поста
+#[derive(Debug, Clone, Default)]
+pub struct RevisionsPage {
+    // Правки от новых к старым
+    pub revisions: Vec<PostRevision>,
+    // Токен следующей страницы (None, если страница последняя)
+    pub next_page_token: Option<String>,
+}
+impl RevisionsPage {
+    pub fn has_next(&self) -> bool {
+        self.next_page_token.is_some()
+    }
+}
+
+// Настройки TLS соединения с сервером
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    // CA сертификата сервера в PEM (по умолчанию - системные корневые сертификаты)
+    pub ca_cert: Option<Vec<u8>>,
+    // Сертификат и закрытый ключ клиента в PEM для взаимного TLS
+    pub identity: Option<(Vec<u8>, Vec<u8>)>,
+    // Имя сервера в сертификате, если оно отличается от адреса подключения
+    pub domain: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct Client {
     pub users_client: UsersServiceClient<InterceptedService<Channel, auth::Auth>>,
     pub posts_client: PostsServiceClient<InterceptedService<Channel, auth::Auth>>,
+    channel: Channel,
+    auth: auth::Auth,
 }
 impl Client {
-    #[instrument(name = "new rpc client", skip(token))]
+    // Подключается к серверу на этом же хосте без TLS
     pub async fn new(port: u16, token: String) -> Result<Self> {
-        let addr = format!("http://[::1]:{port}");
-        let channel = Channel::builder(addr.parse()?).connect().await?;
+        Self::connect("::1", port, token, None).await
+    }
+    // Подключается к серверу по адресу или имени хоста, с TLS, если заданы
+    // его настройки
+    #[instrument(name = "new rpc client", skip(token, tls))]
+    pub async fn connect(
+        host: &str,
+        port: u16,
+        token: String,
+        tls: Option<TlsOptions>,
+    ) -> Result<Self> {
+        // Адрес IPv6 в URI записывается в квадратных скобках
+        let authority = if host.contains(':') && !host.starts_with('[') {
+            format!("[{host}]")
+        } else {
+            host.to_string()
+        };
+        let scheme = if tls.is_some() { "https" } else { "http" };
+        let mut endpoint = Channel::from_shared(format!("{scheme}://{authority}:{port}"))
+            .map_err(|e| Error::Config(e.to_string()))?;
+        if let Some(tls) = tls {
+            let mut config = ClientTlsConfig::new().with_native_roots();
+            if let Some(ca_cert) = tls.ca_cert {
+                config = config.ca_certificate(Certificate::from_pem(ca_cert));
+            }
+            if let Some((cert, key)) = tls.identity {
+                config = config.identity(Identity::from_pem(cert, key));
+            }
+            // Имя сервера берется из адреса без скобок: иначе адрес IPv6 не
+            // проходит проверку сертификата
+            let domain = tls
+                .domain
+                .unwrap_or_else(|| host.trim_matches(['[', ']']).to_string());
+            config = config.domain_name(domain);
+            endpoint = endpoint
+                .tls_config(config)
+                .map_err(|e| Error::Config(e.to_string()))?;
+        }
+        let channel = endpoint.connect().await?;
         let bearer_token = format!("Bearer {token}");
         let auth = auth::Auth::new(bearer_token)?;
+        info!("rpc client initialized");
+        Ok(Self::with_auth(channel, auth))
+    }
+    fn with_auth(channel: Channel, auth: auth::Auth) -> Self {
         let users_client =
             grpc::smm::users::users_service_client::UsersServiceClient::with_interceptor(
                 channel.clone(),
@@ -29,13 +118,28 @@ impl Client {
             );
         let posts_client =
             grpc::smm::posts::posts_service_client::PostsServiceClient::with_interceptor(
-                channel, auth,
+                channel.clone(),
+                auth.clone(),
             );
-        info!("rpc client initialized");
-        Ok(Self {
+        Self {
             users_client,
             posts_client,
-        })
+            channel,
+            auth,
+        }
+    }
+    // Задает ключ подписи пользователя, общий с сервером. Нужен для запросов
+    // от имени пользователя
+    pub fn with_signing_key(self, signing_key: &str) -> Self {
+        Self::with_auth(self.channel, self.auth.with_signing_key(signing_key))
+    }
+    // Клиент, выполняющий запросы от имени пользователя: сервер проверяет его
+    // роль и права на изменяемые посты
+    pub fn acting_as(&self, telegram_id: i64) -> Self {
+        Self::with_auth(
+            self.channel.clone(),
+            self.auth.clone().acting_as(telegram_id),
+        )
     }
 
     #[instrument(name = "create user", skip(self))]
@@ -60,7 +164,9 @@ impl Client {
             .await?
             .into_inner()
             .created_user
-            .and_then(|u| u.try_into().ok());
+            .map(|u| u.try_into())
+            .transpose()
+            .map_err(Error::invalid_response)?;
         if let Some(created) = response.as_ref() {
             info!("Created new user:\n{created:#?}");
         }
@@ -76,7 +182,9 @@ impl Client {
             .await?
             .into_inner()
             .user
-            .and_then(|u| u.try_into().ok());
+            .map(|u| u.try_into())
+            .transpose()
+            .map_err(Error::invalid_response)?;
         if let Some(founded) = response.as_ref() {
             info!("Result:\n{founded:#?}");
         }
@@ -89,6 +197,7 @@ impl Client {
             page_size: 10,
             role_filter: Some(shared::models::Role::Admin.into()),
             sort_by_created_asc: None,
+            page_token: None,
         });
         let response = self
             .users_client
@@ -100,24 +209,55 @@ impl Client {
         Ok(!response.is_empty())
     }
 
+    // Администраторы (для уведомлений)
+    #[instrument(name = "list admins", skip(self))]
+    pub async fn list_admins(&mut self) -> Result<Vec<User>> {
+        let request = tonic::Request::new(grpc::smm::users::ListUsersRequest {
+            page: 1,
+            page_size: 100,
+            role_filter: Some(shared::models::Role::Admin.into()),
+            sort_by_created_asc: None,
+            page_token: None,
+        });
+        let admins = self
+            .users_client
+            .list_users(request)
+            .await?
+            .into_inner()
+            .users
+            .into_iter()
+            .flat_map(|u| u.try_into())
+            .collect::<Vec<_>>();
+        info!("Total admins: {l}", l = admins.len());
+        Ok(admins)
+    }
+
+    // Страница пользователей: первая, если токен не передан. Возвращает токен
+    // следующей страницы (None, если страница последняя)
     #[instrument(name = "list users", skip(self))]
-    pub async fn list_users(&mut self, page: u32) -> Result<(Vec<User>, bool)> {
+    pub async fn list_users(
+        &mut self,
+        page_token: Option<String>,
+    ) -> Result<(Vec<User>, Option<String>)> {
         let request = tonic::Request::new(grpc::smm::users::ListUsersRequest {
-            page,
+            page: 1,
             page_size: 10,
             role_filter: None,
             sort_by_created_asc: None,
+            page_token,
         });
         let response = self.users_client.list_users(request).await?.into_inner();
-        let total_pages = response.total_pages;
-        let has_next = response.total_pages > page;
-        info!("Current page: {page}, total pages: {total_pages} => has next: {has_next}");
+        let next_page_token = Some(response.next_page_token).filter(|t| !t.is_empty());
+        info!(
+            "Has next page: {has_next}",
+            has_next = next_page_token.is_some()
+        );
         let list = response
             .users
             .into_iter()
             .flat_map(|u| u.try_into())
             .collect();
-        Ok((list, has_next))
+        Ok((list, next_page_token))
     }
 
     #[instrument(name = "update user", skip(self))]
@@ -131,7 +271,9 @@ impl Client {
             .await?
             .into_inner()
             .updated_user
-            .and_then(|u| u.try_into().ok());
+            .map(|u| u.try_into())
+            .transpose()
+            .map_err(Error::invalid_response)?;
         Ok(response)
     }
 
@@ -148,66 +290,185 @@ impl Client {
         Ok(response)
     }
 
-    #[instrument(name = "get draft posts of user", skip(self))]
-    pub async fn drafts(&mut self, author_tg_id: i64, page: u32) -> Result<(Vec<Post>, bool)> {
+    // Страница постов автора (или всех авторов, если author_tg_id равен 0)
+    // в заданном статусе, упорядоченных по возрастанию поля sort_by: по токену,
+    // если он есть, иначе по номеру
+    #[instrument(name = "list posts of user", skip(self))]
+    pub async fn list_posts(
+        &mut self,
+        author_tg_id: i64,
+        status: Status,
+        sort_by: PostSortField,
+        page: u32,
+        page_token: Option<String>,
+    ) -> Result<PostsPage> {
         let request = tonic::Request::new(grpc::smm::posts::ListPostsRequest {
             author_tg_id,
             page,
             page_size: 10,
-            status_filter: Some(Status::Draft.into()),
+            status_filter: Some(status.into()),
+            page_token,
+            sort_by: sort_by.into(),
+            ..Default::default()
         });
         let response = self.posts_client.list_posts(request).await?.into_inner();
-        let total_pages = response.total_pages;
-        let has_next = total_pages > page;
-        info!("Current page: {page}, total pages: {total_pages} => has next: {has_next}");
+        let next_page_token = Some(response.next_page_token).filter(|t| !t.is_empty());
+        info!(
+            "Current page: {page}, has next page: {has_next}",
+            has_next = next_page_token.is_some()
+        );
         let posts = response
             .posts
             .into_iter()
             .flat_map(|p| p.try_into())
             .collect();
-        Ok((posts, has_next))
+        let authors = response
+            .authors
+            .into_iter()
+            .flat_map(|(id, author)| id.parse().map(|id| (id, author.name)))
+            .collect();
+        Ok(PostsPage {
+            posts,
+            authors,
+            next_page_token,
+        })
     }
 
-    #[instrument(name = "get pending posts of user", skip(self))]
-    pub async fn pending(&mut self, author_tg_id: i64, page: u32) -> Result<(Vec<Post>, bool)> {
+    // Страница постов автора (или всех авторов, если author_tg_id равен 0)
+    // в корзине, от новых к старым
+    #[instrument(name = "list trash", skip(self))]
+    pub async fn list_trash(
+        &mut self,
+        author_tg_id: i64,
+        page: u32,
+        page_token: Option<String>,
+    ) -> Result<PostsPage> {
         let request = tonic::Request::new(grpc::smm::posts::ListPostsRequest {
             author_tg_id,
             page,
             page_size: 10,
-            status_filter: Some(Status::Pending.into()),
+            page_token,
+            descending: true,
+            deleted: true,
+            ..Default::default()
         });
         let response = self.posts_client.list_posts(request).await?.into_inner();
-        let total_pages = response.total_pages;
-        let has_next = total_pages > page;
-        info!("Current page: {page}, total pages: {total_pages} => has next: {has_next}");
+        let next_page_token = Some(response.next_page_token).filter(|t| !t.is_empty());
+        info!(
+            "Current page: {page}, has next page: {has_next}",
+            has_next = next_page_token.is_some()
+        );
         let posts = response
             .posts
             .into_iter()
             .flat_map(|p| p.try_into())
             .collect();
-        Ok((posts, has_next))
+        let authors = response
+            .authors
+            .into_iter()
+            .flat_map(|(id, author)| id.parse().map(|id| (id, author.name)))
+            .collect();
+        Ok(PostsPage {
+            posts,
+            authors,
+            next_page_token,
+        })
     }
 
-    #[instrument(name = "get published posts of user", skip(self))]
-    pub async fn published(&mut self, author_tg_id: i64, page: u32) -> Result<(Vec<Post>, bool)> {
-        let request = tonic::Request::new(grpc::smm::posts::ListPostsRequest {
-            author_tg_id,
-            page,
+    // Страница постов, заголовок или содержимое которых включают все слова
+    // запроса, от новых к старым. Пустой список авторов - посты всех авторов
+    #[instrument(name = "search posts", skip(self))]
+    pub async fn search_posts(
+        &mut self,
+        query: String,
+        author_tg_ids: Vec<i64>,
+        status: Option<Status>,
+        page_token: Option<String>,
+    ) -> Result<PostsPage> {
+        let request = tonic::Request::new(grpc::smm::posts::SearchPostsRequest {
+            query,
+            author_tg_ids,
+            status_filter: status.map(Into::into),
             page_size: 10,
-            status_filter: Some(Status::Published.into()),
+            page_token,
+            ..Default::default()
         });
-        let response = self.posts_client.list_posts(request).await?.into_inner();
-        let total_pages = response.total_pages;
-        let has_next = total_pages > page;
-        info!("Current page: {page}, total pages: {total_pages} => has next: {has_next}");
+        let response = self.posts_client.search_posts(request).await?.into_inner();
+        let next_page_token = Some(response.next_page_token).filter(|t| !t.is_empty());
+        info!(
+            "Found: {found}, has next page: {has_next}",
+            found = response.posts.len(),
+            has_next = next_page_token.is_some()
+        );
         let posts = response
             .posts
             .into_iter()
             .flat_map(|p| p.try_into())
             .collect();
-        Ok((posts, has_next))
+        let authors = response
+            .authors
+            .into_iter()
+            .flat_map(|(id, author)| id.parse().map(|id| (id, author.name)))
+            .collect();
+        Ok(PostsPage {
+            posts,
+            authors,
+            next_page_token,
+        })
+    }
+
+    #[instrument(name = "get due posts", skip(self))]
+    pub async fn due_posts(&mut self) -> Result<Vec<Post>> {
+        let request =
+            tonic::Request::new(grpc::smm::posts::ListDuePostsRequest { due_before: None });
+        let posts: Vec<Post> = self
+            .posts_client
+            .list_due_posts(request)
+            .await?
+            .into_inner()
+            .posts
+            .into_iter()
+            .flat_map(|p| p.try_into())
+            .collect();
+        info!("Due posts: {l}", l = posts.len());
+        Ok(posts)
+    }
+
+    #[instrument(name = "get next due time", skip(self))]
+    pub async fn next_due_time(&mut self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let request = tonic::Request::new(grpc::smm::posts::GetNextDueTimeRequest {});
+        let response = self
+            .posts_client
+            .get_next_due_time(request)
+            .await?
+            .into_inner()
+            .next_due_time
+            .and_then(|d| chrono::DateTime::from_timestamp(d.seconds, d.nanos as u32));
+        info!("Next due time: {response:?}");
+        Ok(response)
+    }
+
+    #[instrument(name = "watch posts", skip(self))]
+    pub async fn watch_posts(
+        &mut self,
+        author_tg_id: Option<i64>,
+        statuses: Vec<Status>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<PostEvent>> + Send>>> {
+        let request = tonic::Request::new(grpc::smm::posts::WatchPostsRequest {
+            author_tg_id,
+            statuses: statuses.into_iter().map(|s| s.into()).collect(),
+        });
+        let events = self
+            .posts_client
+            .watch_posts(request)
+            .await?
+            .into_inner()
+            .map(|event| event?.try_into().map_err(Error::invalid_response));
+        info!("watching posts");
+        Ok(Box::pin(events))
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[instrument(name = "create new post", skip(self))]
     pub async fn create_post(
         &mut self,
@@ -235,29 +496,159 @@ impl Client {
             .await?
             .into_inner()
             .created_post
-            .and_then(|p| p.try_into().ok())
-            .ok_or(anyhow!("Error creating post"))?;
+            .ok_or(Error::InvalidResponse(String::from(
+                "created post is missing",
+            )))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
         info!("Created post:\n{response:#?}");
         Ok(response)
     }
 
+    // Перемещает пост в корзину от имени пользователя с заданным идентификатором в Telegram
+    // и возвращает его
     #[instrument(name = "delete post", skip(self))]
-    pub async fn delete_post(&mut self, post_id: Uuid) -> Result<()> {
+    pub async fn delete_post(&mut self, post_id: Uuid, deleted_by: i64) -> Result<Post> {
         let request = tonic::Request::new(grpc::smm::posts::DeletePostRequest {
             post_id: post_id.into(),
+            deleted_by: Some(deleted_by),
+        });
+        let response = self.posts_client.delete_post(request).await?.into_inner();
+        info!("Delete post result: {success}", success = response.success);
+        if !response.success {
+            return Err(Error::Internal(String::from("post was not deleted")));
+        }
+        let post = response
+            .deleted_post
+            .ok_or(Error::InvalidResponse(String::from("no post in response")))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
+        Ok(post)
+    }
+
+    // Возвращает пост из корзины
+    #[instrument(name = "undelete post", skip(self))]
+    pub async fn undelete_post(&mut self, post_id: Uuid) -> Result<Post> {
+        let request = tonic::Request::new(grpc::smm::posts::UndeletePostRequest {
+            post_id: post_id.into(),
+        });
+        let post = self
+            .posts_client
+            .undelete_post(request)
+            .await?
+            .into_inner()
+            .post
+            .ok_or(Error::InvalidResponse(String::from("no post in response")))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
+        info!("Post restored from trash");
+        Ok(post)
+    }
+
+    // Страница истории правок поста, от новых к старым
+    #[instrument(name = "list post revisions", skip(self))]
+    pub async fn list_post_revisions(
+        &mut self,
+        post_id: Uuid,
+        page_token: Option<String>,
+    ) -> Result<RevisionsPage> {
+        let request = tonic::Request::new(grpc::smm::posts::ListPostRevisionsRequest {
+            post_id: post_id.into(),
+            page_size: 5,
+            page_token,
         });
         let response = self
             .posts_client
-            .delete_post(request)
+            .list_post_revisions(request)
+            .await?
+            .into_inner();
+        let next_page_token = Some(response.next_page_token).filter(|t| !t.is_empty());
+        let revisions = response
+            .revisions
+            .into_iter()
+            .map(|r| r.try_into())
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(Error::invalid_response)?;
+        info!(
+            "Revisions: {count}, has next page: {has_next}",
+            count = revisions.len(),
+            has_next = next_page_token.is_some()
+        );
+        Ok(RevisionsPage {
+            revisions,
+            next_page_token,
+        })
+    }
+
+    // Возвращает пост к содержимому до правки от имени пользователя с заданным
+    // идентификатором в Telegram
+    #[instrument(name = "restore post revision", skip(self))]
+    pub async fn restore_post_revision(
+        &mut self,
+        revision_id: Uuid,
+        edited_by: i64,
+    ) -> Result<Post> {
+        let request = tonic::Request::new(grpc::smm::posts::RestorePostRevisionRequest {
+            revision_id: revision_id.into(),
+            edited_by: Some(edited_by),
+        });
+        let post = self
+            .posts_client
+            .restore_post_revision(request)
             .await?
             .into_inner()
-            .success;
-        info!("Delete post result: {response}");
-        if response {
-            Ok(())
-        } else {
-            Err(anyhow!("Error deleting post"))
-        }
+            .post
+            .ok_or(Error::InvalidResponse(String::from("no post in response")))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
+        info!("Post restored to revision");
+        Ok(post)
+    }
+
+    // Отправляет пост на проверку от имени пользователя с заданным идентификатором в Telegram
+    #[instrument(name = "submit post", skip(self))]
+    pub async fn submit_post(&mut self, post_id: Uuid, submitted_by: i64) -> Result<Post> {
+        let request = tonic::Request::new(grpc::smm::posts::SubmitPostRequest {
+            post_id: post_id.into(),
+            submitted_by: Some(submitted_by),
+        });
+        let post = self
+            .posts_client
+            .submit_post(request)
+            .await?
+            .into_inner()
+            .post
+            .ok_or(Error::InvalidResponse(String::from("no post in response")))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
+        info!("Post submitted for review");
+        Ok(post)
+    }
+
+    // Одобряет или отклоняет пост от имени администратора. Возвращает пост и
+    // идентификатор автора в Telegram (None, если автор не найден)
+    #[instrument(name = "review post", skip(self))]
+    pub async fn review_post(
+        &mut self,
+        post_id: Uuid,
+        reviewed_by: i64,
+        approved: bool,
+        comment: Option<String>,
+    ) -> Result<(Post, Option<i64>)> {
+        let request = tonic::Request::new(grpc::smm::posts::ReviewPostRequest {
+            post_id: post_id.into(),
+            reviewed_by,
+            approved,
+            comment,
+        });
+        let response = self.posts_client.review_post(request).await?.into_inner();
+        let post = response
+            .post
+            .ok_or(Error::InvalidResponse(String::from("no post in response")))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
+        info!("Post reviewed, approved: {approved}");
+        Ok((post, response.author.map(|a| a.telegram_id)))
     }
 
     #[instrument(name = "get post", skip(self))]
@@ -271,60 +662,229 @@ impl Client {
             .await?
             .into_inner()
             .post
-            .and_then(|p| p.try_into().ok());
+            .map(|p| p.try_into())
+            .transpose()
+            .map_err(Error::invalid_response)?;
         if let Some(founded) = response.as_ref() {
             info!("Found post:\n{founded:#?}");
         }
         Ok(response)
     }
 
+    #[instrument(name = "claim post", skip(self))]
+    pub async fn claim_post(
+        &mut self,
+        post_id: Uuid,
+        owner: String,
+        lease_seconds: u32,
+    ) -> Result<Option<Post>> {
+        let request = tonic::Request::new(grpc::smm::posts::ClaimPostRequest {
+            post_id: post_id.into(),
+            owner,
+            lease_seconds,
+        });
+        let response = self
+            .posts_client
+            .claim_post(request)
+            .await?
+            .into_inner()
+            .post
+            .map(|p| p.try_into())
+            .transpose()
+            .map_err(Error::invalid_response)?;
+        info!("Claimed: {claimed}", claimed = response.is_some());
+        Ok(response)
+    }
+
+    #[instrument(name = "renew post", skip(self))]
+    pub async fn renew_post(
+        &mut self,
+        post_id: Uuid,
+        owner: String,
+        lease_seconds: u32,
+    ) -> Result<Option<Post>> {
+        let request = tonic::Request::new(grpc::smm::posts::RenewPostRequest {
+            post_id: post_id.into(),
+            owner,
+            lease_seconds,
+        });
+        let response = self
+            .posts_client
+            .renew_post(request)
+            .await?
+            .into_inner()
+            .post
+            .map(|p| p.try_into())
+            .transpose()
+            .map_err(Error::invalid_response)?;
+        Ok(response)
+    }
+
+    #[instrument(name = "release post", skip(self))]
+    pub async fn release_post(&mut self, post_id: Uuid, owner: String) -> Result<Option<Post>> {
+        let request = tonic::Request::new(grpc::smm::posts::ReleasePostRequest {
+            post_id: post_id.into(),
+            owner,
+        });
+        let response = self
+            .posts_client
+            .release_post(request)
+            .await?
+            .into_inner()
+            .post
+            .map(|p| p.try_into())
+            .transpose()
+            .map_err(Error::invalid_response)?;
+        Ok(response)
+    }
+
     #[instrument(name = "set post published", skip(self))]
-    pub async fn publish_now(&mut self, post_id: Uuid) -> Result<Option<Post>> {
-        let Some(mut existing) = self.get_post(post_id).await? else {
-            return Err(anyhow!("post not found"));
-        };
-        existing.publish_datetime = Some(chrono::Utc::now());
-        existing.status = Status::Published;
-        let request = tonic::Request::new(grpc::smm::posts::UpdatePostRequest {
-            updated_post: Some(existing.into()),
+    pub async fn complete_post(
+        &mut self,
+        post_id: Uuid,
+        owner: String,
+        deliveries: Vec<Delivery>,
+    ) -> Result<Option<Post>> {
+        let request = tonic::Request::new(grpc::smm::posts::CompletePostRequest {
+            post_id: post_id.into(),
+            owner,
+            deliveries: deliveries.into_iter().map(|d| d.into()).collect(),
         });
         let response = self
             .posts_client
-            .update_post(request)
+            .complete_post(request)
             .await?
             .into_inner()
-            .updated_post
-            .and_then(|p| p.try_into().ok());
+            .post
+            .map(|p| p.try_into())
+            .transpose()
+            .map_err(Error::invalid_response)?;
         if let Some(updated) = response.as_ref() {
             info!("Updated post:\n{updated:#?}");
         }
         Ok(response)
     }
 
-    #[instrument(name = "set post publish date", skip(self))]
-    pub async fn set_publish_date(
+    #[instrument(name = "record failed publish attempt", skip(self))]
+    pub async fn fail_post(
         &mut self,
         post_id: Uuid,
-        publish_date: chrono::DateTime<chrono::Utc>,
+        owner: String,
+        error: String,
+        retry_at: Option<chrono::DateTime<chrono::Utc>>,
+        deliveries: Vec<Delivery>,
     ) -> Result<Option<Post>> {
-        let Some(mut existing) = self.get_post(post_id).await? else {
-            return Err(anyhow!("post not found"));
-        };
-        existing.publish_datetime = Some(publish_date);
-        existing.status = Status::Pending;
-        let request = tonic::Request::new(grpc::smm::posts::UpdatePostRequest {
-            updated_post: Some(existing.into()),
+        let retry_at = retry_at.map(|d| {
+            let st: std::time::SystemTime = d.into();
+            st.into()
+        });
+        let request = tonic::Request::new(grpc::smm::posts::FailPostRequest {
+            post_id: post_id.into(),
+            owner,
+            error,
+            retry_at,
+            deliveries: deliveries.into_iter().map(|d| d.into()).collect(),
         });
         let response = self
             .posts_client
-            .update_post(request)
+            .fail_post(request)
             .await?
             .into_inner()
-            .updated_post
-            .and_then(|p| p.try_into().ok());
+            .post
+            .map(|p| p.try_into())
+            .transpose()
+            .map_err(Error::invalid_response)?;
         if let Some(updated) = response.as_ref() {
             info!("Updated post:\n{updated:#?}");
         }
         Ok(response)
     }
+
+    // Планирует публикацию поста от имени пользователя с заданным
+    // идентификатором в Telegram
+    #[instrument(name = "schedule post", skip(self))]
+    pub async fn schedule_post(
+        &mut self,
+        post_id: Uuid,
+        publish_date: chrono::DateTime<chrono::Utc>,
+        edited_by: i64,
+    ) -> Result<Post> {
+        let st: std::time::SystemTime = publish_date.into();
+        let request = tonic::Request::new(grpc::smm::posts::SchedulePostRequest {
+            post_id: post_id.into(),
+            publish_datetime: Some(st.into()),
+            edited_by: Some(edited_by),
+        });
+        let post = self
+            .posts_client
+            .schedule_post(request)
+            .await?
+            .into_inner()
+            .post
+            .ok_or(Error::InvalidResponse(String::from("no post in response")))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
+        info!("Scheduled post:\n{post:#?}");
+        Ok(post)
+    }
+
+    // Снимает пост с очереди публикации, возвращая его в черновики
+    #[instrument(name = "unschedule post", skip(self))]
+    pub async fn unschedule_post(&mut self, post_id: Uuid, edited_by: i64) -> Result<Post> {
+        let request = tonic::Request::new(grpc::smm::posts::UnschedulePostRequest {
+            post_id: post_id.into(),
+            edited_by: Some(edited_by),
+        });
+        let post = self
+            .posts_client
+            .unschedule_post(request)
+            .await?
+            .into_inner()
+            .post
+            .ok_or(Error::InvalidResponse(String::from("no post in response")))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
+        info!("Post unscheduled");
+        Ok(post)
+    }
+
+    // Отменяет пост
+    #[instrument(name = "abandon post", skip(self))]
+    pub async fn abandon_post(&mut self, post_id: Uuid, edited_by: i64) -> Result<Post> {
+        let request = tonic::Request::new(grpc::smm::posts::AbandonPostRequest {
+            post_id: post_id.into(),
+            edited_by: Some(edited_by),
+        });
+        let post = self
+            .posts_client
+            .abandon_post(request)
+            .await?
+            .into_inner()
+            .post
+            .ok_or(Error::InvalidResponse(String::from("no post in response")))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
+        info!("Post abandoned");
+        Ok(post)
+    }
+
+    // Возвращает отмененный пост в черновики
+    #[instrument(name = "restore post", skip(self))]
+    pub async fn restore_post(&mut self, post_id: Uuid, edited_by: i64) -> Result<Post> {
+        let request = tonic::Request::new(grpc::smm::posts::RestorePostRequest {
+            post_id: post_id.into(),
+            edited_by: Some(edited_by),
+        });
+        let post = self
+            .posts_client
+            .restore_post(request)
+            .await?
+            .into_inner()
+            .post
+            .ok_or(Error::InvalidResponse(String::from("no post in response")))?
+            .try_into()
+            .map_err(Error::invalid_response)?;
+        info!("Post restored to drafts");
+        Ok(post)
+    }
 }
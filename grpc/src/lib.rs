@@ -36,6 +36,7 @@ pub mod smm {
                     created_at: pc,
                     updated_at: pu,
                     last_activity: pl,
+                    version: value.version,
                 }
             }
         }
@@ -51,7 +52,8 @@ pub mod smm {
                     .username(value.username)
                     .try_role(role)
                     .map_err(|e| anyhow!("{e}"))?
-                    .language_code(value.language_code);
+                    .language_code(value.language_code)
+                    .version(value.version);
                 if let Some(c) = value.created_at {
                     b.created_at(c.seconds, c.nanos);
                 }
@@ -72,6 +74,10 @@ pub mod smm {
                     total_count: value.total_count,
                     current_page: value.current_page,
                     total_pages: value.total_pages,
+                    next_page_token: value
+                        .next_page_token
+                        .map(|t| t.to_string())
+                        .unwrap_or_default(),
                 }
             }
         }
@@ -104,6 +110,12 @@ pub mod smm {
                 let pc = Some(sc.into());
                 let sp: Option<std::time::SystemTime> = value.publish_datetime.map(|d| d.into());
                 let pp = sp.map(|d| d.into());
+                let sl: Option<std::time::SystemTime> = value.lease_expires_at.map(|d| d.into());
+                let pl = sl.map(|d| d.into());
+                let sn: Option<std::time::SystemTime> = value.next_attempt_at.map(|d| d.into());
+                let pn = sn.map(|d| d.into());
+                let sd: Option<std::time::SystemTime> = value.deleted_at.map(|d| d.into());
+                let pd = sd.map(|d| d.into());
                 Post {
                     id: value.id.to_string(),
                     title: value.title,
@@ -116,6 +128,16 @@ pub mod smm {
                     created_at: pc,
                     publish_datetime: pp,
                     author_id: value.author_id.to_string(),
+                    lease_owner: value.lease_owner,
+                    lease_expires_at: pl,
+                    attempts: value.attempts,
+                    last_error: value.last_error,
+                    next_attempt_at: pn,
+                    deliveries: value.deliveries.into_iter().map(Delivery::from).collect(),
+                    deleted_at: pd,
+                    deleted_by: value.deleted_by,
+                    version: value.version,
+                    review_comment: value.review_comment,
                 }
             }
         }
@@ -132,6 +154,18 @@ pub mod smm {
                     .publish_datetime
                     .as_ref()
                     .and_then(|d| chrono::DateTime::from_timestamp(d.seconds, d.nanos as u32));
+                let lease_expires_at = value
+                    .lease_expires_at
+                    .as_ref()
+                    .and_then(|d| chrono::DateTime::from_timestamp(d.seconds, d.nanos as u32));
+                let next_attempt_at = value
+                    .next_attempt_at
+                    .as_ref()
+                    .and_then(|d| chrono::DateTime::from_timestamp(d.seconds, d.nanos as u32));
+                let deleted_at = value
+                    .deleted_at
+                    .as_ref()
+                    .and_then(|d| chrono::DateTime::from_timestamp(d.seconds, d.nanos as u32));
                 b.try_id(value.id)?
                     .title(value.title)
                     .content(value.content)
@@ -142,11 +176,81 @@ pub mod smm {
                     .publish_datetime(pdt)
                     .created_at(created)
                     .try_status(value.status)?
-                    .try_author_id(value.author_id)?;
+                    .try_author_id(value.author_id)?
+                    .lease_owner(value.lease_owner)
+                    .lease_expires_at(lease_expires_at)
+                    .attempts(value.attempts)
+                    .last_error(value.last_error)
+                    .next_attempt_at(next_attempt_at)
+                    .deleted_at(deleted_at)
+                    .deleted_by(value.deleted_by)
+                    .version(value.version)
+                    .review_comment(value.review_comment)
+                    .deliveries(
+                        value
+                            .deliveries
+                            .into_iter()
+                            .map(|d| d.try_into())
+                            .collect::<anyhow::Result<Vec<_>>>()?,
+                    );
                 let p = b.build()?;
                 Ok(p)
             }
         }
+        impl From<shared::models::Delivery> for Delivery {
+            fn from(value: shared::models::Delivery) -> Self {
+                let sd: Option<std::time::SystemTime> = value.delivered_at.map(|d| d.into());
+                let pd = sd.map(|d| d.into());
+                Delivery {
+                    platform: value.platform.into(),
+                    target_id: value.target_id,
+                    remote_id: value.remote_id,
+                    permalink: value.permalink,
+                    delivered_at: pd,
+                    error: value.error,
+                }
+            }
+        }
+        impl TryFrom<Delivery> for shared::models::Delivery {
+            type Error = anyhow::Error;
+            fn try_from(value: Delivery) -> Result<Self, Self::Error> {
+                let delivered_at = value
+                    .delivered_at
+                    .as_ref()
+                    .and_then(|d| chrono::DateTime::from_timestamp(d.seconds, d.nanos as u32));
+                Ok(shared::models::Delivery {
+                    platform: value.platform.try_into()?,
+                    target_id: value.target_id,
+                    remote_id: value.remote_id,
+                    permalink: value.permalink,
+                    delivered_at,
+                    error: value.error,
+                })
+            }
+        }
+        impl From<shared::models::PostEvent> for PostEvent {
+            fn from(value: shared::models::PostEvent) -> Self {
+                PostEvent {
+                    kind: value.kind.into(),
+                    post: Some(value.post.into()),
+                    previous_status: value.previous_status.map(|s| s.into()),
+                }
+            }
+        }
+        impl TryFrom<PostEvent> for shared::models::PostEvent {
+            type Error = anyhow::Error;
+            fn try_from(value: PostEvent) -> Result<Self, Self::Error> {
+                let post = value
+                    .post
+                    .ok_or(anyhow::anyhow!("event without post"))?
+                    .try_into()?;
+                Ok(shared::models::PostEvent {
+                    kind: value.kind.try_into()?,
+                    post,
+                    previous_status: value.previous_status.map(|s| s.try_into()).transpose()?,
+                })
+            }
+        }
         impl From<shared::models::ListPostsResult> for ListPostsResponse {
             fn from(value: shared::models::ListPostsResult) -> Self {
                 ListPostsResponse {
@@ -154,9 +258,177 @@ pub mod smm {
                     total_count: value.total_count,
                     current_page: value.current_page,
                     total_pages: value.total_pages,
+                    next_page_token: value
+                        .next_page_token
+                        .map(|t| t.to_string())
+                        .unwrap_or_default(),
+                    authors: std::collections::HashMap::new(),
+                }
+            }
+        }
+        impl From<shared::models::SearchPostsResult> for SearchPostsResponse {
+            fn from(value: shared::models::SearchPostsResult) -> Self {
+                SearchPostsResponse {
+                    posts: value.posts.into_iter().map(Post::from).collect(),
+                    next_page_token: value
+                        .next_page_token
+                        .map(|t| t.to_string())
+                        .unwrap_or_default(),
+                    authors: std::collections::HashMap::new(),
+                }
+            }
+        }
+        impl From<shared::models::PostContent> for PostContent {
+            fn from(value: shared::models::PostContent) -> Self {
+                let sp: Option<std::time::SystemTime> = value.publish_datetime.map(|d| d.into());
+                let pp = sp.map(|d| d.into());
+                PostContent {
+                    title: value.title,
+                    content: value.content,
+                    tg_photo_file_id: value.tg_photo_file_id,
+                    vk_photo_file_id: value.vk_photo_file_id,
+                    tg_video_file_id: value.tg_video_file_id,
+                    vk_video_file_id: value.vk_video_file_id,
+                    status: value.status.into(),
+                    publish_datetime: pp,
+                }
+            }
+        }
+        impl TryFrom<PostContent> for shared::models::PostContent {
+            type Error = anyhow::Error;
+            fn try_from(value: PostContent) -> Result<Self, Self::Error> {
+                let publish_datetime = value
+                    .publish_datetime
+                    .as_ref()
+                    .and_then(|d| chrono::DateTime::from_timestamp(d.seconds, d.nanos as u32));
+                Ok(shared::models::PostContent {
+                    title: value.title,
+                    content: value.content,
+                    tg_photo_file_id: value.tg_photo_file_id,
+                    vk_photo_file_id: value.vk_photo_file_id,
+                    tg_video_file_id: value.tg_video_file_id,
+                    vk_video_file_id: value.vk_video_file_id,
+                    status: value.status.try_into()?,
+                    publish_datetime,
+                })
+            }
+        }
+        impl From<shared::models::PostRevision> for PostRevision {
+            fn from(value: shared::models::PostRevision) -> Self {
+                let sc: std::time::SystemTime = value.created_at.into();
+                let changed_fields = value.changes().into_iter().map(i32::from).collect();
+                PostRevision {
+                    id: value.id.to_string(),
+                    post_id: value.post_id.to_string(),
+                    edited_by: value.edited_by,
+                    created_at: Some(sc.into()),
+                    before: Some(value.before.into()),
+                    after: Some(value.after.into()),
+                    changed_fields,
+                }
+            }
+        }
+        impl TryFrom<PostRevision> for shared::models::PostRevision {
+            type Error = anyhow::Error;
+            fn try_from(value: PostRevision) -> Result<Self, Self::Error> {
+                let created_at = value
+                    .created_at
+                    .as_ref()
+                    .and_then(|d| chrono::DateTime::from_timestamp(d.seconds, d.nanos as u32))
+                    .ok_or(anyhow::anyhow!("revision without creation time"))?;
+                Ok(shared::models::PostRevision {
+                    id: value.id.parse()?,
+                    post_id: value.post_id.parse()?,
+                    edited_by: value.edited_by,
+                    created_at,
+                    before: value
+                        .before
+                        .ok_or(anyhow::anyhow!("revision without previous content"))?
+                        .try_into()?,
+                    after: value
+                        .after
+                        .ok_or(anyhow::anyhow!("revision without new content"))?
+                        .try_into()?,
+                })
+            }
+        }
+        impl From<shared::models::ListPostRevisionsResult> for ListPostRevisionsResponse {
+            fn from(value: shared::models::ListPostRevisionsResult) -> Self {
+                ListPostRevisionsResponse {
+                    revisions: value
+                        .revisions
+                        .into_iter()
+                        .map(PostRevision::from)
+                        .collect(),
+                    next_page_token: value
+                        .next_page_token
+                        .map(|t| t.to_string())
+                        .unwrap_or_default(),
+                }
+            }
+        }
+        impl From<shared::models::User> for PostAuthor {
+            fn from(value: shared::models::User) -> Self {
+                PostAuthor {
+                    id: value.id.to_string(),
+                    telegram_id: value.telegram_id,
+                    name: value.full_name(),
+                    username: value.username,
                 }
             }
         }
     }
     pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("smm_descriptor");
 }
+// Домен и причины ошибок (google.rpc.ErrorInfo), общие для сервера и клиента
+pub mod errors {
+    pub const DOMAIN: &str = "smmaster";
+
+    pub const NOT_FOUND: &str = "NOT_FOUND";
+    pub const ALREADY_EXISTS: &str = "ALREADY_EXISTS";
+    pub const INVALID_ARGUMENT: &str = "INVALID_ARGUMENT";
+    pub const INVALID_TRANSITION: &str = "INVALID_TRANSITION";
+    pub const UNAUTHENTICATED: &str = "UNAUTHENTICATED";
+    pub const PERMISSION_DENIED: &str = "PERMISSION_DENIED";
+    pub const CONFLICT: &str = "CONFLICT";
+    pub const REVIEW_REQUIRED: &str = "REVIEW_REQUIRED";
+    pub const STORAGE: &str = "STORAGE";
+
+    // Ключи метаданных ErrorInfo
+    pub const RESOURCE: &str = "resource";
+    pub const FROM: &str = "from";
+    pub const TO: &str = "to";
+}
+// Подписанные метаданные пользователя, от имени которого выполняется запрос
+pub mod caller {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    // Telegram ID пользователя
+    pub const USER: &str = "x-smm-user";
+    // Время подписи в секундах Unix
+    pub const SIGNED_AT: &str = "x-smm-signed-at";
+    // HMAC-SHA256 от Telegram ID пользователя и времени подписи в hex
+    pub const SIGNATURE: &str = "x-smm-signature";
+    // Допустимое расхождение времени подписи и часов сервера: перехваченная
+    // подпись перестает действовать по его истечении
+    pub const MAX_AGE_SECONDS: i64 = 300;
+
+    fn mac(key: &[u8], telegram_id: i64, signed_at: i64) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+        mac.update(format!("{telegram_id}:{signed_at}").as_bytes());
+        mac
+    }
+    pub fn sign(key: &[u8], telegram_id: i64, signed_at: i64) -> String {
+        hex::encode(mac(key, telegram_id, signed_at).finalize().into_bytes())
+    }
+    // Проверяет подпись за постоянное время
+    pub fn verify(key: &[u8], telegram_id: i64, signed_at: i64, signature: &str) -> bool {
+        hex::decode(signature)
+            .is_ok_and(|s| mac(key, telegram_id, signed_at).verify_slice(&s).is_ok())
+    }
+    // Подпись сделана не раньше и не позже допустимого расхождения
+    pub fn is_fresh(signed_at: i64, now: i64) -> bool {
+        (now - signed_at).abs() <= MAX_AGE_SECONDS
+    }
+}
@@ -1,100 +1,307 @@
-use anyhow::Result;
-use shared::models::Post;
-use teloxide::{
-    prelude::*,
-    types::{ChatId, FileId, InputFile},
-};
+mod platform;
+pub use platform::{Capabilities, MediaKind, Platform, TelegramPlatform, VkPlatform};
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Result, anyhow};
+use shared::models::{self, Delivery, Post, Status};
+use tokio::sync::Notify;
+use tokio_stream::StreamExt;
+
+const LEASE_SECONDS: u32 = 60;
+// Захват продлевается заранее, пока идет доставка
+const LEASE_RENEW_INTERVAL: Duration = Duration::from_secs(LEASE_SECONDS as u64 / 3);
+// Максимальное время ожидания, если о новых постах не сообщили
+const MAX_IDLE: Duration = Duration::from_secs(60);
+// Минимальная пауза между проходами, чтобы не нагружать сервер
+const MIN_IDLE: Duration = Duration::from_secs(1);
+// Пауза после ошибки обращения к серверу
+const ERROR_IDLE: Duration = Duration::from_secs(10);
+const MAX_ATTEMPTS: u32 = 5;
+const RETRY_BASE_SECONDS: i64 = 30;
+// Повторы запросов к серверу при временных ошибках
+const RPC_RETRIES: u32 = 3;
+const RPC_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+// Пробуждает публикатор, когда очередь постов изменилась
+#[derive(Clone, Default)]
+pub struct Waker(Arc<Notify>);
+impl Waker {
+    pub fn wake(&self) {
+        self.0.notify_one();
+    }
+    async fn woken(&self) {
+        self.0.notified().await;
+    }
+}
 
 #[derive(Clone)]
 pub struct Publisher {
-    tg: teloxide::Bot,
-    tg_channel: ChatId,
+    id: String,
     rpc_client: client::Client,
-    vk_client: vk::VKClient,
+    platforms: Vec<Arc<dyn Platform>>,
+    waker: Waker,
 }
 impl Publisher {
-    pub fn new(
-        bot: teloxide::Bot,
-        tg_channel_id: i64,
-        rpc_client: client::Client,
-        vk_client: vk::VKClient,
-    ) -> Self {
-        let tg_channel = ChatId(tg_channel_id);
+    pub fn new(rpc_client: client::Client) -> Self {
         Self {
-            tg: bot,
-            tg_channel,
+            id: uuid::Uuid::new_v4().to_string(),
             rpc_client,
-            vk_client,
+            platforms: Vec::new(),
+            waker: Waker::default(),
         }
     }
+    pub fn waker(&self) -> Waker {
+        self.waker.clone()
+    }
+    pub fn with_platform(mut self, platform: impl Platform + 'static) -> Self {
+        self.platforms.push(Arc::new(platform));
+        self
+    }
     pub async fn run(self) {
+        tokio::spawn(self.clone().watch());
         let mut p = self.clone();
         loop {
             if let Err(e) = p.process().await {
                 tracing::error!("Error running publisher: {e:?}");
             }
-            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+            let idle = match p.rpc_client.next_due_time().await {
+                Ok(Some(due)) => (due - chrono::Utc::now())
+                    .to_std()
+                    .unwrap_or_default()
+                    .clamp(MIN_IDLE, MAX_IDLE),
+                Ok(None) => MAX_IDLE,
+                Err(e) => {
+                    tracing::error!("Error getting next due time: {e:?}");
+                    ERROR_IDLE
+                }
+            };
+            tracing::debug!("Sleeping for {idle:?}");
+            tokio::select! {
+                _ = tokio::time::sleep(idle) => {}
+                _ = p.waker.woken() => {}
+            }
+        }
+    }
+    // Пробуждает публикатор при изменении постов в очереди на сервере
+    async fn watch(mut self) {
+        loop {
+            match self
+                .rpc_client
+                .watch_posts(None, vec![Status::Pending])
+                .await
+            {
+                Ok(mut events) => {
+                    while let Some(event) = events.next().await {
+                        match event {
+                            Ok(event) => {
+                                tracing::debug!("Post {id} changed", id = event.post.id);
+                                self.waker.wake();
+                            }
+                            Err(e) => {
+                                tracing::error!("Error watching posts: {e:?}");
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("Error subscribing to posts: {e:?}"),
+            }
+            tokio::time::sleep(ERROR_IDLE).await;
         }
     }
     async fn publish(&self, post: Post) -> Result<()> {
         let mut client = self.rpc_client.clone();
-        if let Some(post) = client.publish_now(post.id).await? {
-            // VK
-            self.vk_client.publish(&post).await?;
-
-            // Telegram
-            if let Some(photo) = post.tg_photo_file_id {
-                let photo = InputFile::file_id(FileId::from(photo));
-                self.tg
-                    .send_photo(self.tg_channel, photo)
-                    .caption(post.content)
-                    .await?;
-            } else if let Some(video) = post.tg_video_file_id {
-                let video = InputFile::file_id(FileId::from(video));
-                self.tg
-                    .send_video(self.tg_channel, video)
-                    .caption(post.content)
-                    .await?;
-            } else {
-                self.tg.send_message(self.tg_channel, post.content).await?;
+        let Some(post) = client
+            .claim_post(post.id, self.id.clone(), LEASE_SECONDS)
+            .await?
+        else {
+            tracing::info!("Post {id} is already claimed", id = post.id);
+            return Ok(());
+        };
+        // Пока идет доставка, захват продлевается: медленная загрузка не должна
+        // отдать пост другому публикатору. Потерянный захват прерывает доставку
+        let deliveries = tokio::select! {
+            deliveries = self.deliver(&post) => deliveries,
+            () = self.keep_lease(post.id) => {
+                return Err(anyhow!("lease on post {id} was lost during delivery", id = post.id));
             }
+        };
+        let errors = deliveries
+            .iter()
+            .filter_map(|d| {
+                d.error
+                    .as_ref()
+                    .map(|e| format!("{platform}: {e}", platform = d.platform))
+            })
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            let error = errors.join("\n");
+            let attempt = post.attempts + 1;
+            let retry_at = (attempt < MAX_ATTEMPTS).then(|| {
+                let backoff = RETRY_BASE_SECONDS * 2_i64.pow(post.attempts);
+                chrono::Utc::now() + chrono::Duration::seconds(backoff)
+            });
+            tracing::warn!(
+                "Attempt {attempt}/{MAX_ATTEMPTS} to publish {id} failed, retry at {retry_at:?}",
+                id = post.id
+            );
+            let failed = retry(|| {
+                let mut client = client.clone();
+                let (owner, error, deliveries) =
+                    (self.id.clone(), error.clone(), deliveries.clone());
+                async move {
+                    client
+                        .fail_post(post.id, owner, error, retry_at, deliveries)
+                        .await
+                }
+            })
+            .await?;
+            if failed.is_none() {
+                tracing::error!(
+                    "Lease on post {id} was lost, failed attempt is not recorded",
+                    id = post.id
+                );
+            }
+            return Err(anyhow!(error));
+        }
+        // Пост уже доставлен: результат нужно записать, иначе после истечения захвата он уйдет повторно
+        let completed = retry(|| {
+            let mut client = client.clone();
+            let (owner, deliveries) = (self.id.clone(), deliveries.clone());
+            async move { client.complete_post(post.id, owner, deliveries).await }
+        })
+        .await?;
+        if completed.is_none() {
+            tracing::error!(
+                "Lease on post {id} was lost before completion, it may be published again",
+                id = post.id
+            );
+            return Err(anyhow!("lease on post {id} was lost", id = post.id));
         }
         Ok(())
     }
-    async fn process(&mut self) -> Result<()> {
-        let mut authors = Vec::new();
-        let mut page = 1;
+    // Продлевает захват поста и возвращается, только если захват потерян
+    async fn keep_lease(&self, id: uuid::Uuid) {
+        let mut client = self.rpc_client.clone();
+        let mut interval = tokio::time::interval(LEASE_RENEW_INTERVAL);
+        // Первый тик срабатывает сразу, а пост только что захвачен
+        interval.tick().await;
         loop {
-            let (current_authors, has_next) = self.rpc_client.list_users(page).await?;
-            authors.extend(current_authors);
-            if has_next {
-                page += 1;
-            } else {
-                break;
+            interval.tick().await;
+            match client.renew_post(id, self.id.clone(), LEASE_SECONDS).await {
+                Ok(Some(_)) => tracing::debug!("Lease on post {id} renewed"),
+                Ok(None) => {
+                    tracing::error!("Lease on post {id} was lost during delivery");
+                    return;
+                }
+                // Захват еще действует: следующая попытка будет до его истечения
+                Err(e) => tracing::warn!("Error renewing lease on post {id}: {e:?}"),
             }
         }
-        for author in authors {
-            let mut page = 1;
-            let mut pendings = Vec::new();
-            loop {
-                let (current_pendings, has_next) =
-                    self.rpc_client.pending(author.telegram_id, page).await?;
-                pendings.extend(current_pendings);
-                if has_next {
-                    page += 1;
-                } else {
-                    break;
-                }
+    }
+    // Загружает медиафайл на площадки, которым он нужен до публикации, и
+    // возвращает идентификаторы по площадкам. Ошибка одной площадки не мешает
+    // остальным: пост уйдет туда без медиа
+    pub async fn prepare_media(
+        &self,
+        kind: MediaKind,
+        file_path: &str,
+    ) -> Vec<(models::Platform, String)> {
+        let mut prepared = Vec::new();
+        for platform in self.platforms.iter() {
+            match platform.prepare_media(kind, file_path.to_string()).await {
+                Ok(Some(id)) => prepared.push((platform.kind(), id)),
+                Ok(None) => {}
+                Err(e) => tracing::error!(
+                    "Error preparing media for {platform}: {e:?}",
+                    platform = platform.kind()
+                ),
             }
-            for pending in pendings {
-                if let Some(pd) = pending.publish_datetime {
-                    let now = chrono::Utc::now();
-                    if pd <= now {
-                        self.publish(pending).await?;
-                    }
-                }
+        }
+        prepared
+    }
+    // Изменяет пост на всех площадках, куда он был доставлен
+    pub async fn edit(&self, post: &Post) -> Result<()> {
+        for platform in self.platforms.iter() {
+            if let Some(delivery) = post.delivered_to(platform.kind())
+                && platform.capabilities().can_edit
+            {
+                platform.edit(delivery, post).await?;
+            }
+        }
+        Ok(())
+    }
+    // Удаляет пост со всех площадок, куда он был доставлен
+    pub async fn delete(&self, post: &Post) -> Result<()> {
+        for platform in self.platforms.iter() {
+            if let Some(delivery) = post.delivered_to(platform.kind()) {
+                platform.delete(delivery).await?;
+            }
+        }
+        Ok(())
+    }
+    // Доставляет пост на площадки, на которые он еще не был доставлен
+    async fn deliver(&self, post: &Post) -> Vec<Delivery> {
+        let mut deliveries = Vec::new();
+        for platform in self.platforms.iter() {
+            let delivery = match post.delivered_to(platform.kind()) {
+                Some(existing) => existing.clone(),
+                None => self
+                    .deliver_to(platform.as_ref(), post)
+                    .await
+                    .unwrap_or_else(|e| {
+                        Delivery::failed(platform.kind(), platform.target_id(), format!("{e:#}"))
+                    }),
+            };
+            deliveries.push(delivery);
+        }
+        deliveries
+    }
+    async fn deliver_to(&self, platform: &dyn Platform, post: &Post) -> Result<Delivery> {
+        let has_media = post.tg_photo_file_id.is_some()
+            || post.tg_video_file_id.is_some()
+            || post.vk_photo_file_id.is_some()
+            || post.vk_video_file_id.is_some();
+        let capabilities = platform.capabilities();
+        let limit = if has_media {
+            capabilities.max_caption_len
+        } else {
+            capabilities.max_text_len
+        };
+        let len = post.content.chars().count();
+        if len > limit {
+            return Err(anyhow!("content is too long: {len} > {limit}"));
+        }
+        platform.publish(post).await
+    }
+    async fn process(&mut self) -> Result<()> {
+        let due = self.rpc_client.due_posts().await?;
+        for post in due {
+            let id = post.id;
+            if let Err(e) = self.publish(post).await {
+                tracing::error!("Error publishing post {id}: {e:?}");
             }
         }
         Ok(())
     }
 }
+
+// Повторяет запрос к серверу, пока ошибка временная
+async fn retry<T, F, Fut>(mut request: F) -> client::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = client::Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Err(e) if e.is_transient() && attempt < RPC_RETRIES => {
+                attempt += 1;
+                tracing::warn!("Transient error, retry {attempt}/{RPC_RETRIES}: {e}");
+                tokio::time::sleep(RPC_RETRY_DELAY * attempt).await;
+            }
+            result => return result,
+        }
+    }
+}
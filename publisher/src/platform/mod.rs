@@ -0,0 +1,48 @@
+mod telegram;
+mod vk;
+
+pub use telegram::TelegramPlatform;
+pub use vk::VkPlatform;
+
+use anyhow::Result;
+use shared::models::{self, Delivery, Post};
+
+// Ограничения площадки публикации
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    // Максимальная длина текста поста без медиа
+    pub max_text_len: usize,
+    // Максимальная длина подписи к фото или видео
+    pub max_caption_len: usize,
+    // Поддерживается ли редактирование опубликованных записей
+    pub can_edit: bool,
+}
+
+// Тип медиафайла
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Photo,
+    Video,
+}
+
+// Площадка, на которую публикуются посты
+#[async_trait::async_trait]
+pub trait Platform: Send + Sync {
+    // Вид площадки, записывается в результаты доставки
+    fn kind(&self) -> models::Platform;
+    // Идентификатор канала или сообщества на площадке
+    fn target_id(&self) -> String;
+    // Ограничения площадки
+    fn capabilities(&self) -> Capabilities;
+    // Загружает локальный медиафайл на площадку и возвращает идентификатор для поста
+    // Площадки, использующие идентификаторы файлов из Telegram, возвращают None
+    async fn prepare_media(&self, _kind: MediaKind, _file_path: String) -> Result<Option<String>> {
+        Ok(None)
+    }
+    // Публикует пост и возвращает результат доставки
+    async fn publish(&self, post: &Post) -> Result<Delivery>;
+    // Изменяет ранее опубликованную запись
+    async fn edit(&self, delivery: &Delivery, post: &Post) -> Result<()>;
+    // Удаляет ранее опубликованную запись
+    async fn delete(&self, delivery: &Delivery) -> Result<()>;
+}
@@ -0,0 +1,91 @@
+use anyhow::{Result, anyhow};
+use shared::models::{self, Delivery, Post};
+use teloxide::{
+    prelude::*,
+    types::{ChatId, FileId, InputFile, MessageId},
+};
+
+use super::{Capabilities, Platform};
+
+#[derive(Clone)]
+pub struct TelegramPlatform {
+    bot: teloxide::Bot,
+    channel: ChatId,
+}
+impl TelegramPlatform {
+    pub fn new(bot: teloxide::Bot, channel_id: i64) -> Self {
+        Self {
+            bot,
+            channel: ChatId(channel_id),
+        }
+    }
+    fn message_id(delivery: &Delivery) -> Result<MessageId> {
+        let id = delivery
+            .remote_id
+            .as_ref()
+            .ok_or(anyhow!("no telegram message id"))?
+            .parse()?;
+        Ok(MessageId(id))
+    }
+}
+
+#[async_trait::async_trait]
+impl Platform for TelegramPlatform {
+    fn kind(&self) -> models::Platform {
+        models::Platform::Telegram
+    }
+    fn target_id(&self) -> String {
+        self.channel.to_string()
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_text_len: 4096,
+            max_caption_len: 1024,
+            can_edit: true,
+        }
+    }
+    async fn publish(&self, post: &Post) -> Result<Delivery> {
+        let message = if let Some(photo) = post.tg_photo_file_id.clone() {
+            let photo = InputFile::file_id(FileId::from(photo));
+            self.bot
+                .send_photo(self.channel, photo)
+                .caption(post.content.clone())
+                .await?
+        } else if let Some(video) = post.tg_video_file_id.clone() {
+            let video = InputFile::file_id(FileId::from(video));
+            self.bot
+                .send_video(self.channel, video)
+                .caption(post.content.clone())
+                .await?
+        } else {
+            self.bot
+                .send_message(self.channel, post.content.clone())
+                .await?
+        };
+        Ok(Delivery::delivered(
+            self.kind(),
+            self.target_id(),
+            message.id.0.to_string(),
+            message.url().map(|u| u.to_string()),
+        ))
+    }
+    async fn edit(&self, delivery: &Delivery, post: &Post) -> Result<()> {
+        let message_id = Self::message_id(delivery)?;
+        if post.tg_photo_file_id.is_some() || post.tg_video_file_id.is_some() {
+            self.bot
+                .edit_message_caption(self.channel, message_id)
+                .caption(post.content.clone())
+                .await?;
+        } else {
+            self.bot
+                .edit_message_text(self.channel, message_id, post.content.clone())
+                .await?;
+        }
+        Ok(())
+    }
+    async fn delete(&self, delivery: &Delivery) -> Result<()> {
+        let message_id = Self::message_id(delivery)?;
+        self.bot.delete_message(self.channel, message_id).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::{Result, anyhow};
+use shared::models::{self, Delivery, Post};
+
+use super::{Capabilities, MediaKind, Platform};
+
+#[derive(Clone)]
+pub struct VkPlatform {
+    client: vk::VKClient,
+}
+impl VkPlatform {
+    pub fn new(client: vk::VKClient) -> Self {
+        Self { client }
+    }
+    fn post_id(delivery: &Delivery) -> Result<i64> {
+        let id = delivery
+            .remote_id
+            .as_ref()
+            .ok_or(anyhow!("no vk post id"))?
+            .parse()?;
+        Ok(id)
+    }
+}
+
+#[async_trait::async_trait]
+impl Platform for VkPlatform {
+    fn kind(&self) -> models::Platform {
+        models::Platform::Vk
+    }
+    fn target_id(&self) -> String {
+        self.client.owner_id()
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_text_len: 16384,
+            max_caption_len: 16384,
+            can_edit: true,
+        }
+    }
+    async fn prepare_media(&self, kind: MediaKind, file_path: String) -> Result<Option<String>> {
+        let id = match kind {
+            MediaKind::Photo => self.client.get_photo_id(file_path).await?,
+            MediaKind::Video => self.client.get_video_id(file_path).await?,
+        };
+        Ok(Some(id))
+    }
+    async fn publish(&self, post: &Post) -> Result<Delivery> {
+        let response = self.client.publish(post).await?;
+        Ok(Delivery::delivered(
+            self.kind(),
+            self.target_id(),
+            response.post_id.to_string(),
+            Some(self.client.wall_url(response.post_id)),
+        ))
+    }
+    async fn edit(&self, delivery: &Delivery, post: &Post) -> Result<()> {
+        self.client.edit(Self::post_id(delivery)?, post).await
+    }
+    async fn delete(&self, delivery: &Delivery) -> Result<()> {
+        self.client.delete(Self::post_id(delivery)?).await
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use grpc::errors;
+use shared::models::Status;
+use tonic::Code;
+use tonic_types::{ErrorDetails, FieldViolation, StatusExt};
+
+// Ошибки обработчиков сервиса, отображаемые в коды gRPC
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    // Запрошенная сущность не найдена (пост, автор, пользователь)
+    #[error("{0} not found")]
+    NotFound(&'static str),
+    // Сущность уже существует
+    #[error("{0} already exists")]
+    AlreadyExists(&'static str),
+    // Некорректные поля запроса
+    #[error("invalid argument: {}", describe(.0))]
+    InvalidArgument(Vec<FieldViolation>),
+    // Недопустимый переход статуса поста
+    #[error("post status can not be changed from {from} to {to}")]
+    InvalidTransition { from: Status, to: Status },
+    // Неизвестный, отозванный или истекший API-ключ
+    #[error("unauthenticated: {0}")]
+    Unauthenticated(String),
+    // Недостаточно прав для операции
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    // Состояние сущности изменилось конкурентно
+    #[error("conflict: {0}")]
+    Conflict(String),
+    // Пост редактора нужно одобрить перед планированием публикации
+    #[error("post must be approved by an admin before it can be scheduled")]
+    ReviewRequired,
+    // Ошибка хранилища
+    #[error("storage error")]
+    Storage(#[from] anyhow::Error),
+}
+impl Error {
+    pub fn invalid_argument(field: &str, description: impl Into<String>) -> Self {
+        Self::InvalidArgument(vec![FieldViolation::new(field, description)])
+    }
+    // Переданная версия записи не совпадает с текущей
+    pub fn stale_version(sent: u32, current: u32) -> Self {
+        Self::Conflict(format!(
+            "version {sent} is stale, current version is {current}"
+        ))
+    }
+    fn code(&self) -> Code {
+        match self {
+            Self::NotFound(_) => Code::NotFound,
+            Self::AlreadyExists(_) => Code::AlreadyExists,
+            Self::InvalidArgument(_) => Code::InvalidArgument,
+            Self::InvalidTransition { .. } => Code::FailedPrecondition,
+            Self::Unauthenticated(_) => Code::Unauthenticated,
+            Self::PermissionDenied(_) => Code::PermissionDenied,
+            Self::Conflict(_) => Code::Aborted,
+            Self::ReviewRequired => Code::FailedPrecondition,
+            Self::Storage(_) => Code::Internal,
+        }
+    }
+    fn reason(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => errors::NOT_FOUND,
+            Self::AlreadyExists(_) => errors::ALREADY_EXISTS,
+            Self::InvalidArgument(_) => errors::INVALID_ARGUMENT,
+            Self::InvalidTransition { .. } => errors::INVALID_TRANSITION,
+            Self::Unauthenticated(_) => errors::UNAUTHENTICATED,
+            Self::PermissionDenied(_) => errors::PERMISSION_DENIED,
+            Self::Conflict(_) => errors::CONFLICT,
+            Self::ReviewRequired => errors::REVIEW_REQUIRED,
+            Self::Storage(_) => errors::STORAGE,
+        }
+    }
+    fn metadata(&self) -> HashMap<String, String> {
+        match self {
+            Self::NotFound(resource) | Self::AlreadyExists(resource) => {
+                HashMap::from([(errors::RESOURCE.into(), resource.to_string())])
+            }
+            Self::InvalidTransition { from, to } => HashMap::from([
+                (errors::FROM.into(), from.to_string()),
+                (errors::TO.into(), to.to_string()),
+            ]),
+            _ => HashMap::new(),
+        }
+    }
+}
+impl From<Error> for tonic::Status {
+    fn from(error: Error) -> Self {
+        if let Error::Storage(e) = &error {
+            tracing::error!("{e:?}");
+        }
+        let mut details =
+            ErrorDetails::with_error_info(error.reason(), errors::DOMAIN, error.metadata());
+        if let Error::InvalidArgument(violations) = &error {
+            details.set_bad_request(violations.clone());
+        }
+        tonic::Status::with_error_details(error.code(), error.to_string(), details)
+    }
+}
+
+fn describe(violations: &[FieldViolation]) -> String {
+    violations
+        .iter()
+        .map(|v| format!("{}: {}", v.field, v.description))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,59 @@
+use shared::models::{ApiKey, Scope};
+
+// Выпускает ключ и печатает его токен: токен показывается только один раз
+pub async fn issue(
+    db: &storage::Storage,
+    name: String,
+    scopes: Vec<Scope>,
+    expires_in_days: Option<u32>,
+) -> anyhow::Result<()> {
+    let expires_at = expires_in_days.map(|d| chrono::Utc::now() + chrono::Duration::days(d.into()));
+    let (key, token) = ApiKey::issue(name, scopes, expires_at);
+    let Some(key) = db.api_keys().create(&key).await? else {
+        anyhow::bail!("API key {name} already exists", name = key.name);
+    };
+    tracing::info!(message = "Issued API key", name = %key.name, scopes = %scopes_text(&key.scopes));
+    println!("{token}");
+    Ok(())
+}
+
+// Отзывает ключ; запросы с ним перестают приниматься сразу
+pub async fn revoke(db: &storage::Storage, name: &str) -> anyhow::Result<()> {
+    if db
+        .api_keys()
+        .revoke(name, chrono::Utc::now())
+        .await?
+        .is_none()
+    {
+        anyhow::bail!("active API key {name} not found");
+    }
+    tracing::info!(message = "Revoked API key", %name);
+    Ok(())
+}
+
+pub async fn list(db: &storage::Storage) -> anyhow::Result<()> {
+    let now = chrono::Utc::now();
+    for key in db.api_keys().list().await? {
+        let state = match (key.revoked_at, key.expires_at) {
+            (Some(revoked_at), _) => format!("revoked {revoked_at}"),
+            (None, Some(expires_at)) if expires_at <= now => format!("expired {expires_at}"),
+            (None, Some(expires_at)) => format!("expires {expires_at}"),
+            (None, None) => String::from("active"),
+        };
+        println!(
+            "{name}\t{scopes}\t{created}\t{state}",
+            name = key.name,
+            scopes = scopes_text(&key.scopes),
+            created = key.created_at
+        );
+    }
+    Ok(())
+}
+
+fn scopes_text(scopes: &[Scope]) -> String {
+    scopes
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -4,11 +4,19 @@ use grpc::smm::{
 };
 use posts::AppPostService;
 use users::AppUsersService;
+use validation::ValidationLayer;
 
+mod error;
+mod keys;
+mod policy;
 mod posts;
+mod trash;
 mod users;
+mod validation;
 
-use clap::Parser;
+pub use error::Error;
+
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "SMMaster server", version, about = "gRPC server for SMM telegram bot", long_about = None)]
@@ -16,22 +24,86 @@ struct Cli {
     /// Define port to serve
     #[arg(short, long)]
     port: Option<u16>,
-    /// MongoDB URI
+    /// Address to bind (defaults to ::1, use :: or 0.0.0.0 to accept remote clients)
+    #[arg(long)]
+    host: Option<std::net::IpAddr>,
+    /// PEM certificate chain to serve over TLS
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<std::path::PathBuf>,
+    /// PEM private key of the TLS certificate
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<std::path::PathBuf>,
+    /// PEM CA certificate to verify client certificates (mutual TLS)
+    #[arg(long, requires = "tls_cert")]
+    tls_client_ca: Option<std::path::PathBuf>,
+    /// Database URI: mongodb://host:port, sqlite://smm.db, postgres://user@host/db
+    /// or memory:// for in-process storage
     #[arg(short, long)]
     database: Option<String>,
-    /// Bearer token
+    /// Static bearer token with bot and publisher scopes, accepted along with
+    /// issued API keys
     #[arg(short, long)]
     bearer: Option<String>,
+    /// Secret for acting user signatures, shared only with the trusted bot.
+    /// Required to serve requests
+    #[arg(long)]
+    signing_key: Option<String>,
+    /// Days to keep deleted posts in the trash before purging them
+    #[arg(long)]
+    trash_retention_days: Option<u32>,
+    /// Require admin approval before editors can schedule posts
+    #[arg(long)]
+    require_review: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Apply pending database migrations and exit
+    Migrate,
+    /// Manage API keys and exit
+    Keys {
+        #[command(subcommand)]
+        command: KeysCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeysCommand {
+    /// Issue a new API key and print its token
+    Issue {
+        /// Unique key name
+        name: String,
+        /// Key scope: read-only, publisher, bot or admin
+        #[arg(short, long = "scope", required = true)]
+        scopes: Vec<shared::models::Scope>,
+        /// Days until the key expires (never by default)
+        #[arg(long)]
+        expires_in_days: Option<u32>,
+    },
+    /// Revoke an API key by name
+    Revoke {
+        /// Key name
+        name: String,
+    },
+    /// List issued API keys
+    List,
 }
 
 pub async fn run() -> anyhow::Result<()> {
     let cli = Cli::parse();
     let port = cli.port.unwrap_or(50052);
-    let mongo_db_uri = cli
+    let database_uri = cli
         .database
         .unwrap_or(String::from("mongodb://localhost:27017"));
-    let bearer = cli.bearer.unwrap_or("some-secret-token".into());
-    let addr = format!("[::1]:{port}");
+    let bearer = cli.bearer;
+    let signing_key = cli.signing_key;
+    let trash_retention = chrono::Duration::days(cli.trash_retention_days.unwrap_or(30).into());
+    let host = cli
+        .host
+        .unwrap_or(std::net::IpAddr::V6(std::net::Ipv6Addr::LOCALHOST));
+    let addr = std::net::SocketAddr::new(host, port);
     let subscriber = tracing_subscriber::fmt()
         .pretty()
         .with_file(true)
@@ -41,14 +113,48 @@ pub async fn run() -> anyhow::Result<()> {
         .finish();
 
     tracing::subscriber::set_global_default(subscriber)?;
-    tracing::info!(message = "Starting server", %addr);
-    let token: tonic::metadata::MetadataValue<_> = format!("Bearer {bearer}").parse()?;
+    if let Some(Command::Keys { command }) = cli.command {
+        let db = storage::Storage::new(&database_uri).await?;
+        return match command {
+            KeysCommand::Issue {
+                name,
+                scopes,
+                expires_in_days,
+            } => keys::issue(&db, name, scopes, expires_in_days).await,
+            KeysCommand::Revoke { name } => keys::revoke(&db, &name).await,
+            KeysCommand::List => keys::list(&db).await,
+        };
+    }
+    if let Some(Command::Migrate) = cli.command {
+        let applied = storage::Storage::migrate(&database_uri).await?;
+        if applied.is_empty() {
+            tracing::info!("database schema is up to date");
+        }
+        for migration in applied {
+            tracing::info!(message = "Applied migration", %migration);
+        }
+        return Ok(());
+    }
+    // Подпись пользователя проверяется отдельным секретом: токен клиента
+    // не дает права действовать от имени произвольного пользователя
+    let Some(signing_key) = signing_key else {
+        anyhow::bail!("--signing-key is required to serve requests");
+    };
+    tracing::info!(message = "Starting server", %addr, tls = cli.tls_cert.is_some());
+    if bearer.is_none() {
+        tracing::info!("no static bearer token, only issued API keys are accepted");
+    }
 
-    let check_auth = move |req: tonic::Request<()>| match req.metadata().get("authorization") {
-        Some(t) if token == t => Ok(req),
-        _ => Err(tonic::Status::unauthenticated("No valid auth token")),
+    // Токен подтверждает клиента, подписанные метаданные - пользователя,
+    // от имени которого выполняется запрос
+    let check_auth = move |mut req: tonic::Request<()>| {
+        let caller =
+            policy::Caller::from_metadata(req.metadata(), bearer.as_deref(), &signing_key)?;
+        req.extensions_mut().insert(caller);
+        Ok(req)
     };
-    let db = storage::Storage::new(&mongo_db_uri).await?;
+    let db = storage::Storage::new(&database_uri).await?;
+    trash::spawn_purge(db.clone(), trash_retention);
     let reflection_service_v1 = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(smm::FILE_DESCRIPTOR_SET)
         .build_v1()?;
@@ -57,14 +163,59 @@ pub async fn run() -> anyhow::Result<()> {
         .build_v1alpha()?;
     let users_service =
         UsersServiceServer::with_interceptor(AppUsersService::new(db.clone()), check_auth.clone());
-    let posts_service = PostsServiceServer::with_interceptor(AppPostService::new(db), check_auth);
-    tonic::transport::Server::builder()
+    let posts_service = PostsServiceServer::with_interceptor(
+        AppPostService::new(db, cli.require_review),
+        check_auth,
+    );
+    let mut server = tonic::transport::Server::builder();
+    if let Some(tls) = tls_config(
+        cli.tls_cert.as_deref(),
+        cli.tls_key.as_deref(),
+        cli.tls_client_ca.as_deref(),
+    )? {
+        server = server.tls_config(tls)?;
+    }
+    server
         .trace_fn(|_| tracing::info_span!("smm"))
+        .layer(ValidationLayer::new(smm::FILE_DESCRIPTOR_SET)?)
         .add_service(reflection_service_v1)
         .add_service(reflection_service_alpha)
         .add_service(users_service)
         .add_service(posts_service)
-        .serve(addr.parse()?)
+        .serve(addr)
         .await?;
     Ok(())
 }
+
+// Настройки TLS: сертификат сервера и, для взаимного TLS, CA сертификатов
+// клиентов. Без сертификата сервер принимает незашифрованные соединения
+fn tls_config(
+    cert: Option<&std::path::Path>,
+    key: Option<&std::path::Path>,
+    client_ca: Option<&std::path::Path>,
+) -> anyhow::Result<Option<tonic::transport::ServerTlsConfig>> {
+    let (Some(cert), Some(key)) = (cert, key) else {
+        return Ok(None);
+    };
+    let read = |path: &std::path::Path| {
+        std::fs::read(path).map_err(|e| anyhow::anyhow!("can not read {}: {e}", path.display()))
+    };
+    let identity = tonic::transport::Identity::from_pem(read(cert)?, read(key)?);
+    let mut tls = tonic::transport::ServerTlsConfig::new().identity(identity);
+    if let Some(client_ca) = client_ca {
+        tls = tls.client_ca_root(tonic::transport::Certificate::from_pem(read(client_ca)?));
+        tracing::info!("client certificates are required");
+    }
+    Ok(Some(tls))
+}
+
+// Разбирает необязательный токен страницы из запроса списка
+fn page_token(token: Option<&str>) -> Result<Option<shared::models::PageToken>, Error> {
+    token
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            t.parse()
+                .map_err(|_| Error::invalid_argument("page_token", "malformed page token"))
+        })
+        .transpose()
+}
@@ -0,0 +1,392 @@
+use grpc::caller;
+use shared::models::{ApiKey, Post, Role, Scope, User};
+use tonic::metadata::MetadataMap;
+
+use crate::Error;
+
+// Ключ, предъявленный клиентом
+#[derive(Debug, Clone)]
+enum Key {
+    // Токен из параметров запуска сервера
+    Static,
+    // Выпущенный API-ключ (хеш токена)
+    Issued(String),
+}
+
+// Учетные данные запроса, подтвержденные перехватчиком авторизации. Ключ
+// проверяется по хранилищу при определении прав
+#[derive(Debug, Clone)]
+pub struct Caller {
+    key: Key,
+    // Пользователь Telegram с подписанными метаданными
+    user: Option<i64>,
+}
+impl Caller {
+    // Определяет вызывающую сторону по метаданным запроса. Подпись пользователя
+    // проверяется ключом подписи сервера и отклоняется, если она устарела
+    pub fn from_metadata(
+        metadata: &MetadataMap,
+        bearer: Option<&str>,
+        signing_key: &str,
+    ) -> Result<Self, tonic::Status> {
+        let token = metadata
+            .get("authorization")
+            .and_then(|t| t.to_str().ok())
+            .and_then(|t| t.strip_prefix("Bearer "))
+            .ok_or(tonic::Status::unauthenticated("No valid auth token"))?;
+        let key = if bearer.is_some_and(|b| b == token) {
+            Key::Static
+        } else {
+            Key::Issued(ApiKey::hash(token))
+        };
+        let Some(user) = metadata.get(caller::USER) else {
+            return Ok(Self { key, user: None });
+        };
+        let telegram_id = user
+            .to_str()
+            .ok()
+            .and_then(|u| u.parse().ok())
+            .ok_or(tonic::Status::unauthenticated("malformed acting user"))?;
+        let signed_at = metadata
+            .get(caller::SIGNED_AT)
+            .and_then(|s| s.to_str().ok())
+            .and_then(|s| s.parse().ok())
+            .ok_or(tonic::Status::unauthenticated(
+                "missing acting user signature time",
+            ))?;
+        if !caller::is_fresh(signed_at, chrono::Utc::now().timestamp()) {
+            return Err(tonic::Status::unauthenticated(
+                "stale acting user signature",
+            ));
+        }
+        let signature = metadata
+            .get(caller::SIGNATURE)
+            .and_then(|s| s.to_str().ok())
+            .unwrap_or_default();
+        if !caller::verify(signing_key.as_bytes(), telegram_id, signed_at, signature) {
+            return Err(tonic::Status::unauthenticated(
+                "invalid acting user signature",
+            ));
+        }
+        Ok(Self {
+            key,
+            user: Some(telegram_id),
+        })
+    }
+}
+
+// Вызывающая сторона с правами ключа и ролью пользователя из хранилища.
+// Любой ключ без пользователя читает посты и пользователей; операции
+// публикатора требуют прав Publisher, а запросы от имени пользователя - прав
+// Bot. Изменения всегда приписываются пользователю, поэтому ключ без него,
+// даже с правами Admin, ничего не меняет, кроме состояния публикации
+#[derive(Debug, Clone)]
+pub enum Actor {
+    // Клиент без пользователя с правами своего ключа
+    Service(Vec<Scope>),
+    // Пользователь; None, если он еще не зарегистрирован
+    User {
+        telegram_id: i64,
+        user: Option<User>,
+    },
+}
+impl Actor {
+    pub async fn of<T>(db: &storage::Storage, request: &tonic::Request<T>) -> Result<Self, Error> {
+        let caller = request
+            .extensions()
+            .get::<Caller>()
+            .cloned()
+            .ok_or(Error::Unauthenticated(String::from("missing credentials")))?;
+        let scopes = match caller.key {
+            // Токен из параметров запуска работает как ключ бота со встроенным
+            // публикатором
+            Key::Static => vec![Scope::Bot, Scope::Publisher],
+            Key::Issued(token_hash) => {
+                db.api_keys()
+                    .get_by_hash(&token_hash)
+                    .await
+                    .map_err(Error::Storage)?
+                    .filter(|k| k.is_active(chrono::Utc::now()))
+                    .ok_or(Error::Unauthenticated(String::from(
+                        "unknown, expired or revoked API key",
+                    )))?
+                    .scopes
+            }
+        };
+        let Some(telegram_id) = caller.user else {
+            return Ok(Self::Service(scopes));
+        };
+        if !Scope::Bot.granted(&scopes) {
+            return Err(Error::PermissionDenied(String::from(
+                "API key can not act on behalf of users",
+            )));
+        }
+        let user = db.users().get(telegram_id).await.map_err(Error::Storage)?;
+        Ok(Self::User { telegram_id, user })
+    }
+    fn role(&self) -> Role {
+        match self {
+            Self::User { user: Some(u), .. } => u.role,
+            _ => Role::Guest,
+        }
+    }
+    // Чтение доступно любому ключу без пользователя и пользователям с ролью не ниже заданной
+    pub fn read(&self, role: Role) -> Result<(), Error> {
+        match self {
+            Self::Service(_) => Ok(()),
+            Self::User { .. } if self.role() >= role => Ok(()),
+            Self::User { .. } => Err(denied(role)),
+        }
+    }
+    // Изменения выполняет только зарегистрированный пользователь с ролью
+    // не ниже заданной
+    pub fn user(&self, role: Role) -> Result<&User, Error> {
+        match self {
+            Self::User { user: Some(u), .. } if u.role >= role => Ok(u),
+            Self::User { .. } => Err(denied(role)),
+            Self::Service(_) => Err(Error::PermissionDenied(String::from(
+                "acting user required",
+            ))),
+        }
+    }
+    // Операции публикатора доступны только ключу с правами публикатора и
+    // недоступны пользователям
+    pub fn service(&self) -> Result<(), Error> {
+        match self {
+            Self::Service(scopes) if Scope::Publisher.granted(scopes) => Ok(()),
+            Self::Service(_) => Err(Error::PermissionDenied(String::from(
+                "API key lacks the publisher scope",
+            ))),
+            Self::User { .. } => Err(Error::PermissionDenied(String::from(
+                "only the publisher can do this",
+            ))),
+        }
+    }
+    // Фильтр авторов для списков постов: ключи без пользователя и администраторы
+    // видят посты любых авторов, остальные - только свои
+    pub fn authors(&self, requested: Vec<i64>) -> Result<Vec<i64>, Error> {
+        match self {
+            Self::Service(_) => Ok(requested),
+            Self::User { .. } if self.role() == Role::Admin => Ok(requested),
+            Self::User { telegram_id, .. }
+                if !requested.is_empty() && requested.iter().all(|id| id == telegram_id) =>
+            {
+                Ok(vec![*telegram_id])
+            }
+            Self::User { .. } => Err(Error::PermissionDenied(String::from(
+                "only admins can list posts of other authors",
+            ))),
+        }
+    }
+    // Пост читают ключи без пользователя, администраторы и его автор
+    pub fn can_read(&self, post: &Post) -> Result<(), Error> {
+        match self {
+            Self::Service(_) => Ok(()),
+            Self::User { .. } if self.role() == Role::Admin => Ok(()),
+            Self::User { user: Some(u), .. }
+                if u.role >= Role::Editor && post.author_id == u.id =>
+            {
+                Ok(())
+            }
+            Self::User { .. } => Err(Error::PermissionDenied(String::from(
+                "editors may only read their own posts",
+            ))),
+        }
+    }
+    // Пользователь совпадает с вызывающим или является администратором
+    pub fn is_self_or_admin(&self, telegram_id: i64) -> bool {
+        match self {
+            Self::Service(_) => false,
+            Self::User {
+                telegram_id: id, ..
+            } => *id == telegram_id || self.role() == Role::Admin,
+        }
+    }
+}
+
+// Проверяет, что действие записывается на вызывающего пользователя, и
+// возвращает его Telegram ID
+pub fn attributed(user: &User, claimed: Option<i64>, field: &str) -> Result<Option<i64>, Error> {
+    match claimed {
+        Some(id) if id != user.telegram_id => Err(Error::PermissionDenied(format!(
+            "{field} does not match the acting user"
+        ))),
+        _ => Ok(Some(user.telegram_id)),
+    }
+}
+
+// Редактор меняет только свои посты, администратор - любые
+pub fn can_edit(user: &User, post: &Post) -> Result<(), Error> {
+    match user.role {
+        Role::Admin => Ok(()),
+        Role::Editor if post.author_id == user.id => Ok(()),
+        _ => Err(Error::PermissionDenied(String::from(
+            "editors may only change their own posts",
+        ))),
+    }
+}
+
+fn denied(role: Role) -> Error {
+    Error::PermissionDenied(format!("{role} role required"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::Storage;
+    use uuid::Uuid;
+
+    const KEY: &str = "signing key";
+
+    // Метаданные запроса от имени пользователя, подписанные в заданное время
+    fn signed(telegram_id: i64, signed_at: i64, key: &str) -> MetadataMap {
+        let mut metadata = MetadataMap::new();
+        metadata.insert("authorization", "Bearer token".parse().unwrap());
+        metadata.insert(caller::USER, telegram_id.into());
+        metadata.insert(caller::SIGNED_AT, signed_at.into());
+        let signature = caller::sign(key.as_bytes(), telegram_id, signed_at);
+        metadata.insert(caller::SIGNATURE, signature.parse().unwrap());
+        metadata
+    }
+
+    fn from_metadata(metadata: &MetadataMap) -> Result<Caller, tonic::Status> {
+        Caller::from_metadata(metadata, Some("token"), KEY)
+    }
+
+    #[test]
+    fn fresh_signature() {
+        let now = chrono::Utc::now().timestamp();
+        let caller = from_metadata(&signed(42, now, KEY)).unwrap();
+        assert_eq!(caller.user, Some(42));
+    }
+
+    #[test]
+    fn stale_signature() {
+        let now = chrono::Utc::now().timestamp();
+        for signed_at in [
+            now - caller::MAX_AGE_SECONDS - 60,
+            now + caller::MAX_AGE_SECONDS + 60,
+        ] {
+            let status = from_metadata(&signed(42, signed_at, KEY)).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        }
+    }
+
+    #[test]
+    fn forged_signature() {
+        let now = chrono::Utc::now().timestamp();
+        assert!(from_metadata(&signed(42, now, "other key")).is_err());
+        // Подпись другого времени не подходит
+        let mut metadata = signed(42, now, KEY);
+        metadata.insert(caller::SIGNED_AT, (now - 1).into());
+        assert!(from_metadata(&metadata).is_err());
+        // Без времени подписи пользователь не принимается
+        let mut metadata = signed(42, now, KEY);
+        metadata.remove(caller::SIGNED_AT);
+        assert!(from_metadata(&metadata).is_err());
+    }
+
+    // Права ключа с заданными правами, действующего от имени пользователя или без него
+    async fn actor(db: &Storage, scopes: &[Scope], user: Option<i64>) -> Result<Actor, Error> {
+        let (key, _) = ApiKey::issue(Uuid::new_v4().to_string(), scopes.to_vec(), None);
+        db.api_keys().create(&key).await.unwrap().unwrap();
+        let mut request = tonic::Request::new(());
+        request.extensions_mut().insert(Caller {
+            key: Key::Issued(key.token_hash),
+            user,
+        });
+        Actor::of(db, &request).await
+    }
+
+    async fn register(db: &Storage, telegram_id: i64, role: Role) -> User {
+        let user = User::builder()
+            .telegram_id(telegram_id)
+            .first_name("user")
+            .role(role)
+            .build()
+            .unwrap();
+        db.users().create(&user).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn service_scopes() {
+        let db = Storage::memory();
+        // (права, операции публикатора: ClaimPost, CompletePost)
+        let matrix = [
+            (Scope::ReadOnly, false),
+            (Scope::Publisher, true),
+            (Scope::Bot, false),
+            (Scope::Admin, true),
+        ];
+        for (scope, publisher) in matrix {
+            let actor = actor(&db, &[scope], None).await.unwrap();
+            // GetPost, ListPosts, GetUser
+            assert!(actor.read(Role::Editor).is_ok(), "{scope}");
+            assert!(actor.read(Role::Admin).is_ok(), "{scope}");
+            assert_eq!(actor.service().is_ok(), publisher, "{scope}");
+            // CreatePost, UpdatePost, UpdateUser без пользователя запрещены всем
+            assert!(actor.user(Role::Editor).is_err(), "{scope}");
+            assert!(actor.user(Role::Admin).is_err(), "{scope}");
+            assert!(actor.authors(vec![1, 2]).is_ok(), "{scope}");
+        }
+    }
+
+    #[tokio::test]
+    async fn acting_user_scopes() {
+        let db = Storage::memory();
+        register(&db, 1, Role::Editor).await;
+        // (права, запросы от имени пользователя)
+        let matrix = [
+            (Scope::ReadOnly, false),
+            (Scope::Publisher, false),
+            (Scope::Bot, true),
+            (Scope::Admin, true),
+        ];
+        for (scope, acting) in matrix {
+            let actor = actor(&db, &[scope], Some(1)).await;
+            assert_eq!(actor.is_ok(), acting, "{scope}");
+            if let Ok(actor) = actor {
+                assert!(actor.user(Role::Editor).is_ok(), "{scope}");
+                // Пользователь не выполняет операции публикатора
+                assert!(actor.service().is_err(), "{scope}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn user_roles() {
+        let db = Storage::memory();
+        let editor = register(&db, 1, Role::Editor).await;
+        let admin = register(&db, 2, Role::Admin).await;
+        register(&db, 3, Role::Guest).await;
+        let own = Post::builder()
+            .title("own")
+            .content("content")
+            .author_id(editor.id)
+            .build()
+            .unwrap();
+        let other = Post {
+            author_id: admin.id,
+            ..own.clone()
+        };
+        let editor = actor(&db, &[Scope::Bot], Some(1)).await.unwrap();
+        assert!(editor.user(Role::Editor).is_ok());
+        assert!(editor.user(Role::Admin).is_err());
+        assert!(editor.can_read(&own).is_ok());
+        assert!(editor.can_read(&other).is_err());
+        assert!(editor.authors(vec![1]).is_ok());
+        assert!(editor.authors(vec![2]).is_err());
+        assert!(editor.authors(Vec::new()).is_err());
+        let admin = actor(&db, &[Scope::Bot], Some(2)).await.unwrap();
+        assert!(admin.user(Role::Admin).is_ok());
+        assert!(admin.can_read(&own).is_ok());
+        assert!(admin.authors(Vec::new()).is_ok());
+        let guest = actor(&db, &[Scope::Bot], Some(3)).await.unwrap();
+        assert!(guest.read(Role::Editor).is_err());
+        assert!(guest.user(Role::Editor).is_err());
+        assert!(guest.can_read(&own).is_err());
+        // Незарегистрированный пользователь - гость
+        let unknown = actor(&db, &[Scope::Bot], Some(4)).await.unwrap();
+        assert!(unknown.read(Role::Editor).is_err());
+    }
+}
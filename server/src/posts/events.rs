@@ -0,0 +1,44 @@
+use shared::models::{Post, PostEvent, PostEventKind, Status};
+use tokio::sync::broadcast;
+
+const CAPACITY: usize = 256;
+
+// Рассылка событий изменения постов подписчикам WatchPosts
+#[derive(Clone, Debug)]
+pub struct PostEvents {
+    sender: broadcast::Sender<PostEvent>,
+}
+impl PostEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+    pub fn subscribe(&self) -> broadcast::Receiver<PostEvent> {
+        self.sender.subscribe()
+    }
+    pub fn created(&self, post: &Post) {
+        self.send(PostEventKind::Created, post.clone(), None);
+    }
+    pub fn changed(&self, previous_status: Option<Status>, post: &Post) {
+        match previous_status {
+            Some(previous) if previous != post.status => {
+                self.send(PostEventKind::StatusChanged, post.clone(), Some(previous))
+            }
+            _ => self.send(PostEventKind::Updated, post.clone(), None),
+        }
+    }
+    pub fn deleted(&self, post: Post) {
+        self.send(PostEventKind::Deleted, post, None);
+    }
+    fn send(&self, kind: PostEventKind, post: Post, previous_status: Option<Status>) {
+        let event = PostEvent {
+            kind,
+            post,
+            previous_status,
+        };
+        // Ошибка означает лишь отсутствие подписчиков
+        if self.sender.send(event).is_err() {
+            tracing::debug!("no post watchers");
+        }
+    }
+}
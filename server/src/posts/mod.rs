@@ -1,23 +1,254 @@
 use grpc::smm::posts::{
-    self, CreatePostRequest, CreatePostResponse, DeletePostRequest, DeletePostResponse,
-    GetPostRequest, GetPostResponse, ListPostsRequest, ListPostsResponse, UpdatePostRequest,
-    UpdatePostResponse,
+    self, AbandonPostRequest, AbandonPostResponse, ClaimPostRequest, ClaimPostResponse,
+    CompletePostRequest, CompletePostResponse, CreatePostRequest, CreatePostResponse,
+    DeletePostRequest, DeletePostResponse, FailPostRequest, FailPostResponse,
+    GetNextDueTimeRequest, GetNextDueTimeResponse, GetPostRequest, GetPostResponse,
+    ListDuePostsRequest, ListDuePostsResponse, ListPostRevisionsRequest, ListPostRevisionsResponse,
+    ListPostsRequest, ListPostsResponse, PostAuthor, PostEvent, ReleasePostRequest,
+    ReleasePostResponse, RenewPostRequest, RenewPostResponse, RestorePostRequest,
+    RestorePostResponse, RestorePostRevisionRequest, RestorePostRevisionResponse,
+    ReviewPostRequest, ReviewPostResponse, SchedulePostRequest, SchedulePostResponse,
+    SearchPostsRequest, SearchPostsResponse, SubmitPostRequest, SubmitPostResponse,
+    UndeletePostRequest, UndeletePostResponse, UnschedulePostRequest, UnschedulePostResponse,
+    UpdatePostRequest, UpdatePostResponse, WatchPostsRequest,
 };
+use std::{collections::HashMap, pin::Pin};
+use tokio_stream::{Stream, StreamExt, wrappers::BroadcastStream};
 use tonic::{Request, Response, Result};
 use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    Error,
+    policy::{self, Actor},
+};
+
+mod events;
+use events::PostEvents;
 
 #[derive(Debug)]
 pub struct AppPostService {
     db: storage::Storage,
+    events: PostEvents,
+    // Посты редакторов публикуются только после одобрения администратором
+    review_required: bool,
 }
 impl AppPostService {
-    pub fn new(db: storage::Storage) -> Self {
-        Self { db }
+    pub fn new(db: storage::Storage, review_required: bool) -> Self {
+        Self {
+            db,
+            events: PostEvents::new(),
+            review_required,
+        }
+    }
+    // Проверяет, что пользователь, выполняющий действие, зарегистрирован,
+    // и возвращает его
+    async fn check_user(&self, tg_id: Option<i64>) -> Result<Option<shared::models::User>> {
+        let Some(tg_id) = tg_id else {
+            return Ok(None);
+        };
+        let user = self
+            .db
+            .users()
+            .get(tg_id)
+            .await
+            .map_err(Error::Storage)?
+            .ok_or(Error::NotFound("user"))?;
+        Ok(Some(user))
+    }
+    // Заменяет пост, сохраняя правку в истории, и рассылает событие изменения
+    async fn replace(
+        &self,
+        post: &shared::models::Post,
+        edited_by: Option<i64>,
+    ) -> Result<shared::models::Post> {
+        let editor = self.check_user(edited_by).await?;
+        let editor_role = editor.as_ref().map(|u| u.role);
+        let current = self.current(post.id).await?;
+        // Редактор не меняет чужие посты и не передает свои другим авторам
+        if let Some(editor) = editor.as_ref() {
+            policy::can_edit(editor, &current)?;
+            policy::can_edit(editor, post)?;
+        }
+        // Обновление применяется только к текущей версии поста
+        if current.version != post.version {
+            return Err(Error::stale_version(post.version, current.version).into());
+        }
+        let previous_status = current.status;
+        // Правка содержимого одобренного, запланированного или неудавшегося
+        // поста редактором возвращает его в черновики: новое содержимое
+        // проходит проверку заново
+        let reviewed = |s| {
+            matches!(
+                s,
+                shared::models::Status::Approved
+                    | shared::models::Status::Pending
+                    | shared::models::Status::Failed
+            )
+        };
+        let mut post = post.clone();
+        if self.review_required
+            && editor_role.is_some_and(|r| r != shared::models::Role::Admin)
+            && reviewed(previous_status)
+            && reviewed(post.status)
+            && shared::models::PostContent::from(&current)
+                .changes(&shared::models::PostContent::from(&post))
+                .iter()
+                .any(|f| {
+                    matches!(
+                        f,
+                        shared::models::PostField::Title
+                            | shared::models::PostField::Content
+                            | shared::models::PostField::Media
+                    )
+                })
+        {
+            post.status = shared::models::Status::Draft;
+            post.publish_datetime = None;
+        }
+        let post = &post;
+        // Статус меняется только по таблице переходов; захват поста выполняется
+        // только через ClaimPost
+        if previous_status != post.status
+            && (post.status == shared::models::Status::Publishing
+                || !previous_status.can_transition_to(post.status))
+        {
+            return Err(Error::InvalidTransition {
+                from: previous_status,
+                to: post.status,
+            }
+            .into());
+        }
+        if post.status == shared::models::Status::Pending && post.publish_datetime.is_none() {
+            return Err(Error::invalid_argument(
+                "publish_datetime",
+                "required for scheduled posts",
+            )
+            .into());
+        }
+        // Решение по проверке принимает только администратор
+        if previous_status != post.status
+            && matches!(
+                post.status,
+                shared::models::Status::Approved | shared::models::Status::Rejected
+            )
+            && editor_role.is_some_and(|r| r != shared::models::Role::Admin)
+        {
+            return Err(
+                Error::PermissionDenied(String::from("only admins can review posts")).into(),
+            );
+        }
+        // Редактор планирует публикацию только одобренного поста (повтор
+        // неудавшейся публикации допускается)
+        if self.review_required
+            && previous_status != post.status
+            && post.status == shared::models::Status::Pending
+            && editor_role.is_some_and(|r| r != shared::models::Role::Admin)
+            && !matches!(
+                previous_status,
+                shared::models::Status::Approved | shared::models::Status::Failed
+            )
+        {
+            return Err(Error::ReviewRequired.into());
+        }
+        let updated = self
+            .db
+            .posts()
+            .update(post, edited_by)
+            .await
+            .map_err(Error::Storage)?;
+        let Some(updated) = updated else {
+            // Пост изменили или удалили между чтением и записью
+            let current = self.current(post.id).await?;
+            return Err(Error::stale_version(post.version, current.version).into());
+        };
+        self.events.changed(Some(previous_status), &updated);
+        Ok(updated)
+    }
+    // Переводит пост в новый статус по таблице переходов, применяя к нему
+    // сопутствующие изменения. Если задан исходный статус, пост должен быть в нем
+    async fn transition(
+        &self,
+        id: Uuid,
+        from: Option<shared::models::Status>,
+        to: shared::models::Status,
+        edited_by: Option<i64>,
+        change: impl FnOnce(&mut shared::models::Post),
+    ) -> Result<shared::models::Post> {
+        let mut post = self.current(id).await?;
+        if from.is_some_and(|f| f != post.status) || !post.status.can_transition_to(to) {
+            return Err(Error::InvalidTransition {
+                from: post.status,
+                to,
+            }
+            .into());
+        }
+        post.status = to;
+        change(&mut post);
+        self.replace(&post, edited_by).await
+    }
+    // Пост, не находящийся в корзине
+    async fn current(&self, id: Uuid) -> Result<shared::models::Post> {
+        let post = self
+            .db
+            .posts()
+            .get(id)
+            .await
+            .map_err(Error::Storage)?
+            .filter(|p| p.deleted_at.is_none())
+            .ok_or(Error::NotFound("post"))?;
+        Ok(post)
+    }
+    async fn status_of(&self, id: Uuid) -> Result<Option<shared::models::Status>> {
+        let status = self
+            .db
+            .posts()
+            .get(id)
+            .await
+            .map_err(Error::Storage)?
+            .map(|p| p.status);
+        Ok(status)
+    }
+    // UUID авторов по идентификаторам в Telegram, без повторов
+    async fn author_ids(&self, tg_ids: impl IntoIterator<Item = i64>) -> Result<Vec<Uuid>> {
+        let mut author_ids = Vec::new();
+        for tg_id in tg_ids {
+            let author_id = self
+                .db
+                .users()
+                .get(tg_id)
+                .await
+                .map_err(Error::Storage)?
+                .map(|u| u.id)
+                .ok_or(Error::NotFound("author"))?;
+            if !author_ids.contains(&author_id) {
+                author_ids.push(author_id);
+            }
+        }
+        Ok(author_ids)
+    }
+    // Авторы постов по UUID пользователя
+    async fn authors(&self, posts: &[shared::models::Post]) -> Result<HashMap<String, PostAuthor>> {
+        let mut ids = posts.iter().map(|p| p.author_id).collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        let authors = self
+            .db
+            .users()
+            .get_by_ids(&ids)
+            .await
+            .map_err(Error::Storage)?
+            .into_iter()
+            .map(|u| (u.id.to_string(), u.into()))
+            .collect();
+        Ok(authors)
     }
 }
 
 #[tonic::async_trait]
 impl posts::posts_service_server::PostsService for AppPostService {
+    type WatchPostsStream = Pin<Box<dyn Stream<Item = Result<PostEvent>> + Send>>;
+
     #[doc = " Создает новый пост"]
     #[instrument(name = "create post", skip(self))]
     async fn create_post(
@@ -25,26 +256,31 @@ impl posts::posts_service_server::PostsService for AppPostService {
         request: Request<CreatePostRequest>,
     ) -> Result<Response<CreatePostResponse>> {
         tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
         let post_to_create = request.into_inner();
+        // Редактор создает посты только от своего имени
+        if user.role != shared::models::Role::Admin {
+            policy::attributed(user, Some(post_to_create.author_tg_id), "author_tg_id")?;
+        }
         let author_id = self
             .db
             .users()
             .get(post_to_create.author_tg_id)
             .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?
-            .ok_or(tonic::Status::not_found("author not found"))?
+            .map_err(Error::Storage)?
+            .ok_or(Error::NotFound("author"))?
             .id;
         let post = post_to_create
             .convert(author_id.to_string())
-            .map_err(|e: anyhow::Error| {
-                tonic::Status::new(tonic::Code::InvalidArgument, e.to_string())
-            })?;
+            .map_err(|e: anyhow::Error| Error::invalid_argument("post", e.to_string()))?;
         let created_post = self
             .db
             .posts()
             .create(&post)
             .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?
+            .map_err(Error::Storage)?
+            .inspect(|p| self.events.created(p))
             .map(|p| p.into());
         tracing::debug!("sending response");
         Ok(Response::new(CreatePostResponse { created_post }))
@@ -57,18 +293,18 @@ impl posts::posts_service_server::PostsService for AppPostService {
         request: Request<GetPostRequest>,
     ) -> Result<tonic::Response<GetPostResponse>> {
         tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        actor.read(shared::models::Role::Editor)?;
         let id = request
             .into_inner()
             .post_id
             .parse()
-            .map_err(|_| tonic::Status::invalid_argument("wrong post id"))?;
-        let post = self
-            .db
-            .posts()
-            .get(id)
-            .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?
-            .map(|p| p.into());
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let post = self.db.posts().get(id).await.map_err(Error::Storage)?;
+        if let Some(post) = post.as_ref() {
+            actor.can_read(post)?;
+        }
+        let post = post.map(|p| p.into());
         tracing::debug!("sending response");
         Ok(Response::new(GetPostResponse { post }))
     }
@@ -80,32 +316,101 @@ impl posts::posts_service_server::PostsService for AppPostService {
         request: Request<ListPostsRequest>,
     ) -> Result<Response<ListPostsResponse>> {
         tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        actor.read(shared::models::Role::Editor)?;
         let l = request.into_inner();
-        let author_tg_id = l.author_tg_id;
-        if author_tg_id < 0 {
-            return Err(tonic::Status::invalid_argument("wrong tg user id"));
+        // Пустой фильтр авторов - посты всех авторов
+        let author_ids = self
+            .author_ids(
+                actor.authors(
+                    std::iter::once(l.author_tg_id)
+                        .filter(|id| *id > 0)
+                        .chain(l.author_tg_ids.iter().copied())
+                        .collect(),
+                )?,
+            )
+            .await?;
+        let sort_by = shared::models::PostSortField::try_from(l.sort_by)
+            .map_err(|e| Error::invalid_argument("sort_by", e.to_string()))?;
+        let page_token = crate::page_token(l.page_token.as_deref())?;
+        if let Some(token) = page_token.as_ref()
+            && !sort_by.accepts(token)
+        {
+            return Err(
+                Error::invalid_argument("page_token", "page token does not match sort_by").into(),
+            );
         }
-        let author_id = self
+        let query = shared::models::ListPostsQuery {
+            author_ids,
+            status: l.status_filter.and_then(|s| s.try_into().ok()),
+            sort_by,
+            descending: l.descending,
+            created_from: l.created_from.and_then(timestamp),
+            created_to: l.created_to.and_then(timestamp),
+            publish_from: l.publish_from.and_then(timestamp),
+            publish_to: l.publish_to.and_then(timestamp),
+            page: l.page.max(1),
+            page_size: l.page_size,
+            page_token,
+            deleted: l.deleted,
+        };
+        check_range(query.created_from, query.created_to, "created_to")?;
+        check_range(query.publish_from, query.publish_to, "publish_to")?;
+        let result = self
             .db
-            .users()
-            .get(author_tg_id)
+            .posts()
+            .list_posts(&query)
             .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?
-            .map(|u| u.id)
-            .ok_or(tonic::Status::not_found("author not found"))?;
-        let page = l.page;
-        let page_size = l.page_size;
-        let filter = l.status_filter.and_then(|s| s.try_into().ok());
-        if page == 0 || page_size < 10 || page_size > 100 {
-            return Err(tonic::Status::invalid_argument("wrong page or page_size"));
+            .map_err(Error::Storage)?;
+        let authors = self.authors(&result.posts).await?;
+        let resp = ListPostsResponse {
+            authors,
+            ..result.into()
+        };
+        tracing::debug!("sending response");
+        Ok(Response::new(resp))
+    }
+
+    #[doc = " Ищет посты по словам заголовка и содержимого"]
+    #[instrument(name = "search posts", skip(self))]
+    async fn search_posts(
+        &self,
+        request: Request<SearchPostsRequest>,
+    ) -> Result<Response<SearchPostsResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        actor.read(shared::models::Role::Editor)?;
+        let s = request.into_inner();
+        let page_token = crate::page_token(s.page_token.as_deref())?;
+        if let Some(token) = page_token.as_ref()
+            && !shared::models::PostSortField::CreatedAt.accepts(token)
+        {
+            return Err(Error::invalid_argument("page_token", "malformed page token").into());
         }
-        let resp = self
+        let query = shared::models::SearchPostsQuery {
+            author_ids: self.author_ids(actor.authors(s.author_tg_ids)?).await?,
+            status: s.status_filter.and_then(|s| s.try_into().ok()),
+            created_from: s.created_from.and_then(timestamp),
+            created_to: s.created_to.and_then(timestamp),
+            page_size: if s.page_size == 0 { 10 } else { s.page_size },
+            page_token,
+            text: s.query,
+        };
+        if query.terms().is_empty() {
+            return Err(Error::invalid_argument("query", "must contain at least one word").into());
+        }
+        check_range(query.created_from, query.created_to, "created_to")?;
+        let result = self
             .db
             .posts()
-            .list_posts(author_id, page, page_size, filter)
+            .search_posts(&query)
             .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?
-            .into();
+            .map_err(Error::Storage)?;
+        let authors = self.authors(&result.posts).await?;
+        let resp = SearchPostsResponse {
+            authors,
+            ..result.into()
+        };
         tracing::debug!("sending response");
         Ok(Response::new(resp))
     }
@@ -117,36 +422,654 @@ impl posts::posts_service_server::PostsService for AppPostService {
         request: Request<UpdatePostRequest>,
     ) -> Result<Response<UpdatePostResponse>> {
         tracing::info!("received request");
-        let post = request
-            .into_inner()
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
+        let u = request.into_inner();
+        let mut post: shared::models::Post = u
             .updated_post
-            .and_then(|p| p.try_into().ok())
-            .ok_or(tonic::Status::invalid_argument("post required"))?;
+            .ok_or(Error::invalid_argument("updated_post", "post required"))?
+            .try_into()
+            .map_err(|e: anyhow::Error| Error::invalid_argument("updated_post", e.to_string()))?;
+        let current = self.current(post.id).await?;
+        // Пост, захваченный публикатором, не меняется до завершения публикации
+        if current.status == shared::models::Status::Publishing {
+            return Err(Error::Conflict(String::from("post is being published")).into());
+        }
+        // Поля захвата, повторов и доставки ведет сервер, комментарий к проверке
+        // меняется только через ReviewPost
+        post.created_at = current.created_at;
+        post.lease_owner = current.lease_owner;
+        post.lease_expires_at = current.lease_expires_at;
+        post.attempts = current.attempts;
+        post.last_error = current.last_error;
+        post.next_attempt_at = current.next_attempt_at;
+        post.deliveries = current.deliveries;
+        post.deleted_by = current.deleted_by;
+        post.review_comment = current.review_comment;
         let updated_post = self
-            .db
-            .posts()
-            .update(&post)
-            .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?
-            .map(|p| p.into());
+            .replace(&post, policy::attributed(user, u.edited_by, "edited_by")?)
+            .await?
+            .into();
         tracing::debug!("sending response");
-        Ok(Response::new(UpdatePostResponse { updated_post }))
+        Ok(Response::new(UpdatePostResponse {
+            updated_post: Some(updated_post),
+        }))
     }
 
-    #[doc = " Удаляет пост"]
+    #[doc = " Перемещает пост в корзину"]
     #[instrument(name = "delete post", skip(self))]
     async fn delete_post(
         &self,
         request: Request<DeletePostRequest>,
     ) -> Result<Response<DeletePostResponse>> {
         tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
+        let d = request.into_inner();
+        let id = d
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let deleted_by = policy::attributed(user, d.deleted_by, "deleted_by")?;
+        let existing = self.current(id).await?;
+        policy::can_edit(user, &existing)?;
+        let deleted_post = self
+            .db
+            .posts()
+            .trash(id, deleted_by, chrono::Utc::now())
+            .await
+            .map_err(Error::Storage)?
+            .ok_or_else(|| match existing.status {
+                shared::models::Status::Publishing => {
+                    Error::Conflict(String::from("post is being published"))
+                }
+                _ => Error::NotFound("post"),
+            })?;
+        self.events.deleted(deleted_post.clone());
+        tracing::debug!("sending response");
+        Ok(Response::new(DeletePostResponse {
+            success: true,
+            deleted_post: Some(deleted_post.into()),
+        }))
+    }
+
+    #[doc = " Возвращает пост из корзины"]
+    #[instrument(name = "undelete post", skip(self))]
+    async fn undelete_post(
+        &self,
+        request: Request<UndeletePostRequest>,
+    ) -> Result<Response<UndeletePostResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
         let id = request
             .into_inner()
             .post_id
             .parse()
-            .map_err(|_| tonic::Status::invalid_argument("wrong post id"))?;
-        let success = self.db.posts().delete(id).await.is_ok();
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let trashed = self
+            .db
+            .posts()
+            .get(id)
+            .await
+            .map_err(Error::Storage)?
+            .ok_or(Error::NotFound("post"))?;
+        policy::can_edit(user, &trashed)?;
+        let post = self
+            .db
+            .posts()
+            .restore_deleted(id)
+            .await
+            .map_err(Error::Storage)?
+            .ok_or(Error::NotFound("post"))
+            .inspect(|p| self.events.changed(None, p))
+            .map(|p| p.into())?;
         tracing::debug!("sending response");
-        Ok(Response::new(DeletePostResponse { success }))
+        Ok(Response::new(UndeletePostResponse { post: Some(post) }))
+    }
+
+    #[doc = " Возвращает историю правок поста"]
+    #[instrument(name = "list post revisions", skip(self))]
+    async fn list_post_revisions(
+        &self,
+        request: Request<ListPostRevisionsRequest>,
+    ) -> Result<Response<ListPostRevisionsResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        actor.read(shared::models::Role::Editor)?;
+        let l = request.into_inner();
+        let post_id = l
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let page_token = crate::page_token(l.page_token.as_deref())?;
+        if let Some(token) = page_token.as_ref()
+            && !shared::models::PostSortField::CreatedAt.accepts(token)
+        {
+            return Err(Error::invalid_argument("page_token", "malformed page token").into());
+        }
+        // История поста в корзине остается доступной до его окончательного удаления
+        let post = self
+            .db
+            .posts()
+            .get(post_id)
+            .await
+            .map_err(Error::Storage)?
+            .ok_or(Error::NotFound("post"))?;
+        actor.can_read(&post)?;
+        let page_size = if l.page_size == 0 { 10 } else { l.page_size };
+        let resp: ListPostRevisionsResponse = self
+            .db
+            .posts()
+            .list_revisions(post_id, page_size, page_token.as_ref())
+            .await
+            .map_err(Error::Storage)?
+            .into();
+        tracing::debug!("sending response");
+        Ok(Response::new(resp))
+    }
+
+    #[doc = " Возвращает пост к содержимому до выбранной правки"]
+    #[instrument(name = "restore post revision", skip(self))]
+    async fn restore_post_revision(
+        &self,
+        request: Request<RestorePostRevisionRequest>,
+    ) -> Result<Response<RestorePostRevisionResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
+        let r = request.into_inner();
+        let revision_id = r
+            .revision_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("revision_id", "wrong revision id"))?;
+        let revision = self
+            .db
+            .posts()
+            .get_revision(revision_id)
+            .await
+            .map_err(Error::Storage)?
+            .ok_or(Error::NotFound("revision"))?;
+        let mut post = self.current(revision.post_id).await?;
+        // Пост, захваченный публикатором, не меняется до завершения публикации
+        if post.status == shared::models::Status::Publishing {
+            return Err(Error::Conflict(String::from("post is being published")).into());
+        }
+        revision.before.apply_to(&mut post);
+        let post = self
+            .replace(&post, policy::attributed(user, r.edited_by, "edited_by")?)
+            .await?
+            .into();
+        tracing::debug!("sending response");
+        Ok(Response::new(RestorePostRevisionResponse {
+            post: Some(post),
+        }))
+    }
+
+    #[doc = " Отправляет пост на проверку администратору"]
+    #[instrument(name = "submit post", skip(self))]
+    async fn submit_post(
+        &self,
+        request: Request<SubmitPostRequest>,
+    ) -> Result<Response<SubmitPostResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
+        let s = request.into_inner();
+        let id = s
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let post = self
+            .transition(
+                id,
+                None,
+                shared::models::Status::InReview,
+                policy::attributed(user, s.submitted_by, "submitted_by")?,
+                |p| p.review_comment = None,
+            )
+            .await?
+            .into();
+        tracing::debug!("sending response");
+        Ok(Response::new(SubmitPostResponse { post: Some(post) }))
+    }
+
+    #[doc = " Одобряет или отклоняет пост, отправленный на проверку"]
+    #[instrument(name = "review post", skip(self))]
+    async fn review_post(
+        &self,
+        request: Request<ReviewPostRequest>,
+    ) -> Result<Response<ReviewPostResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Admin)?;
+        let r = request.into_inner();
+        let id = r
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let reviewed_by = policy::attributed(user, Some(r.reviewed_by), "reviewed_by")?;
+        let status = if r.approved {
+            shared::models::Status::Approved
+        } else {
+            shared::models::Status::Rejected
+        };
+        let post = self
+            .transition(id, None, status, reviewed_by, |p| {
+                p.review_comment = r.comment
+            })
+            .await?;
+        let author = self
+            .db
+            .users()
+            .get_by_ids(&[post.author_id])
+            .await
+            .map_err(Error::Storage)?
+            .into_iter()
+            .next()
+            .map(|u| u.into());
+        tracing::debug!("sending response");
+        Ok(Response::new(ReviewPostResponse {
+            post: Some(post.into()),
+            author,
+        }))
+    }
+
+    #[doc = " Планирует публикацию поста на заданное время"]
+    #[instrument(name = "schedule post", skip(self))]
+    async fn schedule_post(
+        &self,
+        request: Request<SchedulePostRequest>,
+    ) -> Result<Response<SchedulePostResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
+        let s = request.into_inner();
+        let id = s
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let publish_datetime =
+            s.publish_datetime
+                .and_then(timestamp)
+                .ok_or(Error::invalid_argument(
+                    "publish_datetime",
+                    "wrong publish datetime",
+                ))?;
+        let post = self
+            .transition(
+                id,
+                None,
+                shared::models::Status::Pending,
+                policy::attributed(user, s.edited_by, "edited_by")?,
+                |p| {
+                    p.publish_datetime = Some(publish_datetime);
+                    p.attempts = 0;
+                    p.last_error = None;
+                    p.next_attempt_at = None;
+                },
+            )
+            .await?
+            .into();
+        tracing::debug!("sending response");
+        Ok(Response::new(SchedulePostResponse { post: Some(post) }))
+    }
+
+    #[doc = " Снимает пост с очереди публикации, возвращая его в черновики"]
+    #[instrument(name = "unschedule post", skip(self))]
+    async fn unschedule_post(
+        &self,
+        request: Request<UnschedulePostRequest>,
+    ) -> Result<Response<UnschedulePostResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
+        let u = request.into_inner();
+        let id = u
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        // В черновики возвращается только запланированный пост
+        let post = self
+            .transition(
+                id,
+                Some(shared::models::Status::Pending),
+                shared::models::Status::Draft,
+                policy::attributed(user, u.edited_by, "edited_by")?,
+                |p| p.publish_datetime = None,
+            )
+            .await?
+            .into();
+        tracing::debug!("sending response");
+        Ok(Response::new(UnschedulePostResponse { post: Some(post) }))
+    }
+
+    #[doc = " Отменяет пост"]
+    #[instrument(name = "abandon post", skip(self))]
+    async fn abandon_post(
+        &self,
+        request: Request<AbandonPostRequest>,
+    ) -> Result<Response<AbandonPostResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
+        let a = request.into_inner();
+        let id = a
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        // Опубликованный пост отменяют после снятия с площадок: доставки
+        // сбрасываются, и возобновленный пост публикуется заново
+        let unpublished = self.status_of(id).await? == Some(shared::models::Status::Published);
+        let post = self
+            .transition(
+                id,
+                None,
+                shared::models::Status::Abandoned,
+                policy::attributed(user, a.edited_by, "edited_by")?,
+                |p| {
+                    if unpublished {
+                        p.deliveries.clear();
+                    }
+                },
+            )
+            .await?
+            .into();
+        tracing::debug!("sending response");
+        Ok(Response::new(AbandonPostResponse { post: Some(post) }))
+    }
+
+    #[doc = " Возвращает отмененный пост в черновики"]
+    #[instrument(name = "restore post", skip(self))]
+    async fn restore_post(
+        &self,
+        request: Request<RestorePostRequest>,
+    ) -> Result<Response<RestorePostResponse>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        let user = actor.user(shared::models::Role::Editor)?;
+        let r = request.into_inner();
+        let id = r
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        // В черновики возвращается только отмененный пост
+        let post = self
+            .transition(
+                id,
+                Some(shared::models::Status::Abandoned),
+                shared::models::Status::Draft,
+                policy::attributed(user, r.edited_by, "edited_by")?,
+                |_| {},
+            )
+            .await?
+            .into();
+        tracing::debug!("sending response");
+        Ok(Response::new(RestorePostResponse { post: Some(post) }))
+    }
+
+    #[doc = " Возвращает посты всех авторов, время публикации которых наступило"]
+    #[instrument(name = "list due posts", skip(self))]
+    async fn list_due_posts(
+        &self,
+        request: Request<ListDuePostsRequest>,
+    ) -> Result<Response<ListDuePostsResponse>> {
+        tracing::info!("received request");
+        Actor::of(&self.db, &request).await?.service()?;
+        let due_before = request
+            .into_inner()
+            .due_before
+            .and_then(timestamp)
+            .unwrap_or(chrono::Utc::now());
+        let posts = self
+            .db
+            .posts()
+            .list_due_posts(due_before)
+            .await
+            .map_err(Error::Storage)?
+            .into_iter()
+            .map(|p| p.into())
+            .collect();
+        tracing::debug!("sending response");
+        Ok(Response::new(ListDuePostsResponse { posts }))
+    }
+
+    #[doc = " Возвращает ближайшее время, когда какой-либо пост станет готов к публикации"]
+    #[instrument(name = "get next due time", skip(self))]
+    async fn get_next_due_time(
+        &self,
+        request: Request<GetNextDueTimeRequest>,
+    ) -> Result<Response<GetNextDueTimeResponse>> {
+        tracing::info!("received request");
+        Actor::of(&self.db, &request).await?.service()?;
+        let next_due_time = self
+            .db
+            .posts()
+            .next_due_time()
+            .await
+            .map_err(Error::Storage)?
+            .map(|d| {
+                let sd: std::time::SystemTime = d.into();
+                sd.into()
+            });
+        tracing::debug!("sending response");
+        Ok(Response::new(GetNextDueTimeResponse { next_due_time }))
+    }
+
+    #[doc = " Атомарно захватывает ожидающий пост для публикации"]
+    #[instrument(name = "claim post", skip(self))]
+    async fn claim_post(
+        &self,
+        request: Request<ClaimPostRequest>,
+    ) -> Result<Response<ClaimPostResponse>> {
+        tracing::info!("received request");
+        Actor::of(&self.db, &request).await?.service()?;
+        let r = request.into_inner();
+        let id = r
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let lease_expires_at =
+            chrono::Utc::now() + chrono::Duration::seconds(r.lease_seconds.into());
+        let previous_status = self.status_of(id).await?;
+        let post = self
+            .db
+            .posts()
+            .claim(id, &r.owner, lease_expires_at)
+            .await
+            .map_err(Error::Storage)?
+            .inspect(|p| self.events.changed(previous_status, p))
+            .map(|p| p.into());
+        tracing::debug!("sending response");
+        Ok(Response::new(ClaimPostResponse { post }))
+    }
+
+    #[doc = " Продлевает захват поста публикатором, пока идет доставка"]
+    #[instrument(name = "renew post", skip(self))]
+    async fn renew_post(
+        &self,
+        request: Request<RenewPostRequest>,
+    ) -> Result<Response<RenewPostResponse>> {
+        tracing::info!("received request");
+        Actor::of(&self.db, &request).await?.service()?;
+        let r = request.into_inner();
+        let id = r
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let lease_expires_at =
+            chrono::Utc::now() + chrono::Duration::seconds(r.lease_seconds.into());
+        let post = self
+            .db
+            .posts()
+            .renew(id, &r.owner, lease_expires_at)
+            .await
+            .map_err(Error::Storage)?
+            .map(|p| p.into());
+        tracing::debug!("sending response");
+        Ok(Response::new(RenewPostResponse { post }))
+    }
+
+    #[doc = " Освобождает захваченный пост, возвращая его в очередь"]
+    #[instrument(name = "release post", skip(self))]
+    async fn release_post(
+        &self,
+        request: Request<ReleasePostRequest>,
+    ) -> Result<Response<ReleasePostResponse>> {
+        tracing::info!("received request");
+        Actor::of(&self.db, &request).await?.service()?;
+        let r = request.into_inner();
+        let id = r
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let previous_status = self.status_of(id).await?;
+        let post = self
+            .db
+            .posts()
+            .release(id, &r.owner)
+            .await
+            .map_err(Error::Storage)?
+            .inspect(|p| self.events.changed(previous_status, p))
+            .map(|p| p.into());
+        tracing::debug!("sending response");
+        Ok(Response::new(ReleasePostResponse { post }))
+    }
+
+    #[doc = " Отмечает захваченный пост опубликованным"]
+    #[instrument(name = "complete post", skip(self))]
+    async fn complete_post(
+        &self,
+        request: Request<CompletePostRequest>,
+    ) -> Result<Response<CompletePostResponse>> {
+        tracing::info!("received request");
+        Actor::of(&self.db, &request).await?.service()?;
+        let r = request.into_inner();
+        let id = r
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let deliveries = r
+            .deliveries
+            .into_iter()
+            .map(|d| d.try_into())
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| Error::invalid_argument("deliveries", e.to_string()))?;
+        let previous_status = self.status_of(id).await?;
+        let post = self
+            .db
+            .posts()
+            .complete(id, &r.owner, chrono::Utc::now(), &deliveries)
+            .await
+            .map_err(Error::Storage)?
+            .inspect(|p| self.events.changed(previous_status, p))
+            .map(|p| p.into());
+        tracing::debug!("sending response");
+        Ok(Response::new(CompletePostResponse { post }))
+    }
+
+    #[doc = " Записывает неудачную попытку публикации захваченного поста"]
+    #[instrument(name = "fail post", skip(self))]
+    async fn fail_post(
+        &self,
+        request: Request<FailPostRequest>,
+    ) -> Result<Response<FailPostResponse>> {
+        tracing::info!("received request");
+        Actor::of(&self.db, &request).await?.service()?;
+        let r = request.into_inner();
+        let id = r
+            .post_id
+            .parse()
+            .map_err(|_| Error::invalid_argument("post_id", "wrong post id"))?;
+        let retry_at = r.retry_at.and_then(timestamp);
+        let deliveries = r
+            .deliveries
+            .into_iter()
+            .map(|d| d.try_into())
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| Error::invalid_argument("deliveries", e.to_string()))?;
+        let previous_status = self.status_of(id).await?;
+        let post = self
+            .db
+            .posts()
+            .fail(id, &r.owner, &r.error, retry_at, &deliveries)
+            .await
+            .map_err(Error::Storage)?
+            .inspect(|p| self.events.changed(previous_status, p))
+            .map(|p| p.into());
+        tracing::debug!("sending response");
+        Ok(Response::new(FailPostResponse { post }))
+    }
+
+    #[doc = " Подписывается на изменения постов"]
+    #[instrument(name = "watch posts", skip(self))]
+    async fn watch_posts(
+        &self,
+        request: Request<WatchPostsRequest>,
+    ) -> Result<Response<Self::WatchPostsStream>> {
+        tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
+        actor.read(shared::models::Role::Editor)?;
+        let r = request.into_inner();
+        // Редактор следит только за своими постами и должен указать себя автором
+        let author_tg_id = actor
+            .authors(r.author_tg_id.into_iter().collect())?
+            .first()
+            .copied();
+        let author_id = match author_tg_id {
+            Some(author_tg_id) => Some(
+                self.db
+                    .users()
+                    .get(author_tg_id)
+                    .await
+                    .map_err(Error::Storage)?
+                    .ok_or(Error::NotFound("author"))?
+                    .id,
+            ),
+            None => None,
+        };
+        let statuses = r
+            .statuses
+            .into_iter()
+            .flat_map(shared::models::Status::try_from)
+            .collect::<Vec<_>>();
+        let stream = BroadcastStream::new(self.events.subscribe()).filter_map(move |event| {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::warn!("post watcher lagged: {e}");
+                    return None;
+                }
+            };
+            if author_id.is_some_and(|id| id != event.post.author_id) {
+                return None;
+            }
+            if !statuses.is_empty() && !statuses.contains(&event.post.status) {
+                return None;
+            }
+            Some(Ok(event.into()))
+        });
+        tracing::debug!("sending response");
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+fn timestamp(t: prost_types::Timestamp) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::from_timestamp(t.seconds, t.nanos as u32)
+}
+// Проверяет, что нижняя граница диапазона времени меньше верхней
+fn check_range(
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+    field: &str,
+) -> Result<(), Error> {
+    if let (Some(from), Some(to)) = (from, to)
+        && from >= to
+    {
+        return Err(Error::invalid_argument(
+            field,
+            "must be after the lower bound",
+        ));
     }
+    Ok(())
 }
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+// Период очистки корзины
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Периодически окончательно удаляет посты, пролежавшие в корзине дольше срока хранения
+pub fn spawn_purge(db: storage::Storage, retention: chrono::Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let deleted_before = chrono::Utc::now() - retention;
+            match db.posts().purge_deleted(deleted_before).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!(message = "Purged deleted posts", purged),
+                Err(e) => tracing::error!("failed to purge deleted posts: {e:?}"),
+            }
+        }
+    });
+}
@@ -1,6 +1,8 @@
 use grpc::smm::users;
 use tracing::instrument;
 
+use crate::{Error, policy::Actor};
+
 #[derive(Debug)]
 pub struct AppUsersService {
     db: storage::Storage,
@@ -21,27 +23,62 @@ impl users::users_service_server::UsersService for AppUsersService {
         request: tonic::Request<users::CreateUserRequest>,
     ) -> tonic::Result<tonic::Response<users::CreateUserResponse>> {
         tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
         let r = request.into_inner();
         let id = r.telegram_id;
-        let created_user = match self
-            .db
-            .users()
-            .get(id)
-            .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?
-        {
+        // Пользователь регистрирует только себя
+        if !actor.is_self_or_admin(id) {
+            return Err(Error::PermissionDenied(String::from(
+                "users may only register themselves",
+            ))
+            .into());
+        }
+        let created_user = match self.db.users().get(id).await.map_err(Error::Storage)? {
             Some(existing) => Some(existing.into()),
             None => {
-                let new_user: shared::models::User =
-                    r.clone().try_into().map_err(|e: anyhow::Error| {
-                        tonic::Status::new(tonic::Code::InvalidArgument, e.to_string())
-                    })?;
-                self.db
+                let mut new_user: shared::models::User = r
+                    .clone()
+                    .try_into()
+                    .map_err(|e: anyhow::Error| Error::invalid_argument("user", e.to_string()))?;
+                // Первый зарегистрированный пользователь становится администратором.
+                // Из одновременных регистраций назначение достается одной: хранилище
+                // закрепляет его атомарно
+                let admins = self
+                    .db
+                    .users()
+                    .list_users(1, 1, Some(shared::models::Role::Admin), true, None)
+                    .await
+                    .map_err(Error::Storage)?;
+                let first_admin = admins.users.is_empty()
+                    && self
+                        .db
+                        .users()
+                        .claim_first_admin(id)
+                        .await
+                        .map_err(Error::Storage)?;
+                if first_admin {
+                    new_user.role = shared::models::Role::Admin;
+                }
+                let created = self
+                    .db
                     .users()
                     .create(&new_user)
                     .await
-                    .map_err(|e| tonic::Status::internal(e.to_string()))?
-                    .map(|u| u.into())
+                    .map_err(Error::Storage)?;
+                match created {
+                    // Повторный запрос того же пользователя успел создать его
+                    // гостем: назначение уже закреплено за ним
+                    Some(mut user) if first_admin && user.role != new_user.role => {
+                        user.role = new_user.role;
+                        self.db
+                            .users()
+                            .update(&user)
+                            .await
+                            .map_err(Error::Storage)?
+                            .map(|u| u.into())
+                    }
+                    created => created.map(|u| u.into()),
+                }
             }
         };
 
@@ -58,14 +95,18 @@ impl users::users_service_server::UsersService for AppUsersService {
         request: tonic::Request<users::GetUserRequest>,
     ) -> tonic::Result<tonic::Response<users::GetUserResponse>> {
         tracing::info!("received request");
+        let actor = Actor::of(&self.db, &request).await?;
         let r = request.into_inner();
         let id = r.user_id;
+        if !actor.is_self_or_admin(id) {
+            actor.read(shared::models::Role::Editor)?;
+        }
         let user = self
             .db
             .users()
             .get(id)
             .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?
+            .map_err(Error::Storage)?
             .map(|u| u.into());
         tracing::debug!("sending response");
         Ok(tonic::Response::new(users::GetUserResponse { user }))
@@ -78,20 +119,21 @@ impl users::users_service_server::UsersService for AppUsersService {
         request: tonic::Request<users::ListUsersRequest>,
     ) -> tonic::Result<tonic::Response<users::ListUsersResponse>> {
         tracing::info!("received request");
+        Actor::of(&self.db, &request)
+            .await?
+            .read(shared::models::Role::Editor)?;
         let r = request.into_inner();
-        let page = r.page;
+        let page = r.page.max(1);
         let page_size = r.page_size;
-        if page == 0 || page_size < 10 || page_size > 100 {
-            return Err(tonic::Status::invalid_argument("wrong page or page_size"));
-        }
         let role = r.role_filter.and_then(|r| r.try_into().ok());
         let sort_by_created_asc = r.sort_by_created_asc();
+        let page_token = crate::page_token(r.page_token.as_deref())?;
         let res = self
             .db
             .users()
-            .list_users(page, page_size, role, sort_by_created_asc)
+            .list_users(page, page_size, role, sort_by_created_asc, page_token)
             .await
-            .map_err(|e| tonic::Status::internal(e.to_string()))?
+            .map_err(Error::Storage)?
             .into();
         tracing::debug!("sending response");
         Ok(tonic::Response::new(res))
@@ -104,23 +146,37 @@ impl users::users_service_server::UsersService for AppUsersService {
         request: tonic::Request<users::UpdateUserRequest>,
     ) -> tonic::Result<tonic::Response<users::UpdateUserResponse>> {
         tracing::info!("received request");
-        let updated_user = if let Some(update) = request
+        Actor::of(&self.db, &request)
+            .await?
+            .user(shared::models::Role::Admin)?;
+        let update: shared::models::User = request
             .into_inner()
             .updated_user
-            .and_then(|u| u.try_into().ok())
-        {
-            self.db
+            .ok_or(Error::invalid_argument("updated_user", "user required"))?
+            .try_into()
+            .map_err(|e: anyhow::Error| Error::invalid_argument("updated_user", e.to_string()))?;
+        let updated_user = self
+            .db
+            .users()
+            .update(&update)
+            .await
+            .map_err(Error::Storage)?;
+        let Some(updated_user) = updated_user else {
+            // Запись не обновлена: пользователя нет или передана устаревшая версия
+            let current = self
+                .db
                 .users()
-                .update(&update)
+                .get_by_ids(&[update.id])
                 .await
-                .map_err(|e| tonic::Status::internal(e.to_string()))?
-                .map(|u| u.into())
-        } else {
-            None
+                .map_err(Error::Storage)?
+                .into_iter()
+                .next()
+                .ok_or(Error::NotFound("user"))?;
+            return Err(Error::stale_version(update.version, current.version).into());
         };
         tracing::debug!("sending response");
         Ok(tonic::Response::new(users::UpdateUserResponse {
-            updated_user,
+            updated_user: Some(updated_user.into()),
         }))
     }
 
@@ -131,9 +187,20 @@ impl users::users_service_server::UsersService for AppUsersService {
         request: tonic::Request<users::DeleteUserRequest>,
     ) -> tonic::Result<tonic::Response<users::DeleteUserResponse>> {
         tracing::info!("received request");
+        Actor::of(&self.db, &request)
+            .await?
+            .user(shared::models::Role::Admin)?;
         let id = request.into_inner().user_id;
-        let success = self.db.users().delete(id).await.is_ok();
+        self.db
+            .users()
+            .get(id)
+            .await
+            .map_err(Error::Storage)?
+            .ok_or(Error::NotFound("user"))?;
+        self.db.users().delete(id).await.map_err(Error::Storage)?;
         tracing::debug!("sending response");
-        Ok(tonic::Response::new(users::DeleteUserResponse { success }))
+        Ok(tonic::Response::new(users::DeleteUserResponse {
+            success: true,
+        }))
     }
 }
@@ -0,0 +1,783 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use anyhow::anyhow;
+use http_body_util::{BodyExt, Full};
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, FieldDescriptor, MessageDescriptor, ReflectMessage, Value,
+};
+use regex::Regex;
+use tonic::body::Body;
+use tonic_types::FieldViolation;
+use tower::{Layer, Service};
+
+use crate::Error;
+
+const RULES_EXTENSION: &str = "validate.rules";
+const DISABLED_EXTENSION: &str = "validate.disabled";
+
+// Слой, проверяющий входящие запросы по правилам protoc-gen-validate из proto-файлов
+#[derive(Debug, Clone)]
+pub struct ValidationLayer {
+    rules: Arc<Rules>,
+}
+impl ValidationLayer {
+    pub fn new(file_descriptor_set: &[u8]) -> anyhow::Result<Self> {
+        let rules = Rules::new(file_descriptor_set)?;
+        Ok(Self {
+            rules: Arc::new(rules),
+        })
+    }
+}
+impl<S> Layer<S> for ValidationLayer {
+    type Service = Validation<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Validation {
+            rules: self.rules.clone(),
+            inner,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Validation<S> {
+    rules: Arc<Rules>,
+    inner: S,
+}
+impl<S> Service<http::Request<Body>> for Validation<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = std::pin::Pin<
+        Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>,
+    >;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let Some(input) = self.rules.methods.get(request.uri().path()).cloned() else {
+            return Box::pin(self.inner.call(request));
+        };
+        let rules = self.rules.clone();
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let bytes = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(status) => return Ok(status.into_http()),
+            };
+            if let Err(status) = rules.check(input, &bytes) {
+                tracing::debug!(message = "request rejected", %status);
+                return Ok(status.into_http());
+            }
+            let request = http::Request::from_parts(parts, Body::new(Full::new(bytes)));
+            inner.call(request).await
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Rules {
+    // Тип входного сообщения по пути gRPC-метода
+    methods: HashMap<String, MessageDescriptor>,
+    // Правила по полному имени поля
+    fields: HashMap<String, DynamicMessage>,
+    // Сообщения, для которых проверка отключена
+    disabled: HashSet<String>,
+    // Скомпилированные регулярные выражения из правил
+    patterns: HashMap<String, Regex>,
+}
+impl Rules {
+    fn new(file_descriptor_set: &[u8]) -> anyhow::Result<Self> {
+        let pool = DescriptorPool::decode(file_descriptor_set)?;
+        let rules_extension = pool
+            .get_extension_by_name(RULES_EXTENSION)
+            .ok_or(anyhow!("{RULES_EXTENSION} extension not found"))?;
+        let disabled_extension = pool.get_extension_by_name(DISABLED_EXTENSION);
+        let methods = pool
+            .services()
+            .flat_map(|s| s.methods().collect::<Vec<_>>())
+            .filter(|m| !m.is_client_streaming())
+            .map(|m| {
+                let path = format!("/{}/{}", m.parent_service().full_name(), m.name());
+                (path, m.input())
+            })
+            .collect();
+        let mut fields = HashMap::new();
+        let mut disabled = HashSet::new();
+        let mut patterns = HashMap::new();
+        for message in pool.all_messages() {
+            let options = message.options();
+            if let Some(extension) = disabled_extension.as_ref()
+                && options.has_extension(extension)
+                && options.get_extension(extension).as_bool() == Some(true)
+            {
+                disabled.insert(message.full_name().to_string());
+            }
+            for field in message.fields() {
+                let options = field.options();
+                if !options.has_extension(&rules_extension) {
+                    continue;
+                }
+                let rules = options
+                    .get_extension(&rules_extension)
+                    .as_message()
+                    .cloned()
+                    .ok_or(anyhow!("wrong rules for {}", field.full_name()))?;
+                compile_patterns(&rules, &mut patterns)?;
+                fields.insert(field.full_name().to_string(), rules);
+            }
+        }
+        Ok(Self {
+            methods,
+            fields,
+            disabled,
+            patterns,
+        })
+    }
+
+    fn check(&self, input: MessageDescriptor, body: &[u8]) -> Result<(), tonic::Status> {
+        let frame = frame(body)?;
+        let message = DynamicMessage::decode(input, frame)
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        let mut violations = Vec::new();
+        self.message(&message, "", &mut violations);
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(Error::InvalidArgument(violations).into())
+    }
+
+    fn message(&self, message: &DynamicMessage, prefix: &str, out: &mut Vec<FieldViolation>) {
+        let descriptor = message.descriptor();
+        if self.disabled.contains(descriptor.full_name()) {
+            return;
+        }
+        for field in descriptor.fields() {
+            let path = if prefix.is_empty() {
+                field.name().to_string()
+            } else {
+                format!("{prefix}.{}", field.name())
+            };
+            let value = (message.has_field(&field) || !field.supports_presence())
+                .then(|| message.get_field(&field));
+            let rules = self.fields.get(field.full_name());
+            if let Some(rules) = rules {
+                self.field(&field, rules, value.as_deref(), &path, out);
+            }
+            if rules.is_some_and(skipped) {
+                continue;
+            }
+            match value.as_deref() {
+                Some(Value::Message(nested)) => self.message(nested, &path, out),
+                Some(Value::List(items)) => {
+                    for (i, item) in items.iter().enumerate() {
+                        if let Value::Message(nested) = item {
+                            self.message(nested, &format!("{path}[{i}]"), out);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn field(
+        &self,
+        field: &FieldDescriptor,
+        rules: &DynamicMessage,
+        value: Option<&Value>,
+        path: &str,
+        out: &mut Vec<FieldViolation>,
+    ) {
+        for (kind, kind_rules) in rules.fields() {
+            let Some(kind_rules) = kind_rules.as_message() else {
+                continue;
+            };
+            match (kind.name(), value) {
+                ("message" | "timestamp" | "duration" | "any", None) => {
+                    if flag(kind_rules, "required") {
+                        violation(out, path, "value is required");
+                    }
+                }
+                (_, None) => {}
+                ("repeated", Some(Value::List(items))) => {
+                    self.repeated(field, kind_rules, items, path, out)
+                }
+                (name, Some(value)) => self.scalar(field, name, kind_rules, value, path, out),
+            }
+        }
+    }
+
+    fn repeated(
+        &self,
+        field: &FieldDescriptor,
+        rules: &DynamicMessage,
+        items: &[Value],
+        path: &str,
+        out: &mut Vec<FieldViolation>,
+    ) {
+        if items.is_empty() && flag(rules, "ignore_empty") {
+            return;
+        }
+        let len = items.len() as u64;
+        if let Some(min) = rule(rules, "min_items").and_then(|v| v.as_u64())
+            && len < min
+        {
+            violation(
+                out,
+                path,
+                format!("value must contain at least {min} item(s)"),
+            );
+        }
+        if let Some(max) = rule(rules, "max_items").and_then(|v| v.as_u64())
+            && len > max
+        {
+            violation(
+                out,
+                path,
+                format!("value must contain no more than {max} item(s)"),
+            );
+        }
+        if flag(rules, "unique")
+            && items
+                .iter()
+                .enumerate()
+                .any(|(i, item)| items[..i].contains(item))
+        {
+            violation(out, path, "repeated value must contain unique items");
+        }
+        if let Some(item_rules) = rule(rules, "items")
+            && let Some(item_rules) = item_rules.as_message()
+        {
+            for (i, item) in items.iter().enumerate() {
+                self.field(field, item_rules, Some(item), &format!("{path}[{i}]"), out);
+            }
+        }
+    }
+
+    fn scalar(
+        &self,
+        field: &FieldDescriptor,
+        kind: &str,
+        rules: &DynamicMessage,
+        value: &Value,
+        path: &str,
+        out: &mut Vec<FieldViolation>,
+    ) {
+        match kind {
+            "string" => self.string(rules, value.as_str().unwrap_or_default(), path, out),
+            "bool" => {
+                if let Some(expected) = rule(rules, "const").and_then(|v| v.as_bool())
+                    && value.as_bool() != Some(expected)
+                {
+                    violation(out, path, format!("value must equal {expected}"));
+                }
+            }
+            "enum" => enumeration(field, rules, value, path, out),
+            "float" | "double" => number(rules, value, float, path, out),
+            "int32" | "int64" | "uint32" | "uint64" | "sint32" | "sint64" | "fixed32"
+            | "fixed64" | "sfixed32" | "sfixed64" => number(rules, value, integer, path, out),
+            // Остальные правила в proto-файлах проекта не используются
+            _ => {}
+        }
+    }
+
+    fn string(
+        &self,
+        rules: &DynamicMessage,
+        value: &str,
+        path: &str,
+        out: &mut Vec<FieldViolation>,
+    ) {
+        if value.is_empty() && flag(rules, "ignore_empty") {
+            return;
+        }
+        let chars = value.chars().count() as u64;
+        let bytes = value.len() as u64;
+        let uint = |name| rule(rules, name).and_then(|v| v.as_u64());
+        let text = |name| rule(rules, name).and_then(|v| v.as_str().map(String::from));
+        if let Some(expected) = text("const")
+            && value != expected
+        {
+            violation(out, path, format!("value must equal {expected:?}"));
+        }
+        if let Some(len) = uint("len")
+            && chars != len
+        {
+            violation(out, path, format!("value length must be {len} characters"));
+        }
+        if let Some(min) = uint("min_len")
+            && chars < min
+        {
+            violation(
+                out,
+                path,
+                format!("value length must be at least {min} characters"),
+            );
+        }
+        if let Some(max) = uint("max_len")
+            && chars > max
+        {
+            violation(
+                out,
+                path,
+                format!("value length must be at most {max} characters"),
+            );
+        }
+        if let Some(min) = uint("min_bytes")
+            && bytes < min
+        {
+            violation(
+                out,
+                path,
+                format!("value length must be at least {min} bytes"),
+            );
+        }
+        if let Some(max) = uint("max_bytes")
+            && bytes > max
+        {
+            violation(
+                out,
+                path,
+                format!("value length must be at most {max} bytes"),
+            );
+        }
+        if let Some(pattern) = text("pattern")
+            && let Some(regex) = self.patterns.get(&pattern)
+            && !regex.is_match(value)
+        {
+            violation(
+                out,
+                path,
+                format!("value does not match regex pattern {pattern:?}"),
+            );
+        }
+        if let Some(prefix) = text("prefix")
+            && !value.starts_with(&prefix)
+        {
+            violation(out, path, format!("value does not have prefix {prefix:?}"));
+        }
+        if let Some(suffix) = text("suffix")
+            && !value.ends_with(&suffix)
+        {
+            violation(out, path, format!("value does not have suffix {suffix:?}"));
+        }
+        if let Some(substring) = text("contains")
+            && !value.contains(&substring)
+        {
+            violation(
+                out,
+                path,
+                format!("value does not contain substring {substring:?}"),
+            );
+        }
+        if let Some(substring) = text("not_contains")
+            && value.contains(&substring)
+        {
+            violation(out, path, format!("value contains substring {substring:?}"));
+        }
+        let list = |name| {
+            rule(rules, name).and_then(|v| {
+                v.as_list().map(|l| {
+                    l.iter()
+                        .filter_map(|s| s.as_str().map(String::from))
+                        .collect::<Vec<_>>()
+                })
+            })
+        };
+        if let Some(allowed) = list("in")
+            && !allowed.iter().any(|a| a == value)
+        {
+            violation(out, path, format!("value must be in list {allowed:?}"));
+        }
+        if let Some(denied) = list("not_in")
+            && denied.iter().any(|d| d == value)
+        {
+            violation(out, path, format!("value must not be in list {denied:?}"));
+        }
+        if flag(rules, "uuid") && uuid::Uuid::parse_str(value).is_err() {
+            violation(out, path, "value must be a valid UUID");
+        }
+    }
+}
+
+// Возвращает тело первого gRPC-сообщения. Сжатые запросы отклоняются: сервер
+// не принимает сжатие, а непроверенное сообщение не должно дойти до обработчика
+fn frame(body: &[u8]) -> Result<&[u8], tonic::Status> {
+    let malformed = || tonic::Status::invalid_argument("malformed request frame");
+    let (&compressed, rest) = body.split_first().ok_or_else(malformed)?;
+    if compressed != 0 {
+        return Err(tonic::Status::unimplemented(
+            "compressed requests are not supported",
+        ));
+    }
+    let (len, rest) = rest.split_at_checked(4).ok_or_else(malformed)?;
+    let len = u32::from_be_bytes(len.try_into().map_err(|_| malformed())?) as usize;
+    rest.get(..len).ok_or_else(malformed)
+}
+
+fn compile_patterns(
+    rules: &DynamicMessage,
+    patterns: &mut HashMap<String, Regex>,
+) -> anyhow::Result<()> {
+    for (_, value) in rules.fields() {
+        let Some(nested) = value.as_message() else {
+            continue;
+        };
+        if let Some(pattern) = rule(nested, "pattern").and_then(|p| p.as_str().map(String::from)) {
+            let regex = Regex::new(&pattern)?;
+            patterns.insert(pattern, regex);
+        }
+        if let Some(items) = rule(nested, "items")
+            && let Some(items) = items.as_message()
+        {
+            compile_patterns(items, patterns)?;
+        }
+    }
+    Ok(())
+}
+
+fn rule<'a>(rules: &'a DynamicMessage, name: &str) -> Option<Cow<'a, Value>> {
+    rules
+        .has_field_by_name(name)
+        .then(|| rules.get_field_by_name(name))
+        .flatten()
+}
+
+fn flag(rules: &DynamicMessage, name: &str) -> bool {
+    rule(rules, name)
+        .and_then(|v| v.as_bool())
+        .unwrap_or_default()
+}
+
+fn skipped(rules: &DynamicMessage) -> bool {
+    rule(rules, "message")
+        .and_then(|m| m.as_message().map(|m| flag(m, "skip")))
+        .unwrap_or_default()
+}
+
+fn violation(out: &mut Vec<FieldViolation>, path: &str, description: impl Into<String>) {
+    out.push(FieldViolation::new(path, description));
+}
+
+fn integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::I32(v) => Some((*v).into()),
+        Value::I64(v) => Some((*v).into()),
+        Value::U32(v) => Some((*v).into()),
+        Value::U64(v) => Some((*v).into()),
+        _ => None,
+    }
+}
+
+fn float(value: &Value) -> Option<f64> {
+    match value {
+        Value::F32(v) => Some((*v).into()),
+        Value::F64(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn number<T>(
+    rules: &DynamicMessage,
+    value: &Value,
+    convert: fn(&Value) -> Option<T>,
+    path: &str,
+    out: &mut Vec<FieldViolation>,
+) where
+    T: PartialOrd + Display + Default + Copy,
+{
+    let Some(value) = convert(value) else {
+        return;
+    };
+    if value == T::default() && flag(rules, "ignore_empty") {
+        return;
+    }
+    let bound = |name| rule(rules, name).and_then(|v| convert(&v));
+    let list = |name| {
+        rule(rules, name).and_then(|v| {
+            v.as_list()
+                .map(|l| l.iter().filter_map(convert).collect::<Vec<_>>())
+        })
+    };
+    if let Some(expected) = bound("const")
+        && value != expected
+    {
+        violation(out, path, format!("value must equal {expected}"));
+    }
+    let lower = bound("gt")
+        .map(|b| (b, value > b, format!("greater than {b}")))
+        .or_else(|| bound("gte").map(|b| (b, value >= b, format!("greater than or equal to {b}"))));
+    let upper = bound("lt")
+        .map(|b| (b, value < b, format!("less than {b}")))
+        .or_else(|| bound("lte").map(|b| (b, value <= b, format!("less than or equal to {b}"))));
+    match (lower, upper) {
+        // Верхняя граница меньше нижней: значение должно лежать вне диапазона
+        (Some((low, low_ok, low_text)), Some((high, high_ok, high_text))) if high < low => {
+            if !low_ok && !high_ok {
+                violation(
+                    out,
+                    path,
+                    format!("value must be {low_text} or {high_text}"),
+                );
+            }
+        }
+        (lower, upper) => {
+            let bounds = [lower, upper].into_iter().flatten().collect::<Vec<_>>();
+            if bounds.iter().any(|(_, ok, _)| !ok) {
+                let text = bounds
+                    .into_iter()
+                    .map(|(_, _, text)| text)
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+                violation(out, path, format!("value must be {text}"));
+            }
+        }
+    }
+    if let Some(allowed) = list("in")
+        && !allowed.contains(&value)
+    {
+        let allowed = allowed.iter().map(T::to_string).collect::<Vec<_>>();
+        violation(
+            out,
+            path,
+            format!("value must be in list [{}]", allowed.join(", ")),
+        );
+    }
+    if let Some(denied) = list("not_in")
+        && denied.contains(&value)
+    {
+        let denied = denied.iter().map(T::to_string).collect::<Vec<_>>();
+        violation(
+            out,
+            path,
+            format!("value must not be in list [{}]", denied.join(", ")),
+        );
+    }
+}
+
+fn enumeration(
+    field: &FieldDescriptor,
+    rules: &DynamicMessage,
+    value: &Value,
+    path: &str,
+    out: &mut Vec<FieldViolation>,
+) {
+    let Some(value) = value.as_enum_number() else {
+        return;
+    };
+    let list = |name| {
+        rule(rules, name).and_then(|v| {
+            v.as_list()
+                .map(|l| l.iter().filter_map(|n| n.as_i32()).collect::<Vec<_>>())
+        })
+    };
+    if let Some(expected) = rule(rules, "const").and_then(|v| v.as_i32())
+        && value != expected
+    {
+        violation(out, path, format!("value must equal {expected}"));
+    }
+    if flag(rules, "defined_only")
+        && let Some(descriptor) = field.kind().as_enum()
+        && descriptor.get_value(value).is_none()
+    {
+        violation(out, path, "value must be one of the defined enum values");
+    }
+    if let Some(allowed) = list("in")
+        && !allowed.contains(&value)
+    {
+        violation(out, path, format!("value must be in list {allowed:?}"));
+    }
+    if let Some(denied) = list("not_in")
+        && denied.contains(&value)
+    {
+        violation(out, path, format!("value must not be in list {denied:?}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grpc::smm::posts::*;
+    use prost::Message;
+
+    use super::*;
+
+    const CLAIM_POST: &str = "/proto.posts.v1.PostsService/ClaimPost";
+    const CREATE_POST: &str = "/proto.posts.v1.PostsService/CreatePost";
+    const LIST_POSTS: &str = "/proto.posts.v1.PostsService/ListPosts";
+    const SCHEDULE_POST: &str = "/proto.posts.v1.PostsService/SchedulePost";
+    const UPDATE_POST: &str = "/proto.posts.v1.PostsService/UpdatePost";
+
+    fn rules() -> Rules {
+        Rules::new(grpc::smm::FILE_DESCRIPTOR_SET).unwrap()
+    }
+
+    // Кадр gRPC с несжатым сообщением
+    fn frame_of(message: &impl Message) -> Vec<u8> {
+        let encoded = message.encode_to_vec();
+        let mut body = vec![0];
+        body.extend((encoded.len() as u32).to_be_bytes());
+        body.extend(encoded);
+        body
+    }
+
+    // Поля запроса, не прошедшие проверку
+    fn violations(method: &str, message: &impl Message) -> Vec<String> {
+        let rules = rules();
+        let input = rules.methods[method].clone();
+        let message = DynamicMessage::decode(input, message.encode_to_vec().as_slice()).unwrap();
+        let mut out = Vec::new();
+        rules.message(&message, "", &mut out);
+        out.into_iter().map(|v| v.field).collect()
+    }
+
+    fn claim() -> ClaimPostRequest {
+        ClaimPostRequest {
+            post_id: uuid::Uuid::new_v4().to_string(),
+            owner: String::from("worker"),
+            lease_seconds: 60,
+        }
+    }
+
+    fn list() -> ListPostsRequest {
+        ListPostsRequest {
+            page_size: 10,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn valid_requests_pass() {
+        assert!(violations(CLAIM_POST, &claim()).is_empty());
+        assert!(violations(LIST_POSTS, &list()).is_empty());
+    }
+
+    #[test]
+    fn string_rules() {
+        let create = |title: String| CreatePostRequest {
+            author_tg_id: 1,
+            title,
+            content: String::from("content"),
+            ..Default::default()
+        };
+        assert_eq!(violations(CREATE_POST, &create(String::new())), ["title"]);
+        assert!(violations(CREATE_POST, &create("я".repeat(255))).is_empty());
+        assert_eq!(violations(CREATE_POST, &create("я".repeat(256))), ["title"]);
+        let request = ClaimPostRequest {
+            post_id: String::from("not-a-uuid"),
+            owner: String::new(),
+            ..claim()
+        };
+        assert_eq!(violations(CLAIM_POST, &request), ["post_id", "owner"]);
+    }
+
+    #[test]
+    fn uint_rules() {
+        let request = ClaimPostRequest {
+            lease_seconds: 0,
+            ..claim()
+        };
+        assert_eq!(violations(CLAIM_POST, &request), ["lease_seconds"]);
+        for page_size in [9, 101] {
+            let request = ListPostsRequest {
+                page_size,
+                ..list()
+            };
+            assert_eq!(violations(LIST_POSTS, &request), ["page_size"]);
+        }
+        // Пустое значение с ignore_empty не проверяется
+        let request = ListPostsRequest { page: 0, ..list() };
+        assert!(violations(LIST_POSTS, &request).is_empty());
+    }
+
+    #[test]
+    fn repeated_rules() {
+        let with_authors = |author_tg_ids| ListPostsRequest {
+            author_tg_ids,
+            ..list()
+        };
+        assert!(violations(LIST_POSTS, &with_authors(vec![1, 2])).is_empty());
+        assert_eq!(
+            violations(LIST_POSTS, &with_authors(vec![1, 1])),
+            ["author_tg_ids"]
+        );
+        assert_eq!(
+            violations(LIST_POSTS, &with_authors(vec![1, 0])),
+            ["author_tg_ids[1]"]
+        );
+        assert_eq!(
+            violations(LIST_POSTS, &with_authors((1..=101).collect())),
+            ["author_tg_ids"]
+        );
+    }
+
+    #[test]
+    fn required_rules() {
+        let schedule = SchedulePostRequest {
+            post_id: uuid::Uuid::new_v4().to_string(),
+            publish_datetime: None,
+            edited_by: None,
+        };
+        assert_eq!(violations(SCHEDULE_POST, &schedule), ["publish_datetime"]);
+        let update = UpdatePostRequest {
+            updated_post: None,
+            edited_by: None,
+        };
+        assert_eq!(violations(UPDATE_POST, &update), ["updated_post"]);
+        // Вложенные сообщения проверяются с путем от корня запроса
+        let update = UpdatePostRequest {
+            updated_post: Some(Post {
+                id: String::from("not-a-uuid"),
+                title: String::from("title"),
+                content: String::from("content"),
+                author_id: uuid::Uuid::new_v4().to_string(),
+                version: 1,
+                ..Default::default()
+            }),
+            edited_by: None,
+        };
+        assert_eq!(violations(UPDATE_POST, &update), ["updated_post.id"]);
+    }
+
+    #[test]
+    fn enum_rules() {
+        let request = ListPostsRequest {
+            sort_by: 42,
+            ..list()
+        };
+        assert_eq!(violations(LIST_POSTS, &request), ["sort_by"]);
+    }
+
+    #[test]
+    fn frames() {
+        let rules = rules();
+        let input = || rules.methods[CLAIM_POST].clone();
+        assert!(rules.check(input(), &frame_of(&claim())).is_ok());
+        let invalid = ClaimPostRequest {
+            lease_seconds: 0,
+            ..claim()
+        };
+        let status = rules.check(input(), &frame_of(&invalid)).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let mut compressed = frame_of(&claim());
+        compressed[0] = 1;
+        let status = rules.check(input(), &compressed).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unimplemented);
+        let truncated = &frame_of(&claim())[..10];
+        let status = rules.check(input(), truncated).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+}
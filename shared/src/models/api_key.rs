@@ -0,0 +1,116 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::anyhow;
+use bson::serde_helpers::{datetime, uuid_1};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::post::{deserialize_option_datetime, serialize_option_datetime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+// Права API-ключа
+pub enum Scope {
+    // Чтение пользователей и постов
+    ReadOnly,
+    // Чтение и публикация запланированных постов
+    Publisher,
+    // Чтение и запросы от имени пользователей Telegram
+    Bot,
+    // Права всех остальных ключей. Изменения постов и пользователей, как и
+    // у ключа бота, выполняются только от имени пользователя Telegram
+    Admin,
+}
+impl Scope {
+    // Права ключа включают заданные; права администратора включают все остальные
+    pub fn granted(self, scopes: &[Scope]) -> bool {
+        scopes.contains(&Self::Admin) || scopes.contains(&self)
+    }
+}
+impl Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::ReadOnly => "read-only",
+            Self::Publisher => "publisher",
+            Self::Bot => "bot",
+            Self::Admin => "admin",
+        };
+        write!(f, "{name}")
+    }
+}
+impl FromStr for Scope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read-only" => Ok(Self::ReadOnly),
+            "publisher" => Ok(Self::Publisher),
+            "bot" => Ok(Self::Bot),
+            "admin" => Ok(Self::Admin),
+            _ => Err(anyhow!(
+                "Invalid scope: {s} (expected read-only, publisher, bot or admin)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+// API-ключ клиента сервера. Токен ключа не хранится, только его хеш
+pub struct ApiKey {
+    // UUID ключа
+    #[serde(rename = "_id")]
+    #[serde(with = "uuid_1::AsBinary")]
+    pub id: Uuid,
+    // Уникальное имя ключа
+    pub name: String,
+    // SHA-256 токена в hex
+    pub token_hash: String,
+    // Права ключа
+    pub scopes: Vec<Scope>,
+    // Дата и время выпуска
+    #[serde(with = "datetime::FromChrono04DateTime")]
+    pub created_at: DateTime<Utc>,
+    // Срок действия (None - бессрочный)
+    #[serde(
+        default,
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime"
+    )]
+    pub expires_at: Option<DateTime<Utc>>,
+    // Дата и время отзыва
+    #[serde(
+        default,
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime"
+    )]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+impl ApiKey {
+    // Выпускает ключ и возвращает его вместе с токеном, который больше
+    // нигде не сохраняется
+    pub fn issue(
+        name: String,
+        scopes: Vec<Scope>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> (Self, String) {
+        let token = format!("smm_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let key = Self {
+            id: Uuid::new_v4(),
+            name,
+            token_hash: Self::hash(&token),
+            scopes,
+            created_at: Utc::now(),
+            expires_at,
+            revoked_at: None,
+        };
+        (key, token)
+    }
+    pub fn hash(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+    // Ключ не отозван и не истек
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|e| e > now)
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt::Display;
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::post::{deserialize_option_datetime, serialize_option_datetime};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+// Результат доставки поста на одну площадку
+pub struct Delivery {
+    // Площадка публикации
+    pub platform: Platform,
+    // Идентификатор канала или сообщества на площадке
+    pub target_id: String,
+    // Идентификатор опубликованного сообщения или записи на площадке
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_id: Option<String>,
+    // Постоянная ссылка на опубликованную запись
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permalink: Option<String>,
+    // Дата и время успешной доставки
+    #[serde(
+        default,
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime"
+    )]
+    pub delivered_at: Option<DateTime<Utc>>,
+    // Текст ошибки доставки (если не удалось)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+impl Delivery {
+    pub fn delivered(
+        platform: Platform,
+        target_id: impl Into<String>,
+        remote_id: impl Into<String>,
+        permalink: Option<String>,
+    ) -> Self {
+        Self {
+            platform,
+            target_id: target_id.into(),
+            remote_id: Some(remote_id.into()),
+            permalink,
+            delivered_at: Some(Utc::now()),
+            error: None,
+        }
+    }
+    pub fn failed(
+        platform: Platform,
+        target_id: impl Into<String>,
+        error: impl Into<String>,
+    ) -> Self {
+        Self {
+            platform,
+            target_id: target_id.into(),
+            remote_id: None,
+            permalink: None,
+            delivered_at: None,
+            error: Some(error.into()),
+        }
+    }
+    pub fn is_delivered(&self) -> bool {
+        self.delivered_at.is_some() && self.error.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+// Площадки публикации
+pub enum Platform {
+    // Telegram
+    #[default]
+    Telegram,
+    // ВКонтакте
+    Vk,
+}
+impl TryFrom<i32> for Platform {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Telegram),
+            1 => Ok(Self::Vk),
+            _ => Err(anyhow!("Invalid platform value: {value}")),
+        }
+    }
+}
+impl From<Platform> for i32 {
+    fn from(platform: Platform) -> Self {
+        platform as i32
+    }
+}
+impl Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
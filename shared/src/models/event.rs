@@ -0,0 +1,47 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use super::{Post, Status};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+// Событие изменения поста
+pub struct PostEvent {
+    // Тип события
+    pub kind: PostEventKind,
+    // Состояние поста после изменения (для удаленного поста - последнее состояние)
+    pub post: Post,
+    // Статус поста до изменения (для события смены статуса)
+    pub previous_status: Option<Status>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+// Типы событий изменения поста
+pub enum PostEventKind {
+    // Пост создан
+    #[default]
+    Created,
+    // Пост изменен
+    Updated,
+    // Пост удален
+    Deleted,
+    // Изменился статус поста
+    StatusChanged,
+}
+impl TryFrom<i32> for PostEventKind {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Created),
+            1 => Ok(Self::Updated),
+            2 => Ok(Self::Deleted),
+            3 => Ok(Self::StatusChanged),
+            _ => Err(anyhow!("Invalid event kind value: {value}")),
+        }
+    }
+}
+impl From<PostEventKind> for i32 {
+    fn from(kind: PostEventKind) -> Self {
+        kind as i32
+    }
+}
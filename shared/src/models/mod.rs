@@ -1,4 +1,17 @@
 mod user;
 pub use user::{ListUsersResult, Role, User};
 mod post;
-pub use post::{ListPostsResult, Post, Status};
+pub use post::{
+    ListPostsQuery, ListPostsResult, Post, PostSortField, SearchPostsQuery, SearchPostsResult,
+    Status,
+};
+mod revision;
+pub use revision::{ListPostRevisionsResult, PostContent, PostField, PostRevision};
+mod delivery;
+pub use delivery::{Delivery, Platform};
+mod event;
+pub use event::{PostEvent, PostEventKind};
+mod page;
+pub use page::{PageKey, PageToken};
+mod api_key;
+pub use api_key::{ApiKey, Scope};
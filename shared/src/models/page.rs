@@ -0,0 +1,93 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::anyhow;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+// Позиция последней записи страницы: ключ сортировки и идентификатор.
+// Следующая страница начинается строго после этой позиции
+pub struct PageToken {
+    // Значение поля сортировки у последней записи страницы
+    pub key: PageKey,
+    // UUID последней записи (различает записи с одинаковым ключом)
+    pub id: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+// Значение поля сортировки. Пустое значение меньше любого другого
+pub enum PageKey {
+    // Время не задано
+    Null,
+    // Время
+    Time(DateTime<Utc>),
+    // Строка
+    Text(String),
+}
+impl PageToken {
+    pub fn new(key: PageKey, id: Uuid) -> Self {
+        Self { key, id }
+    }
+}
+impl From<DateTime<Utc>> for PageKey {
+    fn from(value: DateTime<Utc>) -> Self {
+        Self::Time(value)
+    }
+}
+impl From<Option<DateTime<Utc>>> for PageKey {
+    fn from(value: Option<DateTime<Utc>>) -> Self {
+        value.map_or(Self::Null, Self::Time)
+    }
+}
+impl From<String> for PageKey {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+// Токен передается клиентам как непрозрачная строка base64:
+// тип ключа (1 байт), ключ и UUID (16 байт)
+impl Display for PageToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut bytes = Vec::new();
+        match &self.key {
+            PageKey::Null => bytes.push(0),
+            PageKey::Time(time) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&time.timestamp().to_be_bytes());
+                bytes.extend_from_slice(&time.timestamp_subsec_nanos().to_be_bytes());
+            }
+            PageKey::Text(text) => {
+                bytes.push(2);
+                bytes.extend_from_slice(text.as_bytes());
+            }
+        }
+        bytes.extend_from_slice(self.id.as_bytes());
+        write!(f, "{}", URL_SAFE_NO_PAD.encode(bytes))
+    }
+}
+impl FromStr for PageToken {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || anyhow!("malformed page token");
+        let bytes = URL_SAFE_NO_PAD.decode(s).map_err(|_| malformed())?;
+        let (kind, rest) = bytes.split_first().ok_or_else(malformed)?;
+        let (key, id) = rest.split_last_chunk::<16>().ok_or_else(malformed)?;
+        let key = match (kind, key.len()) {
+            (0, 0) => PageKey::Null,
+            (1, 12) => {
+                let (secs, nanos) = key.split_at(8);
+                let secs = i64::from_be_bytes(secs.try_into()?);
+                let nanos = u32::from_be_bytes(nanos.try_into()?);
+                PageKey::Time(DateTime::from_timestamp(secs, nanos).ok_or_else(malformed)?)
+            }
+            (2, _) => PageKey::Text(String::from_utf8(key.to_vec()).map_err(|_| malformed())?),
+            _ => return Err(malformed()),
+        };
+        Ok(Self {
+            key,
+            id: Uuid::from_bytes(*id),
+        })
+    }
+}
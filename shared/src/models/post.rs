@@ -7,6 +7,8 @@ use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::{Delivery, PageKey, PageToken, Platform};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Builder)]
 #[builder(build_fn(validate = "Self::validate"))]
 // Сообщение, представляющее пост в системе
@@ -58,23 +60,96 @@ pub struct Post {
     #[builder(try_setter, setter(into))]
     #[serde(with = "uuid_1::AsBinary")]
     pub author_id: Uuid,
+    // Идентификатор публикатора, захватившего пост (для статуса "Публикуется")
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lease_owner: Option<String>,
+    // Время истечения захвата поста публикатором
+    #[builder(default)]
+    #[serde(
+        default,
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime"
+    )]
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    // Количество неудачных попыток публикации
+    #[builder(default)]
+    #[serde(default)]
+    pub attempts: u32,
+    // Текст последней ошибки публикации
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    // Время следующей попытки публикации после ошибки
+    #[builder(default)]
+    #[serde(
+        default,
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime"
+    )]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    // Результаты доставки поста на площадки
+    #[builder(default)]
+    #[serde(default)]
+    pub deliveries: Vec<Delivery>,
+    // Время перемещения поста в корзину (None, если пост не удален)
+    #[builder(default)]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime"
+    )]
+    pub deleted_at: Option<DateTime<Utc>>,
+    // Идентификатор в Telegram пользователя, удалившего пост
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_by: Option<i64>,
+    // Комментарий администратора к проверке поста (причина отклонения)
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_comment: Option<String>,
+    // Версия поста: увеличивается при каждом изменении. Обновление применяется,
+    // только если передана текущая версия
+    #[builder(default = 1)]
+    #[serde(default)]
+    pub version: u32,
 }
 impl Post {
     pub fn builder() -> PostBuilder {
         PostBuilder::default()
     }
+    // Позиция поста в списке, упорядоченном по заданному полю
+    pub fn page_token(&self, sort_by: PostSortField) -> PageToken {
+        let key = match sort_by {
+            PostSortField::CreatedAt => self.created_at.into(),
+            PostSortField::PublishDatetime => self.publish_datetime.into(),
+            PostSortField::Title => self.title.clone().into(),
+        };
+        PageToken::new(key, self.id)
+    }
+    // Текст поста для поиска: заголовок и содержимое в нижнем регистре
+    pub fn search_text(&self) -> String {
+        format!("{}\n{}", self.title, self.content).to_lowercase()
+    }
+    // Возвращает успешную доставку поста на площадку (если была)
+    pub fn delivered_to(&self, platform: Platform) -> Option<&Delivery> {
+        self.deliveries
+            .iter()
+            .find(|d| d.platform == platform && d.is_delivered())
+    }
 }
 impl PostBuilder {
     fn validate(&self) -> Result<(), String> {
-        if let Some(title) = self.title.as_ref() {
-            if title.len() < 1 || title.len() > 255 {
-                return Err(String::from("wrong title"));
-            }
+        if let Some(title) = self.title.as_ref()
+            && (title.is_empty() || title.len() > 255)
+        {
+            return Err(String::from("wrong title"));
         }
-        if let Some(content) = self.content.as_ref() {
-            if content.len() < 1 || content.len() > 4096 {
-                return Err(String::from("wrong content"));
-            }
+        if let Some(content) = self.content.as_ref()
+            && (content.is_empty() || content.len() > 4096)
+        {
+            return Err(String::from("wrong content"));
         }
         Ok(())
     }
@@ -92,6 +167,16 @@ pub enum Status {
     Published,
     // Отменен/Заброшен
     Abandoned,
+    // Публикуется (захвачен публикатором)
+    Publishing,
+    // Не удалось опубликовать
+    Failed,
+    // Отправлен на проверку администратору
+    InReview,
+    // Одобрен администратором, можно планировать публикацию
+    Approved,
+    // Отклонен администратором
+    Rejected,
 }
 impl TryFrom<i32> for Status {
     type Error = anyhow::Error;
@@ -102,11 +187,51 @@ impl TryFrom<i32> for Status {
             1 => Ok(Self::Pending),
             2 => Ok(Self::Published),
             3 => Ok(Self::Abandoned),
+            4 => Ok(Self::Publishing),
+            5 => Ok(Self::Failed),
+            6 => Ok(Self::InReview),
+            7 => Ok(Self::Approved),
+            8 => Ok(Self::Rejected),
             _ => Err(anyhow!("Invalid role value: {value}")),
         }
     }
 }
 
+impl Status {
+    // Допустимые переходы жизненного цикла поста: черновик планируется
+    // и публикуется, запланированный пост можно вернуть в черновики, любой
+    // пост (кроме публикуемого) можно отменить, отмененный - вернуть в черновики.
+    // Перенос запланированной публикации (Pending -> Pending) тоже допустим
+    pub fn can_transition_to(self, to: Status) -> bool {
+        matches!(
+            (self, to),
+            (
+                Self::Draft,
+                Self::Pending | Self::InReview | Self::Abandoned
+            ) | (
+                Self::Pending,
+                Self::Pending | Self::Draft | Self::Publishing | Self::Abandoned
+            ) | (
+                Self::Publishing,
+                Self::Published | Self::Pending | Self::Failed
+            ) | (Self::Failed, Self::Pending | Self::Draft | Self::Abandoned)
+                | (Self::Published, Self::Abandoned)
+                | (
+                    Self::InReview,
+                    Self::Approved | Self::Rejected | Self::Draft | Self::Abandoned
+                )
+                | (
+                    Self::Approved,
+                    Self::Pending | Self::Draft | Self::Abandoned
+                )
+                | (
+                    Self::Rejected,
+                    Self::Draft | Self::InReview | Self::Abandoned
+                )
+                | (Self::Abandoned, Self::Draft)
+        )
+    }
+}
 impl From<Status> for i32 {
     fn from(status: Status) -> Self {
         status as i32
@@ -125,11 +250,107 @@ impl From<String> for Status {
             Self::Published
         } else if Status::Abandoned.to_string() == value {
             Self::Abandoned
+        } else if Status::Publishing.to_string() == value {
+            Self::Publishing
+        } else if Status::Failed.to_string() == value {
+            Self::Failed
+        } else if Status::InReview.to_string() == value {
+            Self::InReview
+        } else if Status::Approved.to_string() == value {
+            Self::Approved
+        } else if Status::Rejected.to_string() == value {
+            Self::Rejected
         } else {
             Self::default()
         }
     }
 }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+// Поля сортировки списка постов
+pub enum PostSortField {
+    // Время создания
+    #[default]
+    CreatedAt,
+    // Запланированное время публикации (посты без даты идут первыми
+    // при сортировке по возрастанию)
+    PublishDatetime,
+    // Заголовок
+    Title,
+}
+impl TryFrom<i32> for PostSortField {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::CreatedAt),
+            1 => Ok(Self::PublishDatetime),
+            2 => Ok(Self::Title),
+            _ => Err(anyhow!("Invalid sort field value: {value}")),
+        }
+    }
+}
+impl From<PostSortField> for i32 {
+    fn from(field: PostSortField) -> Self {
+        field as i32
+    }
+}
+impl PostSortField {
+    // Подходит ли токен страницы для сортировки по этому полю
+    pub fn accepts(&self, token: &PageToken) -> bool {
+        matches!(
+            (self, &token.key),
+            (Self::CreatedAt, PageKey::Time(_))
+                | (Self::PublishDatetime, PageKey::Time(_) | PageKey::Null)
+                | (Self::Title, PageKey::Text(_))
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+// Параметры запроса списка постов. Границы времени включают начало
+// и не включают конец: [from, to)
+pub struct ListPostsQuery {
+    // Авторы постов (пустой - посты всех авторов)
+    pub author_ids: Vec<Uuid>,
+    // Фильтр по статусу
+    pub status: Option<Status>,
+    // Поле сортировки
+    pub sort_by: PostSortField,
+    // Сортировка по убыванию
+    pub descending: bool,
+    // Созданы не раньше
+    pub created_from: Option<DateTime<Utc>>,
+    // Созданы раньше
+    pub created_to: Option<DateTime<Utc>>,
+    // Запланированы не раньше (посты без даты публикации не попадают)
+    pub publish_from: Option<DateTime<Utc>>,
+    // Запланированы раньше (посты без даты публикации не попадают)
+    pub publish_to: Option<DateTime<Utc>>,
+    // Номер страницы (начинается с 1)
+    pub page: u32,
+    // Размер страницы
+    pub page_size: u32,
+    // Позиция, после которой начинается страница (вместо номера)
+    pub page_token: Option<PageToken>,
+    // Посты в корзине вместо обычных
+    pub deleted: bool,
+}
+impl ListPostsQuery {
+    // Проходит ли пост фильтры запроса
+    pub fn matches(&self, post: &Post) -> bool {
+        type Bound = Option<DateTime<Utc>>;
+        let within = |value: Bound, from: Bound, to: Bound| {
+            (from.is_none() && to.is_none())
+                || value.is_some_and(|v| from.is_none_or(|f| v >= f) && to.is_none_or(|t| v < t))
+        };
+        (self.author_ids.is_empty() || self.author_ids.contains(&post.author_id))
+            && post.deleted_at.is_some() == self.deleted
+            && self.status.is_none_or(|s| post.status == s)
+            && within(Some(post.created_at), self.created_from, self.created_to)
+            && within(post.publish_datetime, self.publish_from, self.publish_to)
+    }
+}
+
 // Ответ на запрос списка постов
 pub struct ListPostsResult {
     // Список постов
@@ -140,8 +361,61 @@ pub struct ListPostsResult {
     pub current_page: u32,
     // Общее количество страниц
     pub total_pages: u32,
+    // Токен следующей страницы (None, если страница последняя)
+    pub next_page_token: Option<PageToken>,
+}
+
+#[derive(Debug, Clone, Default)]
+// Параметры поиска постов. Пост подходит, если его заголовок или содержимое
+// включают все слова запроса без учета регистра. Найденные посты упорядочены
+// от новых к старым, посты в корзине не ищутся
+pub struct SearchPostsQuery {
+    // Текст запроса
+    pub text: String,
+    // Авторы постов (пустой - посты всех авторов)
+    pub author_ids: Vec<Uuid>,
+    // Фильтр по статусу
+    pub status: Option<Status>,
+    // Созданы не раньше
+    pub created_from: Option<DateTime<Utc>>,
+    // Созданы раньше
+    pub created_to: Option<DateTime<Utc>>,
+    // Размер страницы
+    pub page_size: u32,
+    // Позиция, после которой начинается страница
+    pub page_token: Option<PageToken>,
+}
+impl SearchPostsQuery {
+    // Слова запроса в нижнем регистре без повторов
+    pub fn terms(&self) -> Vec<String> {
+        let mut terms = Vec::new();
+        for term in self.text.split_whitespace().map(str::to_lowercase) {
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+        terms
+    }
+    // Проходит ли пост фильтры запроса
+    pub fn matches(&self, post: &Post) -> bool {
+        let text = post.search_text();
+        (self.author_ids.is_empty() || self.author_ids.contains(&post.author_id))
+            && post.deleted_at.is_none()
+            && self.status.is_none_or(|s| post.status == s)
+            && self.created_from.is_none_or(|f| post.created_at >= f)
+            && self.created_to.is_none_or(|t| post.created_at < t)
+            && self.terms().iter().all(|term| text.contains(term.as_str()))
+    }
+}
+
+// Ответ на запрос поиска постов
+pub struct SearchPostsResult {
+    // Найденные посты
+    pub posts: Vec<Post>,
+    // Токен следующей страницы (None, если страница последняя)
+    pub next_page_token: Option<PageToken>,
 }
-fn serialize_option_datetime<S>(
+pub(super) fn serialize_option_datetime<S>(
     datetime: &Option<DateTime<Utc>>,
     serializer: S,
 ) -> Result<S::Ok, S::Error>
@@ -154,7 +428,9 @@ where
     }
 }
 
-fn deserialize_option_datetime<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+pub(super) fn deserialize_option_datetime<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -165,3 +441,57 @@ where
     let opt: Option<Helper> = Option::deserialize(deserializer)?;
     Ok(opt.map(|h| h.0))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Status::{self, *};
+
+    // Все статусы в порядке их числовых значений
+    fn statuses() -> Vec<Status> {
+        (0..).map_while(|v| Status::try_from(v).ok()).collect()
+    }
+
+    #[test]
+    fn transition_table() {
+        let allowed: &[(Status, &[Status])] = &[
+            (Draft, &[Pending, InReview, Abandoned]),
+            (Pending, &[Pending, Draft, Publishing, Abandoned]),
+            (Published, &[Abandoned]),
+            (Abandoned, &[Draft]),
+            (Publishing, &[Published, Pending, Failed]),
+            (Failed, &[Pending, Draft, Abandoned]),
+            (InReview, &[Approved, Rejected, Draft, Abandoned]),
+            (Approved, &[Pending, Draft, Abandoned]),
+            (Rejected, &[Draft, InReview, Abandoned]),
+        ];
+        assert_eq!(allowed.len(), statuses().len());
+        for (from, targets) in allowed {
+            for to in statuses() {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    targets.contains(&to),
+                    "{from} -> {to}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn publishing_is_only_reached_by_claim() {
+        // Pending -> Publishing - захват поста публикатором (ClaimPost)
+        let sources = statuses()
+            .into_iter()
+            .filter(|s| s.can_transition_to(Publishing))
+            .collect::<Vec<_>>();
+        assert_eq!(sources, [Pending]);
+    }
+
+    #[test]
+    fn abandoned_only_returns_to_draft() {
+        let targets = statuses()
+            .into_iter()
+            .filter(|s| Abandoned.can_transition_to(*s))
+            .collect::<Vec<_>>();
+        assert_eq!(targets, [Draft]);
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::anyhow;
+use bson::serde_helpers::{datetime, uuid_1};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::post::{deserialize_option_datetime, serialize_option_datetime};
+use super::{PageToken, Post, Status};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+// Редактируемое содержимое поста, сохраняемое в истории правок
+pub struct PostContent {
+    // Заголовок поста
+    pub title: String,
+    // Содержимое поста
+    pub content: String,
+    // Идентификатор фотофайла в Telegram
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tg_photo_file_id: Option<String>,
+    // Идентификатор фотофайла в VK
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vk_photo_file_id: Option<String>,
+    // Идентификатор видеофайла в Telegram
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tg_video_file_id: Option<String>,
+    // Идентификатор видеофайла в VK
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vk_video_file_id: Option<String>,
+    // Статус поста
+    pub status: Status,
+    // Запланированное время публикации
+    #[serde(
+        default,
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime"
+    )]
+    pub publish_datetime: Option<DateTime<Utc>>,
+}
+impl From<&Post> for PostContent {
+    fn from(post: &Post) -> Self {
+        Self {
+            title: post.title.clone(),
+            content: post.content.clone(),
+            tg_photo_file_id: post.tg_photo_file_id.clone(),
+            vk_photo_file_id: post.vk_photo_file_id.clone(),
+            tg_video_file_id: post.tg_video_file_id.clone(),
+            vk_video_file_id: post.vk_video_file_id.clone(),
+            status: post.status,
+            publish_datetime: post.publish_datetime,
+        }
+    }
+}
+impl PostContent {
+    // Переносит содержимое в пост
+    pub fn apply_to(&self, post: &mut Post) {
+        post.title = self.title.clone();
+        post.content = self.content.clone();
+        post.tg_photo_file_id = self.tg_photo_file_id.clone();
+        post.vk_photo_file_id = self.vk_photo_file_id.clone();
+        post.tg_video_file_id = self.tg_video_file_id.clone();
+        post.vk_video_file_id = self.vk_video_file_id.clone();
+        post.status = self.status;
+        post.publish_datetime = self.publish_datetime;
+    }
+    // Изменившиеся поля по сравнению с другим содержимым
+    pub fn changes(&self, other: &PostContent) -> Vec<PostField> {
+        let media = |c: &PostContent| {
+            (
+                c.tg_photo_file_id.clone(),
+                c.vk_photo_file_id.clone(),
+                c.tg_video_file_id.clone(),
+                c.vk_video_file_id.clone(),
+            )
+        };
+        let mut changes = Vec::new();
+        if self.title != other.title {
+            changes.push(PostField::Title);
+        }
+        if self.content != other.content {
+            changes.push(PostField::Content);
+        }
+        if media(self) != media(other) {
+            changes.push(PostField::Media);
+        }
+        if self.status != other.status {
+            changes.push(PostField::Status);
+        }
+        if self.publish_datetime != other.publish_datetime {
+            changes.push(PostField::PublishDatetime);
+        }
+        changes
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+// Поля поста, изменения которых попадают в историю правок
+pub enum PostField {
+    // Заголовок
+    Title,
+    // Содержимое
+    Content,
+    // Фото и видео
+    Media,
+    // Статус
+    Status,
+    // Время публикации
+    PublishDatetime,
+}
+impl TryFrom<i32> for PostField {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Title),
+            1 => Ok(Self::Content),
+            2 => Ok(Self::Media),
+            3 => Ok(Self::Status),
+            4 => Ok(Self::PublishDatetime),
+            _ => Err(anyhow!("Invalid post field value: {value}")),
+        }
+    }
+}
+impl From<PostField> for i32 {
+    fn from(field: PostField) -> Self {
+        field as i32
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+// Правка поста: кто и когда изменил пост, содержимое до и после правки
+pub struct PostRevision {
+    // UUID правки
+    #[serde(rename = "_id")]
+    #[serde(with = "uuid_1::AsBinary")]
+    pub id: Uuid,
+    // UUID поста
+    #[serde(with = "uuid_1::AsBinary")]
+    pub post_id: Uuid,
+    // Идентификатор в Telegram пользователя, изменившего пост (если известен)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_by: Option<i64>,
+    // Дата и время правки
+    #[serde(with = "datetime::FromChrono04DateTime")]
+    pub created_at: DateTime<Utc>,
+    // Содержимое поста до правки
+    pub before: PostContent,
+    // Содержимое поста после правки
+    pub after: PostContent,
+}
+impl PostRevision {
+    // Правка между двумя состояниями поста (None, если содержимое не изменилось)
+    pub fn new(before: &Post, after: &Post, edited_by: Option<i64>) -> Option<Self> {
+        let post_id = after.id;
+        let (before, after) = (PostContent::from(before), PostContent::from(after));
+        if before == after {
+            return None;
+        }
+        Some(Self {
+            id: Uuid::new_v4(),
+            post_id,
+            edited_by,
+            created_at: Utc::now(),
+            before,
+            after,
+        })
+    }
+    // Поля, измененные правкой
+    pub fn changes(&self) -> Vec<PostField> {
+        self.before.changes(&self.after)
+    }
+    // Позиция правки в истории поста
+    pub fn page_token(&self) -> PageToken {
+        PageToken::new(self.created_at.into(), self.id)
+    }
+}
+
+// Ответ на запрос истории правок поста
+pub struct ListPostRevisionsResult {
+    // Правки от новых к старым
+    pub revisions: Vec<PostRevision>,
+    // Токен следующей страницы (None, если страница последняя)
+    pub next_page_token: Option<PageToken>,
+}
@@ -6,6 +6,8 @@ use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::PageToken;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Builder)]
 #[builder(build_fn(validate = "Self::validate"))]
 pub struct User {
@@ -55,45 +57,60 @@ pub struct User {
     #[builder(setter(custom), default = Utc::now())]
     #[serde(with = "datetime::FromChrono04DateTime")]
     pub last_activity: DateTime<Utc>,
+
+    /// Версия записи: увеличивается при каждом изменении. Обновление
+    /// применяется, только если передана текущая версия
+    #[builder(default = 1)]
+    #[serde(default)]
+    pub version: u32,
 }
 impl User {
     pub fn builder() -> UserBuilder {
         UserBuilder::default()
     }
+    // Позиция пользователя в списке пользователей
+    pub fn page_token(&self) -> PageToken {
+        PageToken::new(self.created_at.into(), self.id)
+    }
+    // Имя и фамилия пользователя через пробел
+    pub fn full_name(&self) -> String {
+        match self.last_name.as_deref() {
+            Some(last_name) if !last_name.is_empty() => format!("{} {last_name}", self.first_name),
+            _ => self.first_name.clone(),
+        }
+    }
 }
 impl UserBuilder {
     pub fn validate(&self) -> Result<(), String> {
-        if let Some(tid) = self.telegram_id {
-            if tid < 0 {
-                return Err(String::from("telegram id must be greater than 0"));
-            }
+        if let Some(tid) = self.telegram_id
+            && tid < 0
+        {
+            return Err(String::from("telegram id must be greater than 0"));
         }
-        if let Some(firstname) = self.first_name.as_ref() {
-            if firstname.len() < 1 || firstname.len() > 64 {
-                return Err(String::from("wrong first name"));
-            }
+        if let Some(firstname) = self.first_name.as_ref()
+            && (firstname.is_empty() || firstname.len() > 64)
+        {
+            return Err(String::from("wrong first name"));
         }
 
-        if let Some(lastname) = self.last_name.as_ref().and_then(|l| l.as_ref()) {
-            if lastname.len() < 1 || lastname.len() > 64 {
-                return Err(String::from("wrong last name"));
-            }
+        if let Some(lastname) = self.last_name.as_ref().and_then(|l| l.as_ref())
+            && (lastname.is_empty() || lastname.len() > 64)
+        {
+            return Err(String::from("wrong last name"));
         }
 
-        if let Some(un) = self.username.as_ref().and_then(|u| u.as_ref()) {
-            if let Ok(re) = regex::Regex::new(r"^[a-zA-Z0-9_]{5,32}$") {
-                if !re.is_match(un) {
-                    return Err(String::from("wrong username"));
-                }
-            }
+        if let Some(un) = self.username.as_ref().and_then(|u| u.as_ref())
+            && let Ok(re) = regex::Regex::new(r"^[a-zA-Z0-9_]{5,32}$")
+            && !re.is_match(un)
+        {
+            return Err(String::from("wrong username"));
         }
 
-        if let Some(lc) = self.language_code.as_ref().and_then(|c| c.as_ref()) {
-            if let Ok(re) = regex::Regex::new(r"^[a-z]{2}$") {
-                if !re.is_match(lc) {
-                    return Err(String::from("wrong language code"));
-                }
-            }
+        if let Some(lc) = self.language_code.as_ref().and_then(|c| c.as_ref())
+            && let Ok(re) = regex::Regex::new(r"^[a-z]{2}$")
+            && !re.is_match(lc)
+        {
+            return Err(String::from("wrong language code"));
         }
         Ok(())
     }
@@ -111,9 +128,10 @@ impl UserBuilder {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 pub enum Role {
     /// Роль не определена (значение по умолчанию)
+    #[default]
     Guest,
     /// Редактор - может создавать и управлять своим контентом
     Editor,
@@ -121,12 +139,6 @@ pub enum Role {
     Admin,
 }
 
-impl Default for Role {
-    fn default() -> Self {
-        Self::Guest
-    }
-}
-
 impl TryFrom<i32> for Role {
     type Error = String;
 
@@ -171,4 +183,6 @@ pub struct ListUsersResult {
     pub current_page: u32,
     // Общее количество страниц
     pub total_pages: u32,
+    // Токен следующей страницы (None, если страница последняя)
+    pub next_page_token: Option<PageToken>,
 }
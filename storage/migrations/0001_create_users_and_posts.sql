@@ -0,0 +1,51 @@
+-- Пользователи системы
+CREATE TABLE IF NOT EXISTS users (
+    id TEXT PRIMARY KEY,
+    telegram_id BIGINT NOT NULL UNIQUE,
+    first_name TEXT NOT NULL,
+    last_name TEXT,
+    username TEXT,
+    language_code TEXT,
+    role TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL,
+    last_activity BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS users_role_created_at ON users (role, created_at);
+
+-- Посты (время хранится в микросекундах от начала эпохи UTC)
+CREATE TABLE IF NOT EXISTS posts (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    tg_photo_file_id TEXT,
+    vk_photo_file_id TEXT,
+    tg_video_file_id TEXT,
+    vk_video_file_id TEXT,
+    status TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    publish_datetime BIGINT,
+    author_id TEXT NOT NULL,
+    lease_owner TEXT,
+    lease_expires_at BIGINT,
+    attempts BIGINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS posts_author_status ON posts (author_id, status);
+CREATE INDEX IF NOT EXISTS posts_status_publish_datetime ON posts (status, publish_datetime);
+
+-- Результаты доставки постов на площадки
+CREATE TABLE IF NOT EXISTS post_deliveries (
+    post_id TEXT NOT NULL,
+    ordinal BIGINT NOT NULL,
+    platform BIGINT NOT NULL,
+    target_id TEXT NOT NULL,
+    remote_id TEXT,
+    permalink TEXT,
+    delivered_at BIGINT,
+    error TEXT,
+    PRIMARY KEY (post_id, ordinal)
+);
@@ -0,0 +1,3 @@
+-- Постраничная выборка сортирует по (created_at, id)
+CREATE INDEX IF NOT EXISTS posts_author_created_at ON posts (author_id, created_at, id);
+CREATE INDEX IF NOT EXISTS users_created_at ON users (created_at, id);
@@ -0,0 +1,3 @@
+-- Очередь автора выводится в порядке публикации
+CREATE INDEX IF NOT EXISTS posts_author_status_publish_datetime
+    ON posts (author_id, status, publish_datetime, id);
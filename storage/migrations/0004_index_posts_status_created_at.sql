@@ -0,0 +1,3 @@
+-- Администраторы просматривают посты всех авторов по статусу
+CREATE INDEX IF NOT EXISTS posts_status_created_at
+    ON posts (status, created_at, id);
@@ -0,0 +1,3 @@
+-- Текст для поиска постов: заголовок и содержимое в нижнем регистре.
+-- Заполняется приложением, так как LOWER в SQLite не переводит кириллицу
+ALTER TABLE posts ADD COLUMN search_text TEXT;
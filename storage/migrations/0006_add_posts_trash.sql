@@ -0,0 +1,5 @@
+-- Корзина: время удаления и идентификатор в Telegram удалившего пользователя
+ALTER TABLE posts ADD COLUMN deleted_at BIGINT;
+ALTER TABLE posts ADD COLUMN deleted_by BIGINT;
+
+CREATE INDEX IF NOT EXISTS posts_deleted_at ON posts (deleted_at);
@@ -0,0 +1,26 @@
+-- История правок постов: содержимое до (before_*) и после (after_*) правки
+CREATE TABLE IF NOT EXISTS post_revisions (
+    id TEXT PRIMARY KEY,
+    post_id TEXT NOT NULL,
+    edited_by BIGINT,
+    created_at BIGINT NOT NULL,
+    before_title TEXT NOT NULL,
+    before_content TEXT NOT NULL,
+    before_tg_photo_file_id TEXT,
+    before_vk_photo_file_id TEXT,
+    before_tg_video_file_id TEXT,
+    before_vk_video_file_id TEXT,
+    before_status TEXT NOT NULL,
+    before_publish_datetime BIGINT,
+    after_title TEXT NOT NULL,
+    after_content TEXT NOT NULL,
+    after_tg_photo_file_id TEXT,
+    after_vk_photo_file_id TEXT,
+    after_tg_video_file_id TEXT,
+    after_vk_video_file_id TEXT,
+    after_status TEXT NOT NULL,
+    after_publish_datetime BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS post_revisions_post_created_at
+    ON post_revisions (post_id, created_at, id);
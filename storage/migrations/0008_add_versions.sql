@@ -0,0 +1,3 @@
+-- Версия записи для оптимистичной блокировки: увеличивается при каждом изменении
+ALTER TABLE posts ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE users ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
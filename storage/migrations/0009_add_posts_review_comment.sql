@@ -0,0 +1,2 @@
+-- Комментарий администратора к проверке поста (причина отклонения)
+ALTER TABLE posts ADD COLUMN review_comment TEXT;
@@ -0,0 +1,5 @@
+-- Однократные отметки первичной настройки (назначение первого администратора)
+CREATE TABLE IF NOT EXISTS bootstrap (
+    name TEXT PRIMARY KEY,
+    telegram_id BIGINT NOT NULL
+);
@@ -0,0 +1,16 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use shared::models::ApiKey;
+
+// Хранилище API-ключей
+#[async_trait::async_trait]
+pub trait ApiKeysStorage: std::fmt::Debug + Send + Sync {
+    // Сохраняет новый ключ (None, если ключ с таким именем уже есть)
+    async fn create(&self, key: &ApiKey) -> Result<Option<ApiKey>>;
+    // Возвращает ключ по хешу токена
+    async fn get_by_hash(&self, token_hash: &str) -> Result<Option<ApiKey>>;
+    // Возвращает все ключи в порядке выпуска
+    async fn list(&self) -> Result<Vec<ApiKey>>;
+    // Отзывает ключ по имени (None, если ключ не найден или уже отозван)
+    async fn revoke(&self, name: &str, revoked_at: DateTime<Utc>) -> Result<Option<ApiKey>>;
+}
@@ -1,33 +1,113 @@
+mod api_keys_storage;
+mod memory;
+mod mongo;
 mod posts_storage;
+mod sql;
 mod users_storage;
 
+pub use api_keys_storage::ApiKeysStorage;
+pub use posts_storage::PostsStorage;
+pub use users_storage::UsersStorage;
+
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use shared::models::PageToken;
 
 const DATABASE: &str = "smmaster";
 
 #[derive(Clone, Debug)]
 pub struct Storage {
-    users_storage: Arc<users_storage::UsersStorage>,
-    posts_storage: Arc<posts_storage::PostsStorage>,
+    users_storage: Arc<dyn UsersStorage>,
+    posts_storage: Arc<dyn PostsStorage>,
+    api_keys_storage: Arc<dyn ApiKeysStorage>,
 }
 impl Storage {
+    // Выбирает хранилище по схеме URI: mongodb://, mongodb+srv://, sqlite://,
+    // postgres:// или memory://
     pub async fn new(uri: &str) -> Result<Self> {
-        let client = mongodb::Client::with_uri_str(uri).await?;
-        let db = client.database(DATABASE);
-        db.run_command(bson::doc! {"ping": 1}).await?;
-        let users_storage = Arc::new(users_storage::UsersStorage::new(db.clone()));
-        let posts_storage = Arc::new(posts_storage::PostsStorage::new(db));
+        match scheme(uri) {
+            Some("mongodb" | "mongodb+srv") => Self::mongo(uri).await,
+            Some("sqlite" | "postgres" | "postgresql") => Self::sql(uri).await,
+            Some("memory") => Ok(Self::memory()),
+            _ => Err(anyhow!("unsupported database uri: {uri}")),
+        }
+    }
+    // Применяет недостающие миграции схемы и возвращает их описания
+    pub async fn migrate(uri: &str) -> Result<Vec<String>> {
+        match scheme(uri) {
+            Some("mongodb" | "mongodb+srv") => mongo::migrate(&mongo_database(uri).await?).await,
+            Some("sqlite" | "postgres" | "postgresql") => {
+                sql::migrate(&sql::connect(uri).await?).await
+            }
+            Some("memory") => Ok(Vec::new()),
+            _ => Err(anyhow!("unsupported database uri: {uri}")),
+        }
+    }
+    // MongoDB: индексы и миграции данных применяются при подключении
+    pub async fn mongo(uri: &str) -> Result<Self> {
+        let db = mongo_database(uri).await?;
+        mongo::migrate(&db).await?;
+        let users_storage = Arc::new(mongo::MongoUsersStorage::new(db.clone()));
+        let posts_storage = Arc::new(mongo::MongoPostsStorage::new(db.clone()));
+        let api_keys_storage = Arc::new(mongo::MongoApiKeysStorage::new(db));
         Ok(Self {
             users_storage,
             posts_storage,
+            api_keys_storage,
+        })
+    }
+    // SQLite или PostgreSQL: схема создается встроенными миграциями
+    pub async fn sql(uri: &str) -> Result<Self> {
+        let pool = sql::connect(uri).await?;
+        sql::migrate(&pool).await?;
+        Ok(Self {
+            users_storage: Arc::new(sql::SqlUsersStorage::new(pool.clone())),
+            posts_storage: Arc::new(sql::SqlPostsStorage::new(pool.clone())),
+            api_keys_storage: Arc::new(sql::SqlApiKeysStorage::new(pool)),
         })
     }
-    pub fn users(&self) -> Arc<users_storage::UsersStorage> {
+    // Хранилище в памяти процесса: данные теряются при перезапуске
+    pub fn memory() -> Self {
+        Self {
+            users_storage: Arc::new(memory::MemoryUsersStorage::new()),
+            posts_storage: Arc::new(memory::MemoryPostsStorage::new()),
+            api_keys_storage: Arc::new(memory::MemoryApiKeysStorage::new()),
+        }
+    }
+    pub fn users(&self) -> Arc<dyn UsersStorage> {
         self.users_storage.clone()
     }
-    pub fn posts(&self) -> Arc<posts_storage::PostsStorage> {
+    pub fn posts(&self) -> Arc<dyn PostsStorage> {
         self.posts_storage.clone()
     }
+    pub fn api_keys(&self) -> Arc<dyn ApiKeysStorage> {
+        self.api_keys_storage.clone()
+    }
+}
+
+fn scheme(uri: &str) -> Option<&str> {
+    uri.split_once("://").map(|(scheme, _)| scheme)
+}
+
+async fn mongo_database(uri: &str) -> Result<mongodb::Database> {
+    let client = mongodb::Client::with_uri_str(uri).await?;
+    let db = client.database(DATABASE);
+    db.run_command(bson::doc! {"ping": 1}).await?;
+    Ok(db)
+}
+
+// Страница запрашивается с одной лишней записью: если она пришла, страница
+// не последняя и токен указывает на последнюю оставшуюся запись
+fn split_page<T>(
+    mut items: Vec<T>,
+    page_size: u32,
+    position: impl Fn(&T) -> PageToken,
+) -> (Vec<T>, Option<PageToken>) {
+    if items.len() <= page_size as usize {
+        return (items, None);
+    }
+    items.truncate(page_size as usize);
+    let next_page_token = items.last().map(position);
+    (items, next_page_token)
 }
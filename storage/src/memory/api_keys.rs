@@ -0,0 +1,48 @@
+use std::sync::RwLock;
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use shared::models::ApiKey;
+
+use crate::ApiKeysStorage;
+
+// Хранилище API-ключей в памяти процесса
+#[derive(Debug, Default)]
+pub struct MemoryApiKeysStorage {
+    keys: RwLock<Vec<ApiKey>>,
+}
+impl MemoryApiKeysStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+#[async_trait::async_trait]
+impl ApiKeysStorage for MemoryApiKeysStorage {
+    async fn create(&self, key: &ApiKey) -> Result<Option<ApiKey>> {
+        let mut keys = self.keys.write().map_err(|e| anyhow!("{e}"))?;
+        if keys.iter().any(|k| k.name == key.name) {
+            return Ok(None);
+        }
+        keys.push(key.clone());
+        Ok(Some(key.clone()))
+    }
+    async fn get_by_hash(&self, token_hash: &str) -> Result<Option<ApiKey>> {
+        let keys = self.keys.read().map_err(|e| anyhow!("{e}"))?;
+        Ok(keys.iter().find(|k| k.token_hash == token_hash).cloned())
+    }
+    async fn list(&self) -> Result<Vec<ApiKey>> {
+        let keys = self.keys.read().map_err(|e| anyhow!("{e}"))?;
+        Ok(keys.clone())
+    }
+    async fn revoke(&self, name: &str, revoked_at: DateTime<Utc>) -> Result<Option<ApiKey>> {
+        let mut keys = self.keys.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(key) = keys
+            .iter_mut()
+            .find(|k| k.name == name && k.revoked_at.is_none())
+        else {
+            return Ok(None);
+        };
+        key.revoked_at = Some(revoked_at);
+        Ok(Some(key.clone()))
+    }
+}
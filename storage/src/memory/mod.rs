@@ -0,0 +1,45 @@
+mod api_keys;
+mod posts;
+mod users;
+
+pub use api_keys::MemoryApiKeysStorage;
+pub use posts::MemoryPostsStorage;
+pub use users::MemoryUsersStorage;
+
+use shared::models::PageToken;
+
+// Количество страниц для списка заданного размера
+fn total_pages(total_count: usize, page_size: u32) -> u32 {
+    total_count.div_ceil(page_size.max(1) as usize) as u32
+}
+
+// Элементы запрошенной страницы и одна лишняя запись: после позиции токена
+// или по номеру страницы (страницы начинаются с 1)
+fn page<T: Clone>(
+    items: &[T],
+    page: u32,
+    page_size: u32,
+    page_token: Option<&PageToken>,
+    ascending: bool,
+    position: impl Fn(&T) -> PageToken,
+) -> Vec<T> {
+    let start = match page_token {
+        Some(token) => items
+            .iter()
+            .position(|item| {
+                if ascending {
+                    position(item) > *token
+                } else {
+                    position(item) < *token
+                }
+            })
+            .unwrap_or(items.len()),
+        None => (page.saturating_sub(1) as usize).saturating_mul(page_size as usize),
+    };
+    items
+        .iter()
+        .skip(start)
+        .take(page_size as usize + 1)
+        .cloned()
+        .collect()
+}
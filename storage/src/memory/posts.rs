@@ -0,0 +1,337 @@
+use std::sync::RwLock;
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use shared::models::{
+    Delivery, ListPostRevisionsResult, ListPostsQuery, ListPostsResult, PageToken, Post,
+    PostRevision, PostSortField, SearchPostsQuery, SearchPostsResult, Status,
+};
+use uuid::Uuid;
+
+use crate::PostsStorage;
+
+// Хранилище постов в памяти процесса
+#[derive(Debug, Default)]
+pub struct MemoryPostsStorage {
+    posts: RwLock<Vec<Post>>,
+    // История правок постов в порядке сохранения
+    revisions: RwLock<Vec<PostRevision>>,
+}
+impl MemoryPostsStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // Изменяет пост, захваченный указанным публикатором
+    fn update_leased(
+        &self,
+        id: Uuid,
+        owner: &str,
+        update: impl FnOnce(&mut Post),
+    ) -> Result<Option<Post>> {
+        let mut posts = self.posts.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(post) = posts.iter_mut().find(|p| {
+            p.id == id && p.status == Status::Publishing && p.lease_owner.as_deref() == Some(owner)
+        }) else {
+            return Ok(None);
+        };
+        update(post);
+        post.lease_owner = None;
+        post.lease_expires_at = None;
+        post.version += 1;
+        Ok(Some(post.clone()))
+    }
+}
+// Время, когда пост станет готов к публикации
+fn due_time(post: &Post) -> Option<DateTime<Utc>> {
+    match post.status {
+        _ if post.deleted_at.is_some() => None,
+        Status::Publishing => post.lease_expires_at,
+        Status::Pending => match (post.publish_datetime, post.next_attempt_at) {
+            (Some(publish), Some(next)) => Some(publish.max(next)),
+            (publish, next) => publish.or(next),
+        },
+        _ => None,
+    }
+}
+#[async_trait::async_trait]
+impl PostsStorage for MemoryPostsStorage {
+    async fn create(&self, post: &Post) -> Result<Option<Post>> {
+        let mut posts = self.posts.write().map_err(|e| anyhow!("{e}"))?;
+        if posts.iter().any(|p| p.id == post.id) {
+            return Err(anyhow!("duplicate key: {id}", id = post.id));
+        }
+        posts.push(post.clone());
+        Ok(Some(post.clone()))
+    }
+    async fn get(&self, id: Uuid) -> Result<Option<Post>> {
+        let posts = self.posts.read().map_err(|e| anyhow!("{e}"))?;
+        Ok(posts.iter().find(|p| p.id == id).cloned())
+    }
+    async fn list_posts(&self, query: &ListPostsQuery) -> Result<ListPostsResult> {
+        let posts = self.posts.read().map_err(|e| anyhow!("{e}"))?;
+        let position = |p: &Post| p.page_token(query.sort_by);
+        let mut filtered = posts
+            .iter()
+            .filter(|p| query.matches(p))
+            .cloned()
+            .collect::<Vec<_>>();
+        filtered.sort_by_cached_key(position);
+        if query.descending {
+            filtered.reverse();
+        }
+        let items = super::page(
+            &filtered,
+            query.page,
+            query.page_size,
+            query.page_token.as_ref(),
+            !query.descending,
+            position,
+        );
+        let (posts, next_page_token) = crate::split_page(items, query.page_size, position);
+        if query.page_token.is_some() {
+            return Ok(ListPostsResult {
+                posts,
+                total_count: 0,
+                current_page: 0,
+                total_pages: 0,
+                next_page_token,
+            });
+        }
+        Ok(ListPostsResult {
+            posts,
+            total_count: filtered.len() as u32,
+            current_page: query.page,
+            total_pages: super::total_pages(filtered.len(), query.page_size),
+            next_page_token,
+        })
+    }
+    async fn search_posts(&self, query: &SearchPostsQuery) -> Result<SearchPostsResult> {
+        let posts = self.posts.read().map_err(|e| anyhow!("{e}"))?;
+        let position = |p: &Post| p.page_token(PostSortField::CreatedAt);
+        let mut found = posts
+            .iter()
+            .filter(|p| query.matches(p))
+            .cloned()
+            .collect::<Vec<_>>();
+        found.sort_by_cached_key(position);
+        found.reverse();
+        let items = super::page(
+            &found,
+            1,
+            query.page_size,
+            query.page_token.as_ref(),
+            false,
+            position,
+        );
+        let (posts, next_page_token) = crate::split_page(items, query.page_size, position);
+        Ok(SearchPostsResult {
+            posts,
+            next_page_token,
+        })
+    }
+    async fn list_due_posts(&self, due_before: DateTime<Utc>) -> Result<Vec<Post>> {
+        let posts = self.posts.read().map_err(|e| anyhow!("{e}"))?;
+        let mut due = posts
+            .iter()
+            .filter(|p| p.deleted_at.is_none())
+            .filter(|p| match p.status {
+                Status::Pending => {
+                    p.publish_datetime.is_some_and(|d| d <= due_before)
+                        && p.next_attempt_at.is_none_or(|d| d <= due_before)
+                }
+                Status::Publishing => p.lease_expires_at.is_some_and(|d| d <= due_before),
+                _ => false,
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        due.sort_by_key(|p| p.publish_datetime);
+        Ok(due)
+    }
+    async fn next_due_time(&self) -> Result<Option<DateTime<Utc>>> {
+        let posts = self.posts.read().map_err(|e| anyhow!("{e}"))?;
+        Ok(posts.iter().filter_map(due_time).min())
+    }
+    async fn claim(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease_expires_at: DateTime<Utc>,
+    ) -> Result<Option<Post>> {
+        let now = Utc::now();
+        let mut posts = self.posts.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(post) = posts.iter_mut().find(|p| {
+            p.id == id
+                && p.deleted_at.is_none()
+                && match p.status {
+                    Status::Pending => {
+                        p.publish_datetime.is_some_and(|d| d <= now)
+                            && p.next_attempt_at.is_none_or(|d| d <= now)
+                    }
+                    Status::Publishing => p.lease_expires_at.is_some_and(|d| d <= now),
+                    _ => false,
+                }
+        }) else {
+            return Ok(None);
+        };
+        post.status = Status::Publishing;
+        post.lease_owner = Some(owner.to_string());
+        post.lease_expires_at = Some(lease_expires_at);
+        post.version += 1;
+        Ok(Some(post.clone()))
+    }
+    async fn renew(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease_expires_at: DateTime<Utc>,
+    ) -> Result<Option<Post>> {
+        let mut posts = self.posts.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(post) = posts.iter_mut().find(|p| {
+            p.id == id && p.status == Status::Publishing && p.lease_owner.as_deref() == Some(owner)
+        }) else {
+            return Ok(None);
+        };
+        post.lease_expires_at = Some(lease_expires_at);
+        post.version += 1;
+        Ok(Some(post.clone()))
+    }
+    async fn release(&self, id: Uuid, owner: &str) -> Result<Option<Post>> {
+        self.update_leased(id, owner, |post| post.status = Status::Pending)
+    }
+    async fn complete(
+        &self,
+        id: Uuid,
+        owner: &str,
+        published_at: DateTime<Utc>,
+        deliveries: &[Delivery],
+    ) -> Result<Option<Post>> {
+        self.update_leased(id, owner, |post| {
+            post.status = Status::Published;
+            post.publish_datetime = Some(published_at);
+            post.last_error = None;
+            post.next_attempt_at = None;
+            post.deliveries = deliveries.to_vec();
+        })
+    }
+    async fn fail(
+        &self,
+        id: Uuid,
+        owner: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        deliveries: &[Delivery],
+    ) -> Result<Option<Post>> {
+        self.update_leased(id, owner, |post| {
+            post.status = match retry_at {
+                Some(_) => Status::Pending,
+                None => Status::Failed,
+            };
+            post.attempts += 1;
+            post.last_error = Some(error.to_string());
+            post.next_attempt_at = retry_at;
+            post.deliveries = deliveries.to_vec();
+        })
+    }
+    async fn update(&self, post: &Post, edited_by: Option<i64>) -> Result<Option<Post>> {
+        let mut posts = self.posts.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(existing) = posts
+            .iter_mut()
+            .find(|p| p.id == post.id && p.deleted_at.is_none() && p.version == post.version)
+        else {
+            return Ok(None);
+        };
+        if let Some(revision) = PostRevision::new(existing, post, edited_by) {
+            let mut revisions = self.revisions.write().map_err(|e| anyhow!("{e}"))?;
+            revisions.push(revision);
+        }
+        *existing = Post {
+            deleted_at: None,
+            deleted_by: None,
+            version: post.version + 1,
+            ..post.clone()
+        };
+        Ok(Some(existing.clone()))
+    }
+    async fn list_revisions(
+        &self,
+        post_id: Uuid,
+        page_size: u32,
+        page_token: Option<&PageToken>,
+    ) -> Result<ListPostRevisionsResult> {
+        let revisions = self.revisions.read().map_err(|e| anyhow!("{e}"))?;
+        let mut found = revisions
+            .iter()
+            .filter(|r| r.post_id == post_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        found.sort_by_cached_key(PostRevision::page_token);
+        found.reverse();
+        let items = super::page(
+            &found,
+            1,
+            page_size,
+            page_token,
+            false,
+            PostRevision::page_token,
+        );
+        let (revisions, next_page_token) =
+            crate::split_page(items, page_size, PostRevision::page_token);
+        Ok(ListPostRevisionsResult {
+            revisions,
+            next_page_token,
+        })
+    }
+    async fn get_revision(&self, id: Uuid) -> Result<Option<PostRevision>> {
+        let revisions = self.revisions.read().map_err(|e| anyhow!("{e}"))?;
+        Ok(revisions.iter().find(|r| r.id == id).cloned())
+    }
+    async fn trash(
+        &self,
+        id: Uuid,
+        deleted_by: Option<i64>,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<Option<Post>> {
+        let mut posts = self.posts.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(post) = posts
+            .iter_mut()
+            .find(|p| p.id == id && p.deleted_at.is_none() && p.status != Status::Publishing)
+        else {
+            return Ok(None);
+        };
+        post.deleted_at = Some(deleted_at);
+        post.deleted_by = deleted_by;
+        post.version += 1;
+        Ok(Some(post.clone()))
+    }
+    async fn restore_deleted(&self, id: Uuid) -> Result<Option<Post>> {
+        let mut posts = self.posts.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(post) = posts
+            .iter_mut()
+            .find(|p| p.id == id && p.deleted_at.is_some())
+        else {
+            return Ok(None);
+        };
+        post.deleted_at = None;
+        post.deleted_by = None;
+        post.version += 1;
+        Ok(Some(post.clone()))
+    }
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64> {
+        let mut posts = self.posts.write().map_err(|e| anyhow!("{e}"))?;
+        let before = posts.len();
+        posts.retain(|p| p.deleted_at.is_none_or(|d| d >= deleted_before));
+        let mut revisions = self.revisions.write().map_err(|e| anyhow!("{e}"))?;
+        revisions.retain(|r| posts.iter().any(|p| p.id == r.post_id));
+        Ok((before - posts.len()) as u64)
+    }
+    async fn delete(&self, id: Uuid) -> Result<()> {
+        let mut posts = self.posts.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(index) = posts.iter().position(|p| p.id == id) else {
+            return Err(anyhow!("document not found"));
+        };
+        posts.remove(index);
+        let mut revisions = self.revisions.write().map_err(|e| anyhow!("{e}"))?;
+        revisions.retain(|r| r.post_id != id);
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::{
+    RwLock,
+    atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::{Result, anyhow};
+use shared::models::{ListUsersResult, PageToken, Role, User};
+use uuid::Uuid;
+
+use crate::UsersStorage;
+
+// Хранилище пользователей в памяти процесса
+#[derive(Debug, Default)]
+pub struct MemoryUsersStorage {
+    users: RwLock<Vec<User>>,
+    first_admin_claimed: AtomicBool,
+}
+impl MemoryUsersStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+#[async_trait::async_trait]
+impl UsersStorage for MemoryUsersStorage {
+    async fn create(&self, user: &User) -> Result<Option<User>> {
+        let mut users = self.users.write().map_err(|e| anyhow!("{e}"))?;
+        if users.iter().any(|u| u.id == user.id) {
+            return Err(anyhow!("duplicate key: {id}", id = user.id));
+        }
+        users.push(user.clone());
+        Ok(Some(user.clone()))
+    }
+    async fn get(&self, id: i64) -> Result<Option<User>> {
+        let users = self.users.read().map_err(|e| anyhow!("{e}"))?;
+        Ok(users.iter().find(|u| u.telegram_id == id).cloned())
+    }
+    async fn get_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        let users = self.users.read().map_err(|e| anyhow!("{e}"))?;
+        Ok(users
+            .iter()
+            .filter(|u| ids.contains(&u.id))
+            .cloned()
+            .collect())
+    }
+    async fn list_users(
+        &self,
+        page: u32,
+        page_size: u32,
+        role: Option<Role>,
+        sort_by_created_asc: bool,
+        page_token: Option<PageToken>,
+    ) -> Result<ListUsersResult> {
+        let users = self.users.read().map_err(|e| anyhow!("{e}"))?;
+        let mut filtered = users
+            .iter()
+            .filter(|u| role.is_none_or(|r| u.role == r))
+            .cloned()
+            .collect::<Vec<_>>();
+        filtered.sort_by_key(User::page_token);
+        if !sort_by_created_asc {
+            filtered.reverse();
+        }
+        let items = super::page(
+            &filtered,
+            page,
+            page_size,
+            page_token.as_ref(),
+            sort_by_created_asc,
+            User::page_token,
+        );
+        let (users, next_page_token) = crate::split_page(items, page_size, User::page_token);
+        if page_token.is_some() {
+            return Ok(ListUsersResult {
+                users,
+                total_count: 0,
+                current_page: 0,
+                total_pages: 0,
+                next_page_token,
+            });
+        }
+        Ok(ListUsersResult {
+            users,
+            total_count: filtered.len() as u32,
+            current_page: page,
+            total_pages: super::total_pages(filtered.len(), page_size),
+            next_page_token,
+        })
+    }
+    async fn update(&self, user: &User) -> Result<Option<User>> {
+        let mut users = self.users.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(existing) = users
+            .iter_mut()
+            .find(|u| u.id == user.id && u.version == user.version)
+        else {
+            return Ok(None);
+        };
+        existing.first_name = user.first_name.clone();
+        existing.last_name = user.last_name.clone();
+        existing.username = user.username.clone();
+        existing.language_code = user.language_code.clone();
+        existing.role = user.role;
+        existing.updated_at = user.updated_at;
+        existing.last_activity = user.last_activity;
+        existing.version += 1;
+        Ok(Some(existing.clone()))
+    }
+    async fn delete(&self, id: i64) -> Result<()> {
+        let mut users = self.users.write().map_err(|e| anyhow!("{e}"))?;
+        let Some(index) = users.iter().position(|u| u.telegram_id == id) else {
+            return Err(anyhow!("document not found"));
+        };
+        users.remove(index);
+        Ok(())
+    }
+    async fn claim_first_admin(&self, _telegram_id: i64) -> Result<bool> {
+        Ok(!self.first_admin_claimed.swap(true, Ordering::SeqCst))
+    }
+}
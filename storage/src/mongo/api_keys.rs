@@ -0,0 +1,63 @@
+use anyhow::Result;
+use bson::doc;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use shared::models::ApiKey;
+
+use super::users::is_duplicate_key;
+use crate::ApiKeysStorage;
+pub(super) const API_KEYS_COLLECTION: &str = "api_keys";
+
+#[derive(Clone, Debug)]
+pub struct MongoApiKeysStorage {
+    collection: mongodb::Collection<ApiKey>,
+}
+impl MongoApiKeysStorage {
+    pub fn new(db: mongodb::Database) -> Self {
+        let collection = db.collection(API_KEYS_COLLECTION);
+        Self { collection }
+    }
+}
+#[async_trait::async_trait]
+impl ApiKeysStorage for MongoApiKeysStorage {
+    async fn create(&self, key: &ApiKey) -> Result<Option<ApiKey>> {
+        match self.collection.insert_one(key).await {
+            Ok(_) => Ok(Some(key.clone())),
+            // Имя ключа уже занято
+            Err(e) if is_duplicate_key(&e) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    async fn get_by_hash(&self, token_hash: &str) -> Result<Option<ApiKey>> {
+        let res = self
+            .collection
+            .find_one(doc! {"token_hash": token_hash})
+            .await?;
+        Ok(res)
+    }
+    async fn list(&self) -> Result<Vec<ApiKey>> {
+        let keys = self
+            .collection
+            .find(doc! {})
+            .sort(doc! {"created_at": 1, "_id": 1})
+            .await?
+            .try_collect()
+            .await?;
+        Ok(keys)
+    }
+    async fn revoke(&self, name: &str, revoked_at: DateTime<Utc>) -> Result<Option<ApiKey>> {
+        let filter = doc! {
+            "name": name,
+            "revoked_at": bson::Bson::Null,
+        };
+        let update = doc! {
+            "$set": doc! { "revoked_at": bson::DateTime::from(revoked_at) },
+        };
+        let revoked = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await?;
+        Ok(revoked)
+    }
+}
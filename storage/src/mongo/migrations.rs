@@ -0,0 +1,398 @@
+use std::collections::HashSet;
+
+use anyhow::{Result, anyhow};
+use bson::{Document, doc};
+use futures::{TryStreamExt, future::BoxFuture};
+use mongodb::{Database, IndexModel, options::IndexOptions};
+
+use super::{
+    api_keys::API_KEYS_COLLECTION,
+    posts::{POST_REVISIONS_COLLECTION, POSTS_COLLECTION},
+    users::USERS_COLLECTION,
+};
+
+// Коллекция с примененными версиями схемы
+const MIGRATIONS_COLLECTION: &str = "_migrations";
+
+type Apply = for<'a> fn(&'a Database) -> BoxFuture<'a, Result<()>>;
+
+// Версионированная миграция схемы
+struct Migration {
+    version: i32,
+    description: &'static str,
+    apply: Apply,
+}
+
+// Миграции применяются по возрастанию версии; новые добавляются в конец
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "unique index on users.telegram_id",
+        apply: users_telegram_id_unique,
+    },
+    Migration {
+        version: 2,
+        description: "index users by role and creation time",
+        apply: users_role_created_at,
+    },
+    Migration {
+        version: 3,
+        description: "index posts by author and status",
+        apply: posts_author_status,
+    },
+    Migration {
+        version: 4,
+        description: "index posts by publish schedule and lease",
+        apply: posts_schedule,
+    },
+    Migration {
+        version: 5,
+        description: "backfill publish attempts and deliveries on posts",
+        apply: posts_backfill_attempts,
+    },
+    Migration {
+        version: 6,
+        description: "index posts and users by creation order for page tokens",
+        apply: creation_order,
+    },
+    Migration {
+        version: 7,
+        description: "index posts by author, status and publish time",
+        apply: posts_author_status_publish_datetime,
+    },
+    Migration {
+        version: 8,
+        description: "index posts by status and creation order across authors",
+        apply: posts_status_created_at,
+    },
+    Migration {
+        version: 9,
+        description: "text index on posts title and content",
+        apply: posts_text,
+    },
+    Migration {
+        version: 10,
+        description: "index posts by deletion time for trash purge",
+        apply: posts_deleted_at,
+    },
+    Migration {
+        version: 11,
+        description: "index post revisions by post and creation order",
+        apply: post_revisions_post_created_at,
+    },
+    Migration {
+        version: 12,
+        description: "backfill versions on posts and users",
+        apply: backfill_versions,
+    },
+    Migration {
+        version: 13,
+        description: "unique indexes on api_keys name and token hash",
+        apply: api_keys_unique,
+    },
+    Migration {
+        version: 14,
+        description: "drop text index on posts: search matches substrings",
+        apply: drop_posts_text,
+    },
+];
+
+// Применяет недостающие миграции и возвращает их описания
+pub async fn migrate(db: &Database) -> Result<Vec<String>> {
+    let journal = db.collection::<Document>(MIGRATIONS_COLLECTION);
+    let applied = journal
+        .find(doc! {})
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .iter()
+        .map(|d| d.get_i32("_id").map_err(|e| anyhow!("{e}")))
+        .collect::<Result<HashSet<_>>>()?;
+    let mut done = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| !applied.contains(&m.version)) {
+        (migration.apply)(db).await.map_err(|e| {
+            anyhow!(
+                "migration {version} ({description}) failed: {e}",
+                version = migration.version,
+                description = migration.description
+            )
+        })?;
+        journal
+            .insert_one(doc! {
+                "_id": migration.version,
+                "description": migration.description,
+                "applied_at": bson::DateTime::now(),
+            })
+            .await?;
+        done.push(format!("{}: {}", migration.version, migration.description));
+    }
+    Ok(done)
+}
+
+async fn create_index(db: &Database, collection: &str, name: &str, keys: Document) -> Result<()> {
+    create_index_with(db, collection, name, keys, IndexOptions::default()).await
+}
+
+async fn create_index_with(
+    db: &Database,
+    collection: &str,
+    name: &str,
+    keys: Document,
+    mut options: IndexOptions,
+) -> Result<()> {
+    options.name = Some(name.to_string());
+    let index = IndexModel::builder().keys(keys).options(options).build();
+    db.collection::<Document>(collection)
+        .create_index(index)
+        .await?;
+    Ok(())
+}
+
+// Повторный /start не должен создавать второго пользователя. Дубликаты,
+// созданные до индекса, сначала удаляются, иначе индекс не построится
+fn users_telegram_id_unique(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        dedupe_users(db).await?;
+        let options = IndexOptions::builder().unique(true).build();
+        create_index_with(
+            db,
+            USERS_COLLECTION,
+            "users_telegram_id",
+            doc! { "telegram_id": 1 },
+            options,
+        )
+        .await
+    })
+}
+
+// Из пользователей с одним telegram_id остается самый ранний, посты остальных
+// переходят к нему. Вручную то же делается в mongosh:
+//   db.users.aggregate([
+//     { $sort: { created_at: 1, _id: 1 } },
+//     { $group: { _id: "$telegram_id", ids: { $push: "$_id" } } },
+//     { $match: { "ids.1": { $exists: true } } },
+//   ]).forEach(g => {
+//     const [kept, ...rest] = g.ids;
+//     db.posts.updateMany({ author_id: { $in: rest } }, { $set: { author_id: kept } });
+//     db.users.deleteMany({ _id: { $in: rest } });
+//   })
+async fn dedupe_users(db: &Database) -> Result<()> {
+    let users = db.collection::<Document>(USERS_COLLECTION);
+    let posts = db.collection::<Document>(POSTS_COLLECTION);
+    let pipeline = vec![
+        doc! { "$sort": doc! { "created_at": 1, "_id": 1 } },
+        doc! { "$group": doc! { "_id": "$telegram_id", "ids": doc! { "$push": "$_id" } } },
+        doc! { "$match": doc! { "ids.1": doc! { "$exists": true } } },
+    ];
+    let groups: Vec<Document> = users.aggregate(pipeline).await?.try_collect().await?;
+    for group in groups {
+        let ids = group.get_array("ids")?;
+        let (kept, rest) = ids
+            .split_first()
+            .ok_or(anyhow!("empty group of duplicate users"))?;
+        posts
+            .update_many(
+                doc! { "author_id": doc! { "$in": rest.to_vec() } },
+                doc! { "$set": doc! { "author_id": kept.clone() } },
+            )
+            .await?;
+        users
+            .delete_many(doc! { "_id": doc! { "$in": rest.to_vec() } })
+            .await?;
+    }
+    Ok(())
+}
+
+fn users_role_created_at(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        create_index(
+            db,
+            USERS_COLLECTION,
+            "users_role_created_at",
+            doc! { "role": 1, "created_at": 1 },
+        )
+        .await
+    })
+}
+
+fn posts_author_status(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        create_index(
+            db,
+            POSTS_COLLECTION,
+            "posts_author_status",
+            doc! { "author_id": 1, "status": 1 },
+        )
+        .await
+    })
+}
+
+// Индексы для выборки готовых к публикации и зависших постов
+fn posts_schedule(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        create_index(
+            db,
+            POSTS_COLLECTION,
+            "posts_status_publish_datetime",
+            doc! { "status": 1, "publish_datetime": 1 },
+        )
+        .await?;
+        create_index(
+            db,
+            POSTS_COLLECTION,
+            "posts_status_lease_expires_at",
+            doc! { "status": 1, "lease_expires_at": 1 },
+        )
+        .await
+    })
+}
+
+// Посты, созданные до появления повторных попыток, не содержат этих полей
+fn posts_backfill_attempts(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        let posts = db.collection::<Document>(POSTS_COLLECTION);
+        posts
+            .update_many(
+                doc! { "attempts": doc! { "$exists": false } },
+                doc! { "$set": doc! { "attempts": 0 } },
+            )
+            .await?;
+        posts
+            .update_many(
+                doc! { "deliveries": doc! { "$exists": false } },
+                doc! { "$set": doc! { "deliveries": [] } },
+            )
+            .await?;
+        Ok(())
+    })
+}
+
+// Постраничная выборка сортирует по (created_at, _id)
+fn creation_order(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        create_index(
+            db,
+            POSTS_COLLECTION,
+            "posts_author_created_at",
+            doc! { "author_id": 1, "created_at": 1, "_id": 1 },
+        )
+        .await?;
+        create_index(
+            db,
+            USERS_COLLECTION,
+            "users_created_at",
+            doc! { "created_at": 1, "_id": 1 },
+        )
+        .await
+    })
+}
+
+// Очередь автора выводится в порядке публикации
+fn posts_author_status_publish_datetime(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        create_index(
+            db,
+            POSTS_COLLECTION,
+            "posts_author_status_publish_datetime",
+            doc! { "author_id": 1, "status": 1, "publish_datetime": 1, "_id": 1 },
+        )
+        .await
+    })
+}
+
+// Администраторы просматривают посты всех авторов по статусу
+fn posts_status_created_at(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        create_index(
+            db,
+            POSTS_COLLECTION,
+            "posts_status_created_at",
+            doc! { "status": 1, "created_at": 1, "_id": 1 },
+        )
+        .await
+    })
+}
+
+// Поиск постов по словам заголовка и содержимого
+fn posts_text(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        let options = IndexOptions::builder()
+            .default_language("russian".to_string())
+            .build();
+        create_index_with(
+            db,
+            POSTS_COLLECTION,
+            "posts_text",
+            doc! { "title": "text", "content": "text" },
+            options,
+        )
+        .await
+    })
+}
+
+// Корзина очищается по времени удаления; у остальных постов поля нет
+fn posts_deleted_at(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        let options = IndexOptions::builder().sparse(true).build();
+        create_index_with(
+            db,
+            POSTS_COLLECTION,
+            "posts_deleted_at",
+            doc! { "deleted_at": 1 },
+            options,
+        )
+        .await
+    })
+}
+
+// История правок выводится по посту от новых к старым
+fn post_revisions_post_created_at(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        create_index(
+            db,
+            POST_REVISIONS_COLLECTION,
+            "post_revisions_post_created_at",
+            doc! { "post_id": 1, "created_at": -1, "_id": -1 },
+        )
+        .await
+    })
+}
+
+// Записи, созданные до появления версий, получают первую версию
+fn backfill_versions(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        for collection in [POSTS_COLLECTION, USERS_COLLECTION] {
+            db.collection::<Document>(collection)
+                .update_many(
+                    doc! { "version": doc! { "$exists": false } },
+                    doc! { "$set": doc! { "version": 1 } },
+                )
+                .await?;
+        }
+        Ok(())
+    })
+}
+
+// Ключ находится по хешу токена; имя ключа не повторяется
+fn api_keys_unique(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        for (name, field) in [
+            ("api_keys_name", "name"),
+            ("api_keys_token_hash", "token_hash"),
+        ] {
+            let options = IndexOptions::builder().unique(true).build();
+            create_index_with(db, API_KEYS_COLLECTION, name, doc! { field: 1 }, options).await?;
+        }
+        Ok(())
+    })
+}
+
+// Поиск ищет подстроки, как в памяти и в SQL, и текстовым индексом не пользуется
+fn drop_posts_text(db: &Database) -> BoxFuture<'_, Result<()>> {
+    Box::pin(async move {
+        db.collection::<Document>(POSTS_COLLECTION)
+            .drop_index("posts_text")
+            .await?;
+        Ok(())
+    })
+}
@@ -0,0 +1,40 @@
+mod api_keys;
+mod migrations;
+mod posts;
+mod users;
+
+pub use api_keys::MongoApiKeysStorage;
+pub use migrations::migrate;
+pub use posts::MongoPostsStorage;
+pub use users::MongoUsersStorage;
+
+use bson::{Bson, Document, doc};
+use shared::models::{PageKey, PageToken};
+
+// Условие "после позиции токена" для сортировки по (field, _id). Пустое
+// значение поля меньше любого другого, как и при сортировке MongoDB
+fn after(field: &str, token: &PageToken, ascending: bool) -> Document {
+    let op = if ascending { "$gt" } else { "$lt" };
+    let key = match &token.key {
+        PageKey::Null => Bson::Null,
+        PageKey::Time(time) => bson::DateTime::from(*time).into(),
+        PageKey::Text(text) => text.clone().into(),
+    };
+    let conditions = match (&token.key, ascending) {
+        (PageKey::Null, true) => vec![
+            doc! { field: Bson::Null, "_id": doc! { op: token.id } },
+            doc! { field: doc! { "$ne": Bson::Null } },
+        ],
+        (PageKey::Null, false) => vec![doc! { field: Bson::Null, "_id": doc! { op: token.id } }],
+        (_, true) => vec![
+            doc! { field: doc! { op: &key } },
+            doc! { field: &key, "_id": doc! { op: token.id } },
+        ],
+        (_, false) => vec![
+            doc! { field: doc! { op: &key } },
+            doc! { field: &key, "_id": doc! { op: token.id } },
+            doc! { field: Bson::Null },
+        ],
+    };
+    doc! { "$or": conditions }
+}
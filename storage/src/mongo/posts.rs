@@ -0,0 +1,546 @@
+use anyhow::{Result, anyhow};
+use bson::doc;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use shared::models::{
+    Delivery, ListPostRevisionsResult, ListPostsQuery, ListPostsResult, PageToken, Post,
+    PostRevision, PostSortField, SearchPostsQuery, SearchPostsResult, Status,
+};
+use uuid::Uuid;
+
+use crate::PostsStorage;
+pub(super) const POSTS_COLLECTION: &str = "posts";
+pub(super) const POST_REVISIONS_COLLECTION: &str = "post_revisions";
+
+#[derive(Clone, Debug)]
+pub struct MongoPostsStorage {
+    collection: mongodb::Collection<Post>,
+    revisions: mongodb::Collection<PostRevision>,
+}
+
+impl MongoPostsStorage {
+    pub fn new(db: mongodb::Database) -> Self {
+        let collection = db.collection(POSTS_COLLECTION);
+        let revisions = db.collection(POST_REVISIONS_COLLECTION);
+        Self {
+            collection,
+            revisions,
+        }
+    }
+}
+#[async_trait::async_trait]
+impl PostsStorage for MongoPostsStorage {
+    async fn create(&self, post: &Post) -> Result<Option<Post>> {
+        self.collection.insert_one(post).await?;
+        let inserted = self.collection.find_one(doc! {"_id": post.id}).await?;
+        Ok(inserted)
+    }
+    async fn get(&self, id: Uuid) -> Result<Option<Post>> {
+        let res = self.collection.find_one(doc! {"_id": id}).await?;
+        Ok(res)
+    }
+    async fn list_posts(&self, query: &ListPostsQuery) -> Result<ListPostsResult> {
+        let mut filter = if query.deleted {
+            doc! { "deleted_at": doc! { "$ne": bson::Bson::Null } }
+        } else {
+            doc! { "deleted_at": bson::Bson::Null }
+        };
+        match query.author_ids.as_slice() {
+            [] => {}
+            [author_id] => {
+                filter.insert("author_id", author_id);
+            }
+            author_ids => {
+                filter.insert("author_id", doc! { "$in": author_ids });
+            }
+        }
+        if let Some(status) = query.status {
+            filter.insert("status", status.to_string());
+        }
+        for (field, from, to) in [
+            ("created_at", query.created_from, query.created_to),
+            ("publish_datetime", query.publish_from, query.publish_to),
+        ] {
+            let mut range = doc! {};
+            if let Some(from) = from {
+                range.insert("$gte", bson::DateTime::from(from));
+            }
+            if let Some(to) = to {
+                range.insert("$lt", bson::DateTime::from(to));
+            }
+            if !range.is_empty() {
+                filter.insert(field, range);
+            }
+        }
+        let field = match query.sort_by {
+            PostSortField::CreatedAt => "created_at",
+            PostSortField::PublishDatetime => "publish_datetime",
+            PostSortField::Title => "title",
+        };
+        let mut lpr = ListPostsResult {
+            posts: Vec::new(),
+            total_count: 0,
+            current_page: 0,
+            total_pages: 0,
+            next_page_token: None,
+        };
+        // По токену страница выбирается без подсчета и пропуска документов
+        let offset = match &query.page_token {
+            Some(token) => {
+                filter.extend(super::after(field, token, !query.descending));
+                0
+            }
+            None => {
+                let total_count = self.collection.count_documents(filter.clone()).await?;
+                lpr.total_count = total_count as u32;
+                lpr.current_page = query.page;
+                lpr.total_pages = total_count.div_ceil(query.page_size.max(1) as u64) as u32;
+                (query.page - 1) * query.page_size
+            }
+        };
+        let direction = if query.descending { -1 } else { 1 };
+        let sort = doc! {
+            field: direction,
+            "_id": direction,
+        };
+        let posts = self
+            .collection
+            .find(filter)
+            .sort(sort)
+            .skip(offset as u64)
+            .limit(query.page_size as i64 + 1)
+            .await?
+            .try_collect()
+            .await?;
+        (lpr.posts, lpr.next_page_token) =
+            crate::split_page(posts, query.page_size, |p| p.page_token(query.sort_by));
+        Ok(lpr)
+    }
+    async fn search_posts(&self, query: &SearchPostsQuery) -> Result<SearchPostsResult> {
+        let mut filter = doc! {
+            "deleted_at": bson::Bson::Null,
+        };
+        // Как в памяти и в SQL, каждое слово запроса ищется подстрокой без учета
+        // регистра в заголовке или содержимом
+        let terms = query
+            .terms()
+            .iter()
+            .map(|term| {
+                let pattern = doc! { "$regex": escape_regex(term), "$options": "i" };
+                doc! { "$or": [
+                    doc! { "title": pattern.clone() },
+                    doc! { "content": pattern },
+                ] }
+            })
+            .collect::<Vec<_>>();
+        if !terms.is_empty() {
+            filter.insert("$and", terms);
+        }
+        match query.author_ids.as_slice() {
+            [] => {}
+            [author_id] => {
+                filter.insert("author_id", author_id);
+            }
+            author_ids => {
+                filter.insert("author_id", doc! { "$in": author_ids });
+            }
+        }
+        if let Some(status) = query.status {
+            filter.insert("status", status.to_string());
+        }
+        let mut range = doc! {};
+        if let Some(from) = query.created_from {
+            range.insert("$gte", bson::DateTime::from(from));
+        }
+        if let Some(to) = query.created_to {
+            range.insert("$lt", bson::DateTime::from(to));
+        }
+        if !range.is_empty() {
+            filter.insert("created_at", range);
+        }
+        if let Some(token) = &query.page_token {
+            filter.extend(super::after("created_at", token, false));
+        }
+        let posts = self
+            .collection
+            .find(filter)
+            .sort(doc! { "created_at": -1, "_id": -1 })
+            .limit(query.page_size as i64 + 1)
+            .await?
+            .try_collect()
+            .await?;
+        let (posts, next_page_token) = crate::split_page(posts, query.page_size, |p| {
+            p.page_token(PostSortField::CreatedAt)
+        });
+        Ok(SearchPostsResult {
+            posts,
+            next_page_token,
+        })
+    }
+    async fn list_due_posts(&self, due_before: DateTime<Utc>) -> Result<Vec<Post>> {
+        let due_before = bson::DateTime::from(due_before);
+        let filter = doc! {
+            "$or": [
+                doc! {
+                    "status": Status::Pending.to_string(),
+                    "publish_datetime": doc! { "$lte": due_before },
+                    "$or": [
+                        doc! { "next_attempt_at": bson::Bson::Null },
+                        doc! { "next_attempt_at": doc! { "$lte": due_before } },
+                    ],
+                },
+                doc! {
+                    "status": Status::Publishing.to_string(),
+                    "lease_expires_at": doc! { "$lte": due_before },
+                },
+            ],
+            "deleted_at": bson::Bson::Null,
+        };
+        let sort = doc! {
+            "publish_datetime": 1,
+        };
+        let result = self
+            .collection
+            .find(filter)
+            .sort(sort)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(result)
+    }
+    async fn next_due_time(&self) -> Result<Option<DateTime<Utc>>> {
+        let pipeline = [
+            doc! {
+                "$match": doc! {
+                    "status": doc! {
+                        "$in": [Status::Pending.to_string(), Status::Publishing.to_string()],
+                    },
+                    "deleted_at": bson::Bson::Null,
+                },
+            },
+            doc! {
+                "$project": doc! {
+                    "due": doc! {
+                        "$cond": [
+                            doc! { "$eq": ["$status", Status::Publishing.to_string()] },
+                            "$lease_expires_at",
+                            doc! { "$max": ["$publish_datetime", "$next_attempt_at"] },
+                        ],
+                    },
+                },
+            },
+            doc! { "$match": doc! { "due": doc! { "$ne": bson::Bson::Null } } },
+            doc! { "$sort": doc! { "due": 1 } },
+            doc! { "$limit": 1 },
+        ];
+        let next = self
+            .collection
+            .aggregate(pipeline)
+            .await?
+            .try_next()
+            .await?
+            .and_then(|d| d.get_datetime("due").ok().copied())
+            .map(|d| d.to_chrono());
+        Ok(next)
+    }
+    async fn claim(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease_expires_at: DateTime<Utc>,
+    ) -> Result<Option<Post>> {
+        let now = bson::DateTime::from(Utc::now());
+        let filter = doc! {
+            "_id": id,
+            "deleted_at": bson::Bson::Null,
+            "$or": [
+                doc! {
+                    "status": Status::Pending.to_string(),
+                    "publish_datetime": doc! { "$lte": now },
+                    "$or": [
+                        doc! { "next_attempt_at": bson::Bson::Null },
+                        doc! { "next_attempt_at": doc! { "$lte": now } },
+                    ],
+                },
+                doc! {
+                    "status": Status::Publishing.to_string(),
+                    "lease_expires_at": doc! { "$lte": now },
+                },
+            ],
+        };
+        let update = doc! {
+            "$inc": doc! { "version": 1 },
+            "$set": doc! {
+                "status": Status::Publishing.to_string(),
+                "lease_owner": owner,
+                "lease_expires_at": bson::DateTime::from(lease_expires_at),
+            }
+        };
+        let claimed = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await?;
+        Ok(claimed)
+    }
+    async fn renew(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease_expires_at: DateTime<Utc>,
+    ) -> Result<Option<Post>> {
+        let filter = doc! {
+            "_id": id,
+            "status": Status::Publishing.to_string(),
+            "lease_owner": owner,
+        };
+        let update = doc! {
+            "$inc": doc! { "version": 1 },
+            "$set": doc! {
+                "lease_expires_at": bson::DateTime::from(lease_expires_at),
+            }
+        };
+        let renewed = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await?;
+        Ok(renewed)
+    }
+    async fn release(&self, id: Uuid, owner: &str) -> Result<Option<Post>> {
+        let filter = doc! {
+            "_id": id,
+            "status": Status::Publishing.to_string(),
+            "lease_owner": owner,
+        };
+        let update = doc! {
+            "$inc": doc! { "version": 1 },
+            "$set": doc! {
+                "status": Status::Pending.to_string(),
+                "lease_owner": bson::Bson::Null,
+                "lease_expires_at": bson::Bson::Null,
+            }
+        };
+        let released = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await?;
+        Ok(released)
+    }
+    async fn complete(
+        &self,
+        id: Uuid,
+        owner: &str,
+        published_at: DateTime<Utc>,
+        deliveries: &[Delivery],
+    ) -> Result<Option<Post>> {
+        let filter = doc! {
+            "_id": id,
+            "status": Status::Publishing.to_string(),
+            "lease_owner": owner,
+        };
+        let update = doc! {
+            "$inc": doc! { "version": 1 },
+            "$set": doc! {
+                "status": Status::Published.to_string(),
+                "publish_datetime": bson::DateTime::from(published_at),
+                "lease_owner": bson::Bson::Null,
+                "lease_expires_at": bson::Bson::Null,
+                "last_error": bson::Bson::Null,
+                "next_attempt_at": bson::Bson::Null,
+                "deliveries": bson::serialize_to_bson(deliveries)?,
+            }
+        };
+        let completed = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await?;
+        Ok(completed)
+    }
+    async fn fail(
+        &self,
+        id: Uuid,
+        owner: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        deliveries: &[Delivery],
+    ) -> Result<Option<Post>> {
+        let filter = doc! {
+            "_id": id,
+            "status": Status::Publishing.to_string(),
+            "lease_owner": owner,
+        };
+        let (status, next_attempt_at) = match retry_at {
+            Some(retry_at) => (
+                Status::Pending,
+                bson::Bson::from(bson::DateTime::from(retry_at)),
+            ),
+            None => (Status::Failed, bson::Bson::Null),
+        };
+        let update = doc! {
+            "$inc": doc! { "attempts": 1, "version": 1 },
+            "$set": doc! {
+                "status": status.to_string(),
+                "last_error": error,
+                "next_attempt_at": next_attempt_at,
+                "deliveries": bson::serialize_to_bson(deliveries)?,
+                "lease_owner": bson::Bson::Null,
+                "lease_expires_at": bson::Bson::Null,
+            }
+        };
+        let failed = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await?;
+        Ok(failed)
+    }
+    async fn update(&self, post: &Post, edited_by: Option<i64>) -> Result<Option<Post>> {
+        let query = doc! {
+            "_id": post.id,
+            "deleted_at": bson::Bson::Null,
+            "version": i64::from(post.version),
+        };
+        // Пост перемещается в корзину и восстанавливается только отдельными методами
+        let post = Post {
+            deleted_at: None,
+            deleted_by: None,
+            version: post.version + 1,
+            ..post.clone()
+        };
+        // Возвращается состояние поста до замены
+        let Some(previous) = self.collection.find_one_and_replace(query, &post).await? else {
+            return Ok(None);
+        };
+        if let Some(revision) = PostRevision::new(&previous, &post, edited_by) {
+            self.revisions.insert_one(revision).await?;
+        }
+        let updated = self.collection.find_one(doc! {"_id": post.id}).await?;
+        Ok(updated)
+    }
+    async fn list_revisions(
+        &self,
+        post_id: Uuid,
+        page_size: u32,
+        page_token: Option<&PageToken>,
+    ) -> Result<ListPostRevisionsResult> {
+        let mut filter = doc! { "post_id": post_id };
+        if let Some(token) = page_token {
+            filter.extend(super::after("created_at", token, false));
+        }
+        let revisions = self
+            .revisions
+            .find(filter)
+            .sort(doc! { "created_at": -1, "_id": -1 })
+            .limit(page_size as i64 + 1)
+            .await?
+            .try_collect()
+            .await?;
+        let (revisions, next_page_token) =
+            crate::split_page(revisions, page_size, PostRevision::page_token);
+        Ok(ListPostRevisionsResult {
+            revisions,
+            next_page_token,
+        })
+    }
+    async fn get_revision(&self, id: Uuid) -> Result<Option<PostRevision>> {
+        let res = self.revisions.find_one(doc! {"_id": id}).await?;
+        Ok(res)
+    }
+    async fn trash(
+        &self,
+        id: Uuid,
+        deleted_by: Option<i64>,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<Option<Post>> {
+        let filter = doc! {
+            "_id": id,
+            "deleted_at": bson::Bson::Null,
+            "status": doc! { "$ne": Status::Publishing.to_string() },
+        };
+        let update = doc! {
+            "$inc": doc! { "version": 1 },
+            "$set": doc! {
+                "deleted_at": bson::DateTime::from(deleted_at),
+                "deleted_by": deleted_by,
+            }
+        };
+        let trashed = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await?;
+        Ok(trashed)
+    }
+    async fn restore_deleted(&self, id: Uuid) -> Result<Option<Post>> {
+        let filter = doc! {
+            "_id": id,
+            "deleted_at": doc! { "$ne": bson::Bson::Null },
+        };
+        let update = doc! {
+            "$inc": doc! { "version": 1 },
+            "$unset": doc! {
+                "deleted_at": "",
+                "deleted_by": "",
+            }
+        };
+        let restored = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await?;
+        Ok(restored)
+    }
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64> {
+        let filter = doc! {
+            "deleted_at": doc! { "$lt": bson::DateTime::from(deleted_before) },
+        };
+        // Для удаления истории правок достаточно идентификаторов постов
+        let ids = self
+            .collection
+            .clone_with_type::<bson::Document>()
+            .find(filter)
+            .projection(doc! { "_id": 1 })
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .filter_map(|d| d.get("_id").cloned())
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let res = self
+            .collection
+            .delete_many(doc! { "_id": doc! { "$in": &ids } })
+            .await?;
+        self.revisions
+            .delete_many(doc! { "post_id": doc! { "$in": &ids } })
+            .await?;
+        Ok(res.deleted_count)
+    }
+    async fn delete(&self, id: Uuid) -> Result<()> {
+        let query = doc! {
+            "_id": id,
+        };
+        let res = self.collection.delete_one(query).await?;
+        if res.deleted_count == 0 {
+            return Err(anyhow!("document not found"));
+        }
+        self.revisions.delete_many(doc! { "post_id": id }).await?;
+        Ok(())
+    }
+}
+// Экранирует специальные символы регулярного выражения
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
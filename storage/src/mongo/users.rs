@@ -0,0 +1,159 @@
+use anyhow::{Result, anyhow};
+use bson::doc;
+use futures::TryStreamExt;
+use mongodb::error::{ErrorKind, WriteFailure};
+use shared::models::{ListUsersResult, PageToken, Role, User};
+use uuid::Uuid;
+
+use crate::UsersStorage;
+pub(super) const USERS_COLLECTION: &str = "users";
+// Однократные отметки первичной настройки
+const BOOTSTRAP_COLLECTION: &str = "_bootstrap";
+
+#[derive(Clone, Debug)]
+pub struct MongoUsersStorage {
+    collection: mongodb::Collection<User>,
+    bootstrap: mongodb::Collection<bson::Document>,
+}
+impl MongoUsersStorage {
+    pub fn new(db: mongodb::Database) -> Self {
+        let collection = db.collection(USERS_COLLECTION);
+        let bootstrap = db.collection(BOOTSTRAP_COLLECTION);
+        Self {
+            collection,
+            bootstrap,
+        }
+    }
+}
+// Нарушение уникального индекса (код 11000)
+pub(super) fn is_duplicate_key(error: &mongodb::error::Error) -> bool {
+    matches!(
+        error.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(e)) if e.code == 11000
+    )
+}
+#[async_trait::async_trait]
+impl UsersStorage for MongoUsersStorage {
+    async fn create(&self, user: &User) -> Result<Option<User>> {
+        match self.collection.insert_one(user).await {
+            Ok(_) => {}
+            // Пользователь уже создан конкурентным запросом
+            Err(e) if is_duplicate_key(&e) => {}
+            Err(e) => return Err(e.into()),
+        }
+        let inserted = self.get(user.telegram_id).await?;
+        Ok(inserted)
+    }
+    async fn get(&self, id: i64) -> Result<Option<User>> {
+        let res = self.collection.find_one(doc! {"telegram_id": id}).await?;
+        Ok(res)
+    }
+    async fn get_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let users = self
+            .collection
+            .find(doc! {"_id": {"$in": ids}})
+            .await?
+            .try_collect()
+            .await?;
+        Ok(users)
+    }
+    async fn list_users(
+        &self,
+        page: u32,
+        page_size: u32,
+        role: Option<Role>,
+        sort_by_created_asc: bool,
+        page_token: Option<PageToken>,
+    ) -> Result<ListUsersResult> {
+        let mut filter = if let Some(user_role) = role {
+            doc! {
+                "role": user_role.to_string(),
+            }
+        } else {
+            doc! {}
+        };
+        let mut lur = ListUsersResult {
+            users: Vec::new(),
+            total_count: 0,
+            current_page: 0,
+            total_pages: 0,
+            next_page_token: None,
+        };
+        // По токену страница выбирается без подсчета и пропуска документов
+        let offset = match page_token {
+            Some(token) => {
+                filter.extend(super::after("created_at", &token, sort_by_created_asc));
+                0
+            }
+            None => {
+                let total_count = self.collection.count_documents(filter.clone()).await?;
+                lur.total_count = total_count as u32;
+                lur.current_page = page;
+                lur.total_pages = total_count.div_ceil(page_size.max(1) as u64) as u32;
+                (page - 1) * page_size
+            }
+        };
+        let direction = if sort_by_created_asc { 1 } else { -1 };
+        let sort = doc! {
+            "created_at": direction,
+            "_id": direction,
+        };
+        let users = self
+            .collection
+            .find(filter)
+            .sort(sort)
+            .skip(offset as u64)
+            .limit(page_size as i64 + 1)
+            .await?
+            .try_collect()
+            .await?;
+        (lur.users, lur.next_page_token) = crate::split_page(users, page_size, User::page_token);
+        Ok(lur)
+    }
+    async fn update(&self, user: &User) -> Result<Option<User>> {
+        let filter = doc! {
+            "_id": user.id,
+            "version": i64::from(user.version),
+        };
+        let update = doc! {
+            "$inc": doc! { "version": 1 },
+            "$set": doc! {
+              "first_name": &user.first_name,
+              "last_name": user.last_name.as_ref(),
+              "username": &user.username,
+              "language_code": &user.language_code,
+              "role": user.role.to_string(),
+              "updated_at": bson::DateTime::from(user.updated_at),
+              "last_activity": bson::DateTime::from(user.last_activity),
+            }
+        };
+        let updated = self
+            .collection
+            .find_one_and_update(filter, update)
+            .return_document(mongodb::options::ReturnDocument::After)
+            .await?;
+        Ok(updated)
+    }
+    async fn delete(&self, id: i64) -> Result<()> {
+        let query = doc! {
+            "telegram_id": id,
+        };
+        let res = self.collection.delete_one(query).await?;
+        if res.deleted_count == 0 {
+            return Err(anyhow!("document not found"));
+        }
+        Ok(())
+    }
+    async fn claim_first_admin(&self, telegram_id: i64) -> Result<bool> {
+        // Уникальный _id пропускает только одну вставку
+        let guard = doc! { "_id": "first_admin", "telegram_id": telegram_id };
+        match self.bootstrap.insert_one(guard).await {
+            Ok(_) => Ok(true),
+            Err(e) if is_duplicate_key(&e) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
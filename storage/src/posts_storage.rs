@@ -1,94 +1,91 @@
-use anyhow::{Result, anyhow};
-use bson::doc;
-use futures::TryStreamExt;
-use shared::models::{ListPostsResult, Post, Status};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use shared::models::{
+    Delivery, ListPostRevisionsResult, ListPostsQuery, ListPostsResult, PageToken, Post,
+    PostRevision, SearchPostsQuery, SearchPostsResult,
+};
 use uuid::Uuid;
-const POSTS_COLLECTION: &str = "posts";
 
-#[derive(Clone, Debug)]
-pub struct PostsStorage {
-    collection: mongodb::Collection<Post>,
-}
-
-impl PostsStorage {
-    pub fn new(db: mongodb::Database) -> Self {
-        let collection = db.collection(POSTS_COLLECTION);
-        Self { collection }
-    }
-    pub async fn create(&self, post: &Post) -> Result<Option<Post>> {
-        self.collection.insert_one(post).await?;
-        let inserted = self.collection.find_one(doc! {"_id": post.id}).await?;
-        Ok(inserted)
-    }
-    pub async fn get(&self, id: Uuid) -> Result<Option<Post>> {
-        let res = self.collection.find_one(doc! {"_id": id}).await?;
-        Ok(res)
-    }
-    pub async fn list_posts(
+// Хранилище постов
+#[async_trait::async_trait]
+pub trait PostsStorage: std::fmt::Debug + Send + Sync {
+    // Сохраняет новый пост и возвращает сохраненную запись
+    async fn create(&self, post: &Post) -> Result<Option<Post>>;
+    // Возвращает пост по идентификатору
+    async fn get(&self, id: Uuid) -> Result<Option<Post>>;
+    // Возвращает страницу постов автора, отобранных и упорядоченных по запросу:
+    // по номеру (страницы начинаются с 1) или, если передан page_token, сразу
+    // после его позиции
+    async fn list_posts(&self, query: &ListPostsQuery) -> Result<ListPostsResult>;
+    // Возвращает страницу найденных постов от новых к старым, начиная
+    // с позиции page_token (если передан)
+    async fn search_posts(&self, query: &SearchPostsQuery) -> Result<SearchPostsResult>;
+    // Возвращает посты, время публикации которых наступило, и посты с истекшим захватом
+    async fn list_due_posts(&self, due_before: DateTime<Utc>) -> Result<Vec<Post>>;
+    // Возвращает ближайшее время, когда какой-либо пост станет готов к публикации
+    async fn next_due_time(&self) -> Result<Option<DateTime<Utc>>>;
+    // Захватывает готовый к публикации ожидающий пост (или пост с истекшим
+    // захватом) на тех же условиях, что и list_due_posts
+    async fn claim(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease_expires_at: DateTime<Utc>,
+    ) -> Result<Option<Post>>;
+    // Продлевает захват поста публикатором (None, если пост захвачен другим
+    // публикатором или уже освобожден)
+    async fn renew(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease_expires_at: DateTime<Utc>,
+    ) -> Result<Option<Post>>;
+    // Возвращает захваченный пост в очередь
+    async fn release(&self, id: Uuid, owner: &str) -> Result<Option<Post>>;
+    // Отмечает захваченный пост опубликованным
+    async fn complete(
         &self,
-        author_id: Uuid,
-        page: u32,
+        id: Uuid,
+        owner: &str,
+        published_at: DateTime<Utc>,
+        deliveries: &[Delivery],
+    ) -> Result<Option<Post>>;
+    // Записывает неудачную попытку публикации захваченного поста
+    async fn fail(
+        &self,
+        id: Uuid,
+        owner: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        deliveries: &[Delivery],
+    ) -> Result<Option<Post>>;
+    // Заменяет пост целиком и увеличивает его версию (None, если пост не найден,
+    // в корзине или его версия отличается от переданной). Если изменилось
+    // содержимое поста, сохраняет правку в истории
+    async fn update(&self, post: &Post, edited_by: Option<i64>) -> Result<Option<Post>>;
+    // Возвращает страницу правок поста от новых к старым, начиная с позиции
+    // page_token (если передан)
+    async fn list_revisions(
+        &self,
+        post_id: Uuid,
         page_size: u32,
-        status_filter: Option<Status>,
-    ) -> Result<ListPostsResult> {
-        let mut result = Vec::new();
-        let filter = match status_filter {
-            Some(status) => {
-                doc! {
-                    "author_id": author_id,
-                    "status": status.to_string(),
-                }
-            }
-            None => {
-                doc! {
-                    "author_id": author_id,
-                }
-            }
-        };
-
-        let total_count = self.collection.count_documents(filter.clone()).await?;
-        let total_pages = if total_count == 0 {
-            0
-        } else {
-            ((total_count as f64) / page_size as f64).ceil() as u32
-        };
-        let offset = (page - 1) * page_size;
-        let mut cursor = self
-            .collection
-            .find(filter)
-            .limit(page_size as i64)
-            .skip(offset as u64)
-            .await?;
-        while let Some(post) = cursor.try_next().await? {
-            result.push(post);
-        }
-        let lpr = ListPostsResult {
-            posts: result,
-            total_count: total_count as u32,
-            current_page: page,
-            total_pages,
-        };
-        Ok(lpr)
-    }
-    pub async fn update(&self, post: &Post) -> Result<Option<Post>> {
-        let query = doc! {
-            "_id": post.id,
-        };
-        let res = self.collection.replace_one(query.clone(), post).await?;
-        if res.modified_count == 0 {
-            return Err(anyhow!("document not found"));
-        }
-        let updated = self.collection.find_one(query).await?;
-        Ok(updated)
-    }
-    pub async fn delete(&self, id: Uuid) -> Result<()> {
-        let query = doc! {
-            "_id": id,
-        };
-        let res = self.collection.delete_one(query).await?;
-        if res.deleted_count == 0 {
-            return Err(anyhow!("document not found"));
-        }
-        Ok(())
-    }
+        page_token: Option<&PageToken>,
+    ) -> Result<ListPostRevisionsResult>;
+    // Возвращает правку по идентификатору
+    async fn get_revision(&self, id: Uuid) -> Result<Option<PostRevision>>;
+    // Перемещает пост в корзину (None, если пост не найден, уже в корзине
+    // или захвачен публикатором)
+    async fn trash(
+        &self,
+        id: Uuid,
+        deleted_by: Option<i64>,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<Option<Post>>;
+    // Возвращает пост из корзины (None, если поста нет в корзине)
+    async fn restore_deleted(&self, id: Uuid) -> Result<Option<Post>>;
+    // Окончательно удаляет посты (вместе с историей правок), перемещенные
+    // в корзину раньше заданного времени, и возвращает их количество
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64>;
+    // Удаляет пост вместе с историей правок (ошибка, если не найден)
+    async fn delete(&self, id: Uuid) -> Result<()>;
 }
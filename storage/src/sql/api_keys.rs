@@ -0,0 +1,101 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use shared::models::ApiKey;
+use sqlx::{AnyPool, Row, any::AnyRow};
+
+use super::{from_micros, from_optional_micros, to_micros};
+use crate::ApiKeysStorage;
+
+const COLUMNS: &str = "id, name, token_hash, scopes, created_at, expires_at, revoked_at";
+
+// Хранилище API-ключей в SQLite или PostgreSQL
+#[derive(Clone, Debug)]
+pub struct SqlApiKeysStorage {
+    pool: AnyPool,
+}
+impl SqlApiKeysStorage {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+fn from_row(row: &AnyRow) -> Result<ApiKey> {
+    Ok(ApiKey {
+        id: row.try_get::<String, _>("id")?.parse()?,
+        name: row.try_get("name")?,
+        token_hash: row.try_get("token_hash")?,
+        scopes: row
+            .try_get::<String, _>("scopes")?
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+            .collect::<Result<_>>()?,
+        created_at: from_micros(row.try_get("created_at")?)?,
+        expires_at: from_optional_micros(row.try_get("expires_at")?)?,
+        revoked_at: from_optional_micros(row.try_get("revoked_at")?)?,
+    })
+}
+#[async_trait::async_trait]
+impl ApiKeysStorage for SqlApiKeysStorage {
+    async fn create(&self, key: &ApiKey) -> Result<Option<ApiKey>> {
+        let scopes = key
+            .scopes
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let res = sqlx::query(&format!(
+            "INSERT INTO api_keys ({COLUMNS}) VALUES ($1, $2, $3, $4, $5, $6, $7)"
+        ))
+        .bind(key.id.to_string())
+        .bind(&key.name)
+        .bind(&key.token_hash)
+        .bind(scopes)
+        .bind(to_micros(key.created_at))
+        .bind(key.expires_at.map(to_micros))
+        .bind(key.revoked_at.map(to_micros))
+        .execute(&self.pool)
+        .await;
+        match res {
+            Ok(_) => Ok(Some(key.clone())),
+            // Имя ключа уже занято
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    async fn get_by_hash(&self, token_hash: &str) -> Result<Option<ApiKey>> {
+        let row = sqlx::query(&format!(
+            "SELECT {COLUMNS} FROM api_keys WHERE token_hash = $1"
+        ))
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(from_row).transpose()
+    }
+    async fn list(&self) -> Result<Vec<ApiKey>> {
+        sqlx::query(&format!(
+            "SELECT {COLUMNS} FROM api_keys ORDER BY created_at ASC, id ASC"
+        ))
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(from_row)
+        .collect()
+    }
+    async fn revoke(&self, name: &str, revoked_at: DateTime<Utc>) -> Result<Option<ApiKey>> {
+        let res = sqlx::query(
+            "UPDATE api_keys SET revoked_at = $1 WHERE name = $2 AND revoked_at IS NULL",
+        )
+        .bind(to_micros(revoked_at))
+        .bind(name)
+        .execute(&self.pool)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+        let row = sqlx::query(&format!("SELECT {COLUMNS} FROM api_keys WHERE name = $1"))
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(from_row).transpose()
+    }
+}
@@ -0,0 +1,157 @@
+mod api_keys;
+mod posts;
+mod users;
+
+pub use api_keys::SqlApiKeysStorage;
+pub use posts::SqlPostsStorage;
+pub use users::SqlUsersStorage;
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use shared::models::{PageKey, PageToken};
+use sqlx::{
+    Any, AnyPool,
+    any::{AnyArguments, AnyPoolOptions},
+    migrate::{Migrate, Migrator},
+};
+
+type Query<'q> = sqlx::query::Query<'q, Any, AnyArguments<'q>>;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+// Подключается к SQLite или PostgreSQL
+pub async fn connect(uri: &str) -> Result<AnyPool> {
+    sqlx::any::install_default_drivers();
+    // Файл SQLite создается при первом запуске
+    let uri = if uri.starts_with("sqlite:") && !uri.contains("mode=") {
+        let separator = if uri.contains('?') { '&' } else { '?' };
+        format!("{uri}{separator}mode=rwc")
+    } else {
+        uri.to_string()
+    };
+    let pool = AnyPoolOptions::new().connect(&uri).await?;
+    Ok(pool)
+}
+
+// Применяет встроенные миграции и возвращает описания новых
+pub async fn migrate(pool: &AnyPool) -> Result<Vec<String>> {
+    let applied = {
+        let mut conn = pool.acquire().await?;
+        conn.ensure_migrations_table().await?;
+        conn.list_applied_migrations().await?
+    };
+    MIGRATOR.run(pool).await?;
+    posts::backfill_search_text(pool).await?;
+    let done = MIGRATOR
+        .iter()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+        .map(|m| format!("{}: {}", m.version, m.description))
+        .collect();
+    Ok(done)
+}
+
+// Значение параметра запроса
+#[derive(Debug, Clone)]
+enum Param {
+    Int(i64),
+    Text(String),
+}
+impl From<i64> for Param {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+impl From<String> for Param {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+// Условия WHERE с последовательно пронумерованными параметрами ($1, $2, ...)
+// и их значениями в порядке номеров
+#[derive(Debug, Clone, Default)]
+struct Filter {
+    conditions: Vec<String>,
+    params: Vec<Param>,
+}
+impl Filter {
+    // Добавляет значение параметра и возвращает его плейсхолдер
+    fn param(&mut self, value: impl Into<Param>) -> String {
+        self.params.push(value.into());
+        format!("${}", self.params.len())
+    }
+    fn push(&mut self, condition: String) {
+        self.conditions.push(condition);
+    }
+    // Условие "после позиции токена" для сортировки по (column, id). Пустое
+    // значение меньше любого другого (см. order_by)
+    fn push_after(&mut self, column: &str, token: &PageToken, ascending: bool) {
+        let op = if ascending { ">" } else { "<" };
+        let key = match &token.key {
+            PageKey::Null => None,
+            PageKey::Time(time) => Some(Param::Int(to_micros(*time))),
+            PageKey::Text(text) => Some(Param::Text(text.clone())),
+        };
+        let condition = match key {
+            None => {
+                let id = self.param(token.id.to_string());
+                if ascending {
+                    format!("(({column} IS NULL AND id > {id}) OR {column} IS NOT NULL)")
+                } else {
+                    format!("({column} IS NULL AND id < {id})")
+                }
+            }
+            Some(key) => {
+                let (first, second) = (self.param(key.clone()), self.param(key));
+                let id = self.param(token.id.to_string());
+                let nulls = if ascending {
+                    String::new()
+                } else {
+                    format!(" OR {column} IS NULL")
+                };
+                format!("({column} {op} {first} OR ({column} = {second} AND id {op} {id}){nulls})")
+            }
+        };
+        self.push(condition);
+    }
+    fn clause(&self) -> String {
+        if self.conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", self.conditions.join(" AND "))
+        }
+    }
+    // Привязывает значения всех параметров к запросу
+    fn bind<'q>(&self, query: Query<'q>) -> Query<'q> {
+        self.params.iter().fold(query, |query, param| match param {
+            Param::Int(value) => query.bind(*value),
+            Param::Text(value) => query.bind(value.clone()),
+        })
+    }
+}
+
+// Порядок по (column, id). Для столбца, допускающего NULL, пустые значения
+// явно ставятся первыми при сортировке по возрастанию: SQLite и PostgreSQL
+// по умолчанию упорядочивают их по-разному
+fn order_by(column: &str, nullable: bool, ascending: bool) -> String {
+    let order = if ascending { "ASC" } else { "DESC" };
+    let nulls = if nullable {
+        format!("CASE WHEN {column} IS NULL THEN 0 ELSE 1 END {order}, ")
+    } else {
+        String::new()
+    };
+    format!("ORDER BY {nulls}{column} {order}, id {order}")
+}
+
+// Время хранится в микросекундах от начала эпохи UTC
+fn to_micros(datetime: DateTime<Utc>) -> i64 {
+    datetime.timestamp_micros()
+}
+
+fn from_micros(micros: i64) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp_micros(micros).ok_or(anyhow!("invalid timestamp: {micros}"))
+}
+
+fn from_optional_micros(micros: Option<i64>) -> Result<Option<DateTime<Utc>>> {
+    micros.map(from_micros).transpose()
+}
@@ -0,0 +1,688 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use shared::models::{
+    Delivery, ListPostRevisionsResult, ListPostsQuery, ListPostsResult, PageToken, Platform, Post,
+    PostContent, PostRevision, PostSortField, SearchPostsQuery, SearchPostsResult, Status,
+};
+use sqlx::{Any, AnyPool, Row, Transaction, any::AnyRow};
+use uuid::Uuid;
+
+use super::{Filter, Query, from_micros, from_optional_micros, order_by, to_micros};
+use crate::PostsStorage;
+
+const COLUMNS: &str = "id, title, content, tg_photo_file_id, vk_photo_file_id, tg_video_file_id, \
+                       vk_video_file_id, status, created_at, publish_datetime, author_id, \
+                       lease_owner, lease_expires_at, attempts, last_error, next_attempt_at, \
+                       deleted_at, deleted_by, version, review_comment";
+
+const REVISION_COLUMNS: &str = "id, post_id, edited_by, created_at, before_title, before_content, \
+                                before_tg_photo_file_id, before_vk_photo_file_id, \
+                                before_tg_video_file_id, before_vk_video_file_id, before_status, \
+                                before_publish_datetime, after_title, after_content, \
+                                after_tg_photo_file_id, after_vk_photo_file_id, \
+                                after_tg_video_file_id, after_vk_video_file_id, after_status, \
+                                after_publish_datetime";
+
+// Хранилище постов в SQLite или PostgreSQL
+#[derive(Clone, Debug)]
+pub struct SqlPostsStorage {
+    pool: AnyPool,
+}
+impl SqlPostsStorage {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+    // Загружает результаты доставки для постов
+    async fn with_deliveries(&self, mut posts: Vec<Post>) -> Result<Vec<Post>> {
+        for post in posts.iter_mut() {
+            post.deliveries = sqlx::query(
+                "SELECT platform, target_id, remote_id, permalink, delivered_at, error \
+                 FROM post_deliveries WHERE post_id = $1 ORDER BY ordinal",
+            )
+            .bind(post.id.to_string())
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(delivery_from_row)
+            .collect::<Result<_>>()?;
+        }
+        Ok(posts)
+    }
+    async fn fetch(&self, query: Query<'_>) -> Result<Vec<Post>> {
+        let posts = query
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(from_row)
+            .collect::<Result<Vec<_>>>()?;
+        self.with_deliveries(posts).await
+    }
+    // Завершает работу публикатора с захваченным постом
+    async fn finish_lease(
+        &self,
+        id: Uuid,
+        owner: &str,
+        assignments: &str,
+        bind: impl for<'q> FnOnce(Query<'q>) -> Query<'q>,
+        deliveries: Option<&[Delivery]>,
+    ) -> Result<Option<Post>> {
+        let mut tx = self.pool.begin().await?;
+        let sql = format!(
+            "UPDATE posts SET {assignments}, lease_owner = NULL, lease_expires_at = NULL, \
+             version = version + 1 WHERE id = $1 AND status = $2 AND lease_owner = $3"
+        );
+        let query = sqlx::query(&sql)
+            .bind(id.to_string())
+            .bind(Status::Publishing.to_string())
+            .bind(owner.to_string());
+        let res = bind(query).execute(&mut *tx).await?;
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+        if let Some(deliveries) = deliveries {
+            replace_deliveries(&mut tx, id, deliveries).await?;
+        }
+        tx.commit().await?;
+        self.get(id).await
+    }
+}
+fn from_row(row: &AnyRow) -> Result<Post> {
+    Ok(Post {
+        id: row.try_get::<String, _>("id")?.parse()?,
+        title: row.try_get("title")?,
+        content: row.try_get("content")?,
+        tg_photo_file_id: row.try_get("tg_photo_file_id")?,
+        vk_photo_file_id: row.try_get("vk_photo_file_id")?,
+        tg_video_file_id: row.try_get("tg_video_file_id")?,
+        vk_video_file_id: row.try_get("vk_video_file_id")?,
+        status: Status::from(row.try_get::<String, _>("status")?),
+        created_at: from_micros(row.try_get("created_at")?)?,
+        publish_datetime: from_optional_micros(row.try_get("publish_datetime")?)?,
+        author_id: row.try_get::<String, _>("author_id")?.parse()?,
+        lease_owner: row.try_get("lease_owner")?,
+        lease_expires_at: from_optional_micros(row.try_get("lease_expires_at")?)?,
+        attempts: row.try_get::<i64, _>("attempts")?.try_into()?,
+        last_error: row.try_get("last_error")?,
+        next_attempt_at: from_optional_micros(row.try_get("next_attempt_at")?)?,
+        deliveries: Vec::new(),
+        deleted_at: from_optional_micros(row.try_get("deleted_at")?)?,
+        deleted_by: row.try_get("deleted_by")?,
+        version: row.try_get::<i64, _>("version")?.try_into()?,
+        review_comment: row.try_get("review_comment")?,
+    })
+}
+fn delivery_from_row(row: &AnyRow) -> Result<Delivery> {
+    let platform: i64 = row.try_get("platform")?;
+    Ok(Delivery {
+        platform: Platform::try_from(i32::try_from(platform)?)?,
+        target_id: row.try_get("target_id")?,
+        remote_id: row.try_get("remote_id")?,
+        permalink: row.try_get("permalink")?,
+        delivered_at: from_optional_micros(row.try_get("delivered_at")?)?,
+        error: row.try_get("error")?,
+    })
+}
+fn revision_from_row(row: &AnyRow) -> Result<PostRevision> {
+    // Содержимое поста из столбцов с префиксом before_ или after_
+    let content = |prefix: &str| -> Result<PostContent> {
+        Ok(PostContent {
+            title: row.try_get(format!("{prefix}_title").as_str())?,
+            content: row.try_get(format!("{prefix}_content").as_str())?,
+            tg_photo_file_id: row.try_get(format!("{prefix}_tg_photo_file_id").as_str())?,
+            vk_photo_file_id: row.try_get(format!("{prefix}_vk_photo_file_id").as_str())?,
+            tg_video_file_id: row.try_get(format!("{prefix}_tg_video_file_id").as_str())?,
+            vk_video_file_id: row.try_get(format!("{prefix}_vk_video_file_id").as_str())?,
+            status: Status::from(row.try_get::<String, _>(format!("{prefix}_status").as_str())?),
+            publish_datetime: from_optional_micros(
+                row.try_get(format!("{prefix}_publish_datetime").as_str())?,
+            )?,
+        })
+    };
+    Ok(PostRevision {
+        id: row.try_get::<String, _>("id")?.parse()?,
+        post_id: row.try_get::<String, _>("post_id")?.parse()?,
+        edited_by: row.try_get("edited_by")?,
+        created_at: from_micros(row.try_get("created_at")?)?,
+        before: content("before")?,
+        after: content("after")?,
+    })
+}
+async fn insert_revision(tx: &mut Transaction<'_, Any>, revision: &PostRevision) -> Result<()> {
+    let sql = format!(
+        "INSERT INTO post_revisions ({REVISION_COLUMNS}) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, \
+                 $18, $19, $20)"
+    );
+    let query = sqlx::query(&sql)
+        .bind(revision.id.to_string())
+        .bind(revision.post_id.to_string())
+        .bind(revision.edited_by)
+        .bind(to_micros(revision.created_at));
+    let query = [&revision.before, &revision.after]
+        .into_iter()
+        .fold(query, |query, content| {
+            query
+                .bind(content.title.clone())
+                .bind(content.content.clone())
+                .bind(content.tg_photo_file_id.clone())
+                .bind(content.vk_photo_file_id.clone())
+                .bind(content.tg_video_file_id.clone())
+                .bind(content.vk_video_file_id.clone())
+                .bind(content.status.to_string())
+                .bind(content.publish_datetime.map(to_micros))
+        });
+    query.execute(&mut **tx).await?;
+    Ok(())
+}
+async fn replace_deliveries(
+    tx: &mut Transaction<'_, Any>,
+    post_id: Uuid,
+    deliveries: &[Delivery],
+) -> Result<()> {
+    sqlx::query("DELETE FROM post_deliveries WHERE post_id = $1")
+        .bind(post_id.to_string())
+        .execute(&mut **tx)
+        .await?;
+    for (ordinal, delivery) in deliveries.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO post_deliveries \
+             (post_id, ordinal, platform, target_id, remote_id, permalink, delivered_at, error) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(post_id.to_string())
+        .bind(ordinal as i64)
+        .bind(i64::from(i32::from(delivery.platform)))
+        .bind(delivery.target_id.clone())
+        .bind(delivery.remote_id.clone())
+        .bind(delivery.permalink.clone())
+        .bind(delivery.delivered_at.map(to_micros))
+        .bind(delivery.error.clone())
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+// Экранирует спецсимволы шаблона LIKE
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+// Заполняет текст для поиска (как Post::search_text) у постов, созданных
+// до его появления
+pub(super) async fn backfill_search_text(pool: &AnyPool) -> Result<()> {
+    let rows = sqlx::query("SELECT id, title, content FROM posts WHERE search_text IS NULL")
+        .fetch_all(pool)
+        .await?;
+    for row in rows {
+        let id: String = row.try_get("id")?;
+        let title: String = row.try_get("title")?;
+        let content: String = row.try_get("content")?;
+        sqlx::query("UPDATE posts SET search_text = $1 WHERE id = $2")
+            .bind(format!("{title}\n{content}").to_lowercase())
+            .bind(id)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+#[async_trait::async_trait]
+impl PostsStorage for SqlPostsStorage {
+    async fn create(&self, post: &Post) -> Result<Option<Post>> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&format!(
+            "INSERT INTO posts ({COLUMNS}, search_text) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, \
+                     $17, $18, $19, $20, $21)"
+        ))
+        .bind(post.id.to_string())
+        .bind(post.title.clone())
+        .bind(post.content.clone())
+        .bind(post.tg_photo_file_id.clone())
+        .bind(post.vk_photo_file_id.clone())
+        .bind(post.tg_video_file_id.clone())
+        .bind(post.vk_video_file_id.clone())
+        .bind(post.status.to_string())
+        .bind(to_micros(post.created_at))
+        .bind(post.publish_datetime.map(to_micros))
+        .bind(post.author_id.to_string())
+        .bind(post.lease_owner.clone())
+        .bind(post.lease_expires_at.map(to_micros))
+        .bind(i64::from(post.attempts))
+        .bind(post.last_error.clone())
+        .bind(post.next_attempt_at.map(to_micros))
+        .bind(post.deleted_at.map(to_micros))
+        .bind(post.deleted_by)
+        .bind(i64::from(post.version))
+        .bind(post.review_comment.clone())
+        .bind(post.search_text())
+        .execute(&mut *tx)
+        .await?;
+        replace_deliveries(&mut tx, post.id, &post.deliveries).await?;
+        tx.commit().await?;
+        self.get(post.id).await
+    }
+    async fn get(&self, id: Uuid) -> Result<Option<Post>> {
+        let sql = format!("SELECT {COLUMNS} FROM posts WHERE id = $1");
+        let query = sqlx::query(&sql).bind(id.to_string());
+        Ok(self.fetch(query).await?.pop())
+    }
+    async fn list_posts(&self, query: &ListPostsQuery) -> Result<ListPostsResult> {
+        let mut filter = Filter::default();
+        if query.deleted {
+            filter.push(String::from("deleted_at IS NOT NULL"));
+        } else {
+            filter.push(String::from("deleted_at IS NULL"));
+        }
+        if !query.author_ids.is_empty() {
+            let authors = query
+                .author_ids
+                .iter()
+                .map(|id| filter.param(id.to_string()))
+                .collect::<Vec<_>>();
+            filter.push(format!("author_id IN ({})", authors.join(", ")));
+        }
+        if let Some(status) = query.status {
+            let status = filter.param(status.to_string());
+            filter.push(format!("status = {status}"));
+        }
+        for (column, from, to) in [
+            ("created_at", query.created_from, query.created_to),
+            ("publish_datetime", query.publish_from, query.publish_to),
+        ] {
+            if let Some(from) = from {
+                let from = filter.param(to_micros(from));
+                filter.push(format!("{column} >= {from}"));
+            }
+            if let Some(to) = to {
+                let to = filter.param(to_micros(to));
+                filter.push(format!("{column} < {to}"));
+            }
+        }
+        let (column, nullable) = match query.sort_by {
+            PostSortField::CreatedAt => ("created_at", false),
+            PostSortField::PublishDatetime => ("publish_datetime", true),
+            PostSortField::Title => ("title", false),
+        };
+        let mut lpr = ListPostsResult {
+            posts: Vec::new(),
+            total_count: 0,
+            current_page: 0,
+            total_pages: 0,
+            next_page_token: None,
+        };
+        // По токену страница выбирается без подсчета и пропуска строк
+        let offset = match &query.page_token {
+            Some(token) => {
+                filter.push_after(column, token, !query.descending);
+                0
+            }
+            None => {
+                let sql = format!("SELECT COUNT(*) AS total FROM posts {}", filter.clause());
+                let total_count: i64 = filter
+                    .bind(sqlx::query(&sql))
+                    .fetch_one(&self.pool)
+                    .await?
+                    .try_get("total")?;
+                lpr.total_count = total_count as u32;
+                lpr.current_page = query.page;
+                lpr.total_pages =
+                    (total_count as u64).div_ceil(u64::from(query.page_size.max(1))) as u32;
+                (query.page - 1) * query.page_size
+            }
+        };
+        let where_clause = filter.clause();
+        let limit = filter.param(i64::from(query.page_size) + 1);
+        let skip = filter.param(i64::from(offset));
+        let sql = format!(
+            "SELECT {COLUMNS} FROM posts {where_clause} {order} LIMIT {limit} OFFSET {skip}",
+            order = order_by(column, nullable, !query.descending)
+        );
+        let posts = self.fetch(filter.bind(sqlx::query(&sql))).await?;
+        (lpr.posts, lpr.next_page_token) =
+            crate::split_page(posts, query.page_size, |p| p.page_token(query.sort_by));
+        Ok(lpr)
+    }
+    async fn search_posts(&self, query: &SearchPostsQuery) -> Result<SearchPostsResult> {
+        let mut filter = Filter::default();
+        filter.push(String::from("deleted_at IS NULL"));
+        for term in query.terms() {
+            let pattern = filter.param(format!("%{}%", escape_like(&term)));
+            filter.push(format!("search_text LIKE {pattern} ESCAPE '\\'"));
+        }
+        if !query.author_ids.is_empty() {
+            let authors = query
+                .author_ids
+                .iter()
+                .map(|id| filter.param(id.to_string()))
+                .collect::<Vec<_>>();
+            filter.push(format!("author_id IN ({})", authors.join(", ")));
+        }
+        if let Some(status) = query.status {
+            let status = filter.param(status.to_string());
+            filter.push(format!("status = {status}"));
+        }
+        if let Some(from) = query.created_from {
+            let from = filter.param(to_micros(from));
+            filter.push(format!("created_at >= {from}"));
+        }
+        if let Some(to) = query.created_to {
+            let to = filter.param(to_micros(to));
+            filter.push(format!("created_at < {to}"));
+        }
+        if let Some(token) = &query.page_token {
+            filter.push_after("created_at", token, false);
+        }
+        let where_clause = filter.clause();
+        let limit = filter.param(i64::from(query.page_size) + 1);
+        let sql = format!(
+            "SELECT {COLUMNS} FROM posts {where_clause} {order} LIMIT {limit}",
+            order = order_by("created_at", false, false)
+        );
+        let posts = self.fetch(filter.bind(sqlx::query(&sql))).await?;
+        let (posts, next_page_token) = crate::split_page(posts, query.page_size, |p| {
+            p.page_token(PostSortField::CreatedAt)
+        });
+        Ok(SearchPostsResult {
+            posts,
+            next_page_token,
+        })
+    }
+    async fn list_due_posts(&self, due_before: DateTime<Utc>) -> Result<Vec<Post>> {
+        let due_before = to_micros(due_before);
+        let sql = format!(
+            "SELECT {COLUMNS} FROM posts \
+             WHERE deleted_at IS NULL \
+               AND ((status = $1 AND publish_datetime <= $2 \
+                     AND (next_attempt_at IS NULL OR next_attempt_at <= $3)) \
+                 OR (status = $4 AND lease_expires_at <= $5)) \
+             ORDER BY publish_datetime"
+        );
+        let query = sqlx::query(&sql)
+            .bind(Status::Pending.to_string())
+            .bind(due_before)
+            .bind(due_before)
+            .bind(Status::Publishing.to_string())
+            .bind(due_before);
+        self.fetch(query).await
+    }
+    async fn next_due_time(&self) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query(
+            "SELECT MIN(CASE \
+                 WHEN status = $1 THEN lease_expires_at \
+                 WHEN next_attempt_at IS NULL OR publish_datetime >= next_attempt_at \
+                     THEN COALESCE(publish_datetime, next_attempt_at) \
+                 ELSE next_attempt_at END) AS due \
+             FROM posts WHERE status IN ($2, $3) AND deleted_at IS NULL",
+        )
+        .bind(Status::Publishing.to_string())
+        .bind(Status::Pending.to_string())
+        .bind(Status::Publishing.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+        from_optional_micros(row.try_get("due")?)
+    }
+    async fn claim(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease_expires_at: DateTime<Utc>,
+    ) -> Result<Option<Post>> {
+        let now = to_micros(Utc::now());
+        let res = sqlx::query(
+            "UPDATE posts SET status = $1, lease_owner = $2, lease_expires_at = $3, \
+             version = version + 1 WHERE id = $4 AND deleted_at IS NULL \
+               AND ((status = $5 AND publish_datetime <= $6 \
+                     AND (next_attempt_at IS NULL OR next_attempt_at <= $7)) \
+                 OR (status = $8 AND lease_expires_at <= $9))",
+        )
+        .bind(Status::Publishing.to_string())
+        .bind(owner.to_string())
+        .bind(to_micros(lease_expires_at))
+        .bind(id.to_string())
+        .bind(Status::Pending.to_string())
+        .bind(now)
+        .bind(now)
+        .bind(Status::Publishing.to_string())
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get(id).await
+    }
+    async fn renew(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease_expires_at: DateTime<Utc>,
+    ) -> Result<Option<Post>> {
+        let res = sqlx::query(
+            "UPDATE posts SET lease_expires_at = $1, version = version + 1 \
+             WHERE id = $2 AND status = $3 AND lease_owner = $4",
+        )
+        .bind(to_micros(lease_expires_at))
+        .bind(id.to_string())
+        .bind(Status::Publishing.to_string())
+        .bind(owner.to_string())
+        .execute(&self.pool)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get(id).await
+    }
+    async fn release(&self, id: Uuid, owner: &str) -> Result<Option<Post>> {
+        let status = Status::Pending.to_string();
+        self.finish_lease(id, owner, "status = $4", |q| q.bind(status), None)
+            .await
+    }
+    async fn complete(
+        &self,
+        id: Uuid,
+        owner: &str,
+        published_at: DateTime<Utc>,
+        deliveries: &[Delivery],
+    ) -> Result<Option<Post>> {
+        let status = Status::Published.to_string();
+        self.finish_lease(
+            id,
+            owner,
+            "status = $4, publish_datetime = $5, last_error = NULL, next_attempt_at = NULL",
+            |q| q.bind(status).bind(to_micros(published_at)),
+            Some(deliveries),
+        )
+        .await
+    }
+    async fn fail(
+        &self,
+        id: Uuid,
+        owner: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        deliveries: &[Delivery],
+    ) -> Result<Option<Post>> {
+        let status = match retry_at {
+            Some(_) => Status::Pending,
+            None => Status::Failed,
+        }
+        .to_string();
+        let error = error.to_string();
+        self.finish_lease(
+            id,
+            owner,
+            "status = $4, attempts = attempts + 1, last_error = $5, next_attempt_at = $6",
+            |q| q.bind(status).bind(error).bind(retry_at.map(to_micros)),
+            Some(deliveries),
+        )
+        .await
+    }
+    async fn update(&self, post: &Post, edited_by: Option<i64>) -> Result<Option<Post>> {
+        let mut tx = self.pool.begin().await?;
+        let sql = format!(
+            "SELECT {COLUMNS} FROM posts WHERE id = $1 AND deleted_at IS NULL AND version = $2"
+        );
+        let Some(previous) = sqlx::query(&sql)
+            .bind(post.id.to_string())
+            .bind(i64::from(post.version))
+            .fetch_optional(&mut *tx)
+            .await?
+            .as_ref()
+            .map(from_row)
+            .transpose()?
+        else {
+            return Ok(None);
+        };
+        let res = sqlx::query(
+            "UPDATE posts SET title = $1, content = $2, tg_photo_file_id = $3, \
+             vk_photo_file_id = $4, tg_video_file_id = $5, vk_video_file_id = $6, status = $7, \
+             created_at = $8, publish_datetime = $9, author_id = $10, lease_owner = $11, \
+             lease_expires_at = $12, attempts = $13, last_error = $14, next_attempt_at = $15, \
+             review_comment = $16, search_text = $17, version = version + 1 \
+             WHERE id = $18 AND deleted_at IS NULL AND version = $19",
+        )
+        .bind(post.title.clone())
+        .bind(post.content.clone())
+        .bind(post.tg_photo_file_id.clone())
+        .bind(post.vk_photo_file_id.clone())
+        .bind(post.tg_video_file_id.clone())
+        .bind(post.vk_video_file_id.clone())
+        .bind(post.status.to_string())
+        .bind(to_micros(post.created_at))
+        .bind(post.publish_datetime.map(to_micros))
+        .bind(post.author_id.to_string())
+        .bind(post.lease_owner.clone())
+        .bind(post.lease_expires_at.map(to_micros))
+        .bind(i64::from(post.attempts))
+        .bind(post.last_error.clone())
+        .bind(post.next_attempt_at.map(to_micros))
+        .bind(post.review_comment.clone())
+        .bind(post.search_text())
+        .bind(post.id.to_string())
+        .bind(i64::from(post.version))
+        .execute(&mut *tx)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+        replace_deliveries(&mut tx, post.id, &post.deliveries).await?;
+        if let Some(revision) = PostRevision::new(&previous, post, edited_by) {
+            insert_revision(&mut tx, &revision).await?;
+        }
+        tx.commit().await?;
+        self.get(post.id).await
+    }
+    async fn list_revisions(
+        &self,
+        post_id: Uuid,
+        page_size: u32,
+        page_token: Option<&PageToken>,
+    ) -> Result<ListPostRevisionsResult> {
+        let mut filter = Filter::default();
+        let post_id = filter.param(post_id.to_string());
+        filter.push(format!("post_id = {post_id}"));
+        if let Some(token) = page_token {
+            filter.push_after("created_at", token, false);
+        }
+        let where_clause = filter.clause();
+        let limit = filter.param(i64::from(page_size) + 1);
+        let sql = format!(
+            "SELECT {REVISION_COLUMNS} FROM post_revisions {where_clause} {order} LIMIT {limit}",
+            order = order_by("created_at", false, false)
+        );
+        let revisions = filter
+            .bind(sqlx::query(&sql))
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(revision_from_row)
+            .collect::<Result<Vec<_>>>()?;
+        let (revisions, next_page_token) =
+            crate::split_page(revisions, page_size, PostRevision::page_token);
+        Ok(ListPostRevisionsResult {
+            revisions,
+            next_page_token,
+        })
+    }
+    async fn get_revision(&self, id: Uuid) -> Result<Option<PostRevision>> {
+        let sql = format!("SELECT {REVISION_COLUMNS} FROM post_revisions WHERE id = $1");
+        sqlx::query(&sql)
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await?
+            .as_ref()
+            .map(revision_from_row)
+            .transpose()
+    }
+    async fn delete(&self, id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let res = sqlx::query("DELETE FROM posts WHERE id = $1")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(anyhow!("document not found"));
+        }
+        replace_deliveries(&mut tx, id, &[]).await?;
+        sqlx::query("DELETE FROM post_revisions WHERE post_id = $1")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    async fn trash(
+        &self,
+        id: Uuid,
+        deleted_by: Option<i64>,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<Option<Post>> {
+        let res = sqlx::query(
+            "UPDATE posts SET deleted_at = $1, deleted_by = $2, version = version + 1 \
+             WHERE id = $3 AND deleted_at IS NULL AND status <> $4",
+        )
+        .bind(to_micros(deleted_at))
+        .bind(deleted_by)
+        .bind(id.to_string())
+        .bind(Status::Publishing.to_string())
+        .execute(&self.pool)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get(id).await
+    }
+    async fn restore_deleted(&self, id: Uuid) -> Result<Option<Post>> {
+        let res = sqlx::query(
+            "UPDATE posts SET deleted_at = NULL, deleted_by = NULL, version = version + 1 \
+             WHERE id = $1 AND deleted_at IS NOT NULL",
+        )
+        .bind(id.to_string())
+        .execute(&self.pool)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get(id).await
+    }
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64> {
+        let deleted_before = to_micros(deleted_before);
+        let mut tx = self.pool.begin().await?;
+        for table in ["post_deliveries", "post_revisions"] {
+            sqlx::query(&format!(
+                "DELETE FROM {table} \
+                 WHERE post_id IN (SELECT id FROM posts WHERE deleted_at < $1)"
+            ))
+            .bind(deleted_before)
+            .execute(&mut *tx)
+            .await?;
+        }
+        let res = sqlx::query("DELETE FROM posts WHERE deleted_at < $1")
+            .bind(deleted_before)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Result, anyhow};
+use shared::models::{ListUsersResult, PageToken, Role, User};
+use sqlx::{AnyPool, Row, any::AnyRow};
+use uuid::Uuid;
+
+use super::{Filter, from_micros, order_by, to_micros};
+use crate::UsersStorage;
+
+const COLUMNS: &str = "id, telegram_id, first_name, last_name, username, language_code, role, \
+                       created_at, updated_at, last_activity, version";
+
+// Отметка о назначении первого администратора в таблице bootstrap
+const FIRST_ADMIN: &str = "first_admin";
+
+// Хранилище пользователей в SQLite или PostgreSQL
+#[derive(Clone, Debug)]
+pub struct SqlUsersStorage {
+    pool: AnyPool,
+}
+impl SqlUsersStorage {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+    async fn get_by_id(&self, id: Uuid) -> Result<Option<User>> {
+        let row = sqlx::query(&format!("SELECT {COLUMNS} FROM users WHERE id = $1"))
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(from_row).transpose()
+    }
+}
+fn from_row(row: &AnyRow) -> Result<User> {
+    Ok(User {
+        id: row.try_get::<String, _>("id")?.parse()?,
+        telegram_id: row.try_get("telegram_id")?,
+        first_name: row.try_get("first_name")?,
+        last_name: row.try_get("last_name")?,
+        username: row.try_get("username")?,
+        language_code: row.try_get("language_code")?,
+        role: Role::from(row.try_get::<String, _>("role")?),
+        created_at: from_micros(row.try_get("created_at")?)?,
+        updated_at: from_micros(row.try_get("updated_at")?)?,
+        last_activity: from_micros(row.try_get("last_activity")?)?,
+        version: row.try_get::<i64, _>("version")?.try_into()?,
+    })
+}
+#[async_trait::async_trait]
+impl UsersStorage for SqlUsersStorage {
+    async fn create(&self, user: &User) -> Result<Option<User>> {
+        sqlx::query(&format!(
+            "INSERT INTO users ({COLUMNS}) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"
+        ))
+        .bind(user.id.to_string())
+        .bind(user.telegram_id)
+        .bind(&user.first_name)
+        .bind(&user.last_name)
+        .bind(&user.username)
+        .bind(&user.language_code)
+        .bind(user.role.to_string())
+        .bind(to_micros(user.created_at))
+        .bind(to_micros(user.updated_at))
+        .bind(to_micros(user.last_activity))
+        .bind(i64::from(user.version))
+        .execute(&self.pool)
+        .await?;
+        self.get(user.telegram_id).await
+    }
+    async fn get(&self, id: i64) -> Result<Option<User>> {
+        let row = sqlx::query(&format!(
+            "SELECT {COLUMNS} FROM users WHERE telegram_id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(from_row).transpose()
+    }
+    async fn get_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut filter = Filter::default();
+        let params = ids
+            .iter()
+            .map(|id| filter.param(id.to_string()))
+            .collect::<Vec<_>>();
+        filter.push(format!("id IN ({})", params.join(", ")));
+        let sql = format!("SELECT {COLUMNS} FROM users {}", filter.clause());
+        filter
+            .bind(sqlx::query(&sql))
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(from_row)
+            .collect()
+    }
+    async fn list_users(
+        &self,
+        page: u32,
+        page_size: u32,
+        role: Option<Role>,
+        sort_by_created_asc: bool,
+        page_token: Option<PageToken>,
+    ) -> Result<ListUsersResult> {
+        let mut filter = Filter::default();
+        if let Some(role) = role {
+            let role = filter.param(role.to_string());
+            filter.push(format!("role = {role}"));
+        }
+        let mut lur = ListUsersResult {
+            users: Vec::new(),
+            total_count: 0,
+            current_page: 0,
+            total_pages: 0,
+            next_page_token: None,
+        };
+        // По токену страница выбирается без подсчета и пропуска строк
+        let offset = match &page_token {
+            Some(token) => {
+                filter.push_after("created_at", token, sort_by_created_asc);
+                0
+            }
+            None => {
+                let sql = format!("SELECT COUNT(*) AS total FROM users {}", filter.clause());
+                let total_count: i64 = filter
+                    .bind(sqlx::query(&sql))
+                    .fetch_one(&self.pool)
+                    .await?
+                    .try_get("total")?;
+                lur.total_count = total_count as u32;
+                lur.current_page = page;
+                lur.total_pages = (total_count as u64).div_ceil(u64::from(page_size.max(1))) as u32;
+                (page - 1) * page_size
+            }
+        };
+        let where_clause = filter.clause();
+        let limit = filter.param(i64::from(page_size) + 1);
+        let skip = filter.param(i64::from(offset));
+        let sql = format!(
+            "SELECT {COLUMNS} FROM users {where_clause} {order} LIMIT {limit} OFFSET {skip}",
+            order = order_by("created_at", false, sort_by_created_asc)
+        );
+        let users = filter
+            .bind(sqlx::query(&sql))
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(from_row)
+            .collect::<Result<Vec<_>>>()?;
+        (lur.users, lur.next_page_token) = crate::split_page(users, page_size, User::page_token);
+        Ok(lur)
+    }
+    async fn update(&self, user: &User) -> Result<Option<User>> {
+        let res = sqlx::query(
+            "UPDATE users SET first_name = $1, last_name = $2, username = $3, language_code = $4, \
+             role = $5, updated_at = $6, last_activity = $7, version = version + 1 \
+             WHERE id = $8 AND version = $9",
+        )
+        .bind(&user.first_name)
+        .bind(&user.last_name)
+        .bind(&user.username)
+        .bind(&user.language_code)
+        .bind(user.role.to_string())
+        .bind(to_micros(user.updated_at))
+        .bind(to_micros(user.last_activity))
+        .bind(user.id.to_string())
+        .bind(i64::from(user.version))
+        .execute(&self.pool)
+        .await?;
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get_by_id(user.id).await
+    }
+    async fn delete(&self, id: i64) -> Result<()> {
+        let res = sqlx::query("DELETE FROM users WHERE telegram_id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if res.rows_affected() == 0 {
+            return Err(anyhow!("document not found"));
+        }
+        Ok(())
+    }
+    async fn claim_first_admin(&self, telegram_id: i64) -> Result<bool> {
+        // Первичный ключ пропускает только одну вставку
+        let res = sqlx::query(
+            "INSERT INTO bootstrap (name, telegram_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(FIRST_ADMIN)
+        .bind(telegram_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected() == 1)
+    }
+}
@@ -0,0 +1,541 @@
+// Сценарии хранилища постов, общие для всех бэкендов: каждый тест
+// выполняется в памяти процесса и в SQLite
+use chrono::{DateTime, Duration, Utc};
+use shared::models::{ListPostsQuery, Post, PostSortField, SearchPostsQuery, Status};
+use storage::Storage;
+use uuid::Uuid;
+
+async fn backends() -> Vec<(&'static str, Storage)> {
+    // Отдельная база в памяти для каждого теста, общая для соединений пула
+    let uri = format!("sqlite:file:{}?mode=memory&cache=shared", Uuid::new_v4());
+    let sqlite = Storage::sql(&uri).await.unwrap();
+    vec![("memory", Storage::memory()), ("sqlite", sqlite)]
+}
+
+fn post(author_id: Uuid, title: &str, created_at: DateTime<Utc>) -> Post {
+    Post::builder()
+        .title(title)
+        .content("content")
+        .author_id(author_id)
+        .created_at(created_at)
+        .build()
+        .unwrap()
+}
+
+// Ожидающий пост с заданным временем публикации
+fn pending(publish_datetime: DateTime<Utc>) -> Post {
+    Post {
+        status: Status::Pending,
+        publish_datetime: Some(publish_datetime),
+        ..post(Uuid::new_v4(), "pending", Utc::now())
+    }
+}
+
+async fn create(db: &Storage, post: &Post) {
+    db.posts().create(post).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn page_token_pagination() {
+    for (name, db) in backends().await {
+        let author = Uuid::new_v4();
+        let start = Utc::now() - Duration::hours(1);
+        for i in 0..5 {
+            let created_at = start + Duration::minutes(i);
+            create(&db, &post(author, &format!("post {i}"), created_at)).await;
+        }
+        // Пост другого автора не попадает в выборку
+        create(&db, &post(Uuid::new_v4(), "other", start)).await;
+        let mut query = ListPostsQuery {
+            author_ids: vec![author],
+            sort_by: PostSortField::CreatedAt,
+            page: 1,
+            page_size: 2,
+            ..Default::default()
+        };
+        let mut titles = Vec::new();
+        let mut pages = 0;
+        loop {
+            let result = db.posts().list_posts(&query).await.unwrap();
+            titles.extend(result.posts.into_iter().map(|p| p.title));
+            pages += 1;
+            let Some(token) = result.next_page_token else {
+                break;
+            };
+            query.page_token = Some(token);
+        }
+        assert_eq!(pages, 3, "{name}");
+        let expected = (0..5).map(|i| format!("post {i}")).collect::<Vec<_>>();
+        assert_eq!(titles, expected, "{name}");
+    }
+}
+
+#[tokio::test]
+async fn due_posts() {
+    for (name, db) in backends().await {
+        let now = Utc::now();
+        let due = pending(now - Duration::minutes(1));
+        let future = pending(now + Duration::hours(1));
+        // Повтор после ошибки еще не наступил
+        let retrying = Post {
+            next_attempt_at: Some(now + Duration::minutes(5)),
+            ..pending(now - Duration::minutes(10))
+        };
+        let draft = Post {
+            publish_datetime: Some(now - Duration::minutes(1)),
+            ..post(Uuid::new_v4(), "draft", now)
+        };
+        for post in [&due, &future, &retrying, &draft] {
+            create(&db, post).await;
+        }
+        let listed = db.posts().list_due_posts(now).await.unwrap();
+        let ids = listed.iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(ids, [due.id], "{name}");
+        let next = db.posts().next_due_time().await.unwrap().unwrap();
+        assert_eq!(
+            next.timestamp_micros(),
+            due.publish_datetime.unwrap().timestamp_micros(),
+            "{name}"
+        );
+        // Захватываются только посты, которые возвращает list_due_posts
+        let lease = now + Duration::minutes(1);
+        for post in [&future, &retrying, &draft] {
+            let claimed = db.posts().claim(post.id, "worker", lease).await.unwrap();
+            assert!(claimed.is_none(), "{name}: {}", post.title);
+        }
+        let claimed = db.posts().claim(due.id, "worker", lease).await.unwrap();
+        assert!(claimed.is_some(), "{name}");
+    }
+}
+
+#[tokio::test]
+async fn claim_release_complete() {
+    for (name, db) in backends().await {
+        let now = Utc::now();
+        let post = pending(now - Duration::minutes(1));
+        create(&db, &post).await;
+        let lease = now + Duration::minutes(1);
+        let claimed = db
+            .posts()
+            .claim(post.id, "a", lease)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(claimed.status, Status::Publishing, "{name}");
+        assert_eq!(claimed.lease_owner.as_deref(), Some("a"), "{name}");
+        assert_eq!(claimed.version, post.version + 1, "{name}");
+        // Захват не истек: пост не достается другому публикатору и не
+        // освобождается чужим
+        assert!(
+            db.posts()
+                .claim(post.id, "b", lease)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(db.posts().release(post.id, "b").await.unwrap().is_none());
+        let released = db.posts().release(post.id, "a").await.unwrap().unwrap();
+        assert_eq!(released.status, Status::Pending, "{name}");
+        assert_eq!(released.lease_owner, None, "{name}");
+        db.posts()
+            .claim(post.id, "b", lease)
+            .await
+            .unwrap()
+            .unwrap();
+        let completed = db
+            .posts()
+            .complete(post.id, "b", now, &[])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(completed.status, Status::Published, "{name}");
+        assert_eq!(completed.lease_expires_at, None, "{name}");
+        // Опубликованный пост больше не захватывается
+        assert!(
+            db.posts()
+                .claim(post.id, "a", lease)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(db.posts().list_due_posts(now).await.unwrap().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn expired_lease() {
+    for (name, db) in backends().await {
+        let now = Utc::now();
+        let post = pending(now - Duration::minutes(1));
+        create(&db, &post).await;
+        // Публикатор "a" не успел завершить публикацию до истечения захвата
+        let expired = now - Duration::seconds(1);
+        db.posts()
+            .claim(post.id, "a", expired)
+            .await
+            .unwrap()
+            .unwrap();
+        let due = db.posts().list_due_posts(now).await.unwrap();
+        assert_eq!(due.len(), 1, "{name}");
+        let lease = now + Duration::minutes(1);
+        let reclaimed = db
+            .posts()
+            .claim(post.id, "b", lease)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reclaimed.lease_owner.as_deref(), Some("b"), "{name}");
+        // Прежний владелец больше не может завершить публикацию
+        assert!(
+            db.posts()
+                .complete(post.id, "a", now, &[])
+                .await
+                .unwrap()
+                .is_none(),
+            "{name}"
+        );
+        let failed = db
+            .posts()
+            .fail(post.id, "b", "network error", None, &[])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(failed.status, Status::Failed, "{name}");
+        assert_eq!(failed.attempts, 1, "{name}");
+        assert_eq!(
+            failed.last_error.as_deref(),
+            Some("network error"),
+            "{name}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn renew_lease() {
+    for (name, db) in backends().await {
+        let now = Utc::now();
+        let post = pending(now - Duration::minutes(1));
+        create(&db, &post).await;
+        db.posts()
+            .claim(post.id, "a", now + Duration::minutes(1))
+            .await
+            .unwrap()
+            .unwrap();
+        let extended = now + Duration::minutes(5);
+        // Продлить захват может только его владелец
+        assert!(
+            db.posts()
+                .renew(post.id, "b", extended)
+                .await
+                .unwrap()
+                .is_none(),
+            "{name}"
+        );
+        let renewed = db
+            .posts()
+            .renew(post.id, "a", extended)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            renewed.lease_expires_at.map(|t| t.timestamp_micros()),
+            Some(extended.timestamp_micros()),
+            "{name}"
+        );
+        // Продленный захват не истекает в прежний срок
+        let later = now + Duration::minutes(2);
+        assert!(
+            db.posts().list_due_posts(later).await.unwrap().is_empty(),
+            "{name}"
+        );
+        db.posts()
+            .complete(post.id, "a", now, &[])
+            .await
+            .unwrap()
+            .unwrap();
+        // Завершенную публикацию продлить нельзя
+        assert!(
+            db.posts()
+                .renew(post.id, "a", extended)
+                .await
+                .unwrap()
+                .is_none(),
+            "{name}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn search_matches_substrings() {
+    for (name, db) in backends().await {
+        let author = Uuid::new_v4();
+        let start = Utc::now() - Duration::hours(1);
+        let posts = [
+            ("Новости Недели", "Итоги конкурса"),
+            ("weekly digest", "Подводим ИТОГИ"),
+            ("Анонс", "конкурс 100% призов"),
+            ("other", "nothing here"),
+        ];
+        for (i, (title, content)) in posts.into_iter().enumerate() {
+            let post = Post {
+                content: content.to_string(),
+                ..post(author, title, start + Duration::minutes(i as i64))
+            };
+            create(&db, &post).await;
+        }
+        let search = |text: &str| SearchPostsQuery {
+            text: text.to_string(),
+            page_size: 10,
+            ..Default::default()
+        };
+        let titles = async |text: &str| {
+            db.posts()
+                .search_posts(&search(text))
+                .await
+                .unwrap()
+                .posts
+                .into_iter()
+                .map(|p| p.title)
+                .collect::<Vec<_>>()
+        };
+        // Подстрока без учета регистра, от новых к старым
+        assert_eq!(
+            titles("итог").await,
+            ["weekly digest", "Новости Недели"],
+            "{name}"
+        );
+        // Пост включает все слова запроса, в заголовке или содержимом
+        assert_eq!(titles("НЕДЕЛ конкурс").await, ["Новости Недели"], "{name}");
+        // Специальные символы ищутся как есть
+        assert_eq!(titles("100%").await, ["Анонс"], "{name}");
+        assert!(titles("10_%").await.is_empty(), "{name}");
+        assert!(titles("итоги анонс").await.is_empty(), "{name}");
+    }
+}
+
+#[tokio::test]
+async fn sort_by_title() {
+    for (name, db) in backends().await {
+        let author = Uuid::new_v4();
+        let now = Utc::now();
+        for title in ["b", "c", "a"] {
+            create(&db, &post(author, title, now)).await;
+        }
+        let mut query = ListPostsQuery {
+            author_ids: vec![author],
+            sort_by: PostSortField::Title,
+            page: 1,
+            page_size: 10,
+            ..Default::default()
+        };
+        let titles = async |query: &ListPostsQuery| {
+            db.posts()
+                .list_posts(query)
+                .await
+                .unwrap()
+                .posts
+                .into_iter()
+                .map(|p| p.title)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles(&query).await, ["a", "b", "c"], "{name}");
+        query.descending = true;
+        assert_eq!(titles(&query).await, ["c", "b", "a"], "{name}");
+    }
+}
+
+#[tokio::test]
+async fn trash_restore_purge() {
+    for (name, db) in backends().await {
+        let author = Uuid::new_v4();
+        let now = Utc::now();
+        let old = post(author, "old", now - Duration::minutes(2));
+        let recent = post(author, "recent", now - Duration::minutes(1));
+        create(&db, &old).await;
+        create(&db, &recent).await;
+        // Правка, которая удаляется вместе с постом
+        let edited = Post {
+            content: String::from("edited"),
+            ..old.clone()
+        };
+        db.posts().update(&edited, Some(1)).await.unwrap().unwrap();
+        let titles = async |deleted: bool| {
+            let query = ListPostsQuery {
+                author_ids: vec![author],
+                sort_by: PostSortField::CreatedAt,
+                page: 1,
+                page_size: 10,
+                deleted,
+                ..Default::default()
+            };
+            db.posts()
+                .list_posts(&query)
+                .await
+                .unwrap()
+                .posts
+                .into_iter()
+                .map(|p| p.title)
+                .collect::<Vec<_>>()
+        };
+        let retention = Duration::days(7);
+        let trashed = db
+            .posts()
+            .trash(old.id, Some(1), now - retention - Duration::days(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(trashed.deleted_by, Some(1), "{name}");
+        db.posts()
+            .trash(recent.id, Some(1), now)
+            .await
+            .unwrap()
+            .unwrap();
+        // Пост в корзине скрыт из списка, не ищется и не меняется
+        assert!(titles(false).await.is_empty(), "{name}");
+        assert_eq!(titles(true).await, ["old", "recent"], "{name}");
+        let search = SearchPostsQuery {
+            text: String::from("old"),
+            page_size: 10,
+            ..Default::default()
+        };
+        let found = db.posts().search_posts(&search).await.unwrap();
+        assert!(found.posts.is_empty(), "{name}");
+        assert!(
+            db.posts().update(&trashed, None).await.unwrap().is_none(),
+            "{name}"
+        );
+        assert!(
+            db.posts().trash(old.id, None, now).await.unwrap().is_none(),
+            "{name}"
+        );
+        // Восстановленный пост возвращается в список
+        let restored = db
+            .posts()
+            .restore_deleted(recent.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.deleted_at, None, "{name}");
+        assert_eq!(restored.deleted_by, None, "{name}");
+        assert!(
+            db.posts()
+                .restore_deleted(recent.id)
+                .await
+                .unwrap()
+                .is_none(),
+            "{name}"
+        );
+        assert_eq!(titles(false).await, ["recent"], "{name}");
+        // Очистка удаляет только посты, пролежавшие в корзине дольше срока,
+        // вместе с их правками
+        let purged = db.posts().purge_deleted(now - retention).await.unwrap();
+        assert_eq!(purged, 1, "{name}");
+        assert!(db.posts().get(old.id).await.unwrap().is_none(), "{name}");
+        let revisions = db.posts().list_revisions(old.id, 10, None).await.unwrap();
+        assert!(revisions.revisions.is_empty(), "{name}");
+        assert!(db.posts().get(recent.id).await.unwrap().is_some(), "{name}");
+    }
+}
+
+#[tokio::test]
+async fn version_conflict() {
+    for (name, db) in backends().await {
+        let post = post(Uuid::new_v4(), "title", Utc::now());
+        create(&db, &post).await;
+        // Две правки одной версии: вторая не перезаписывает первую
+        let first = Post {
+            title: String::from("first"),
+            ..post.clone()
+        };
+        let second = Post {
+            title: String::from("second"),
+            ..post.clone()
+        };
+        let updated = db.posts().update(&first, None).await.unwrap().unwrap();
+        assert_eq!(updated.version, post.version + 1, "{name}");
+        assert!(
+            db.posts().update(&second, None).await.unwrap().is_none(),
+            "{name}"
+        );
+        let current = db.posts().get(post.id).await.unwrap().unwrap();
+        assert_eq!(current.title, "first", "{name}");
+        // Изменения состояния публикации тоже меняют версию
+        let pending = Post {
+            status: Status::Pending,
+            publish_datetime: Some(Utc::now() - Duration::minutes(1)),
+            ..current.clone()
+        };
+        let pending = db.posts().update(&pending, None).await.unwrap().unwrap();
+        db.posts()
+            .claim(post.id, "worker", Utc::now() + Duration::minutes(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            db.posts().update(&pending, None).await.unwrap().is_none(),
+            "{name}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn revisions() {
+    for (name, db) in backends().await {
+        let original = post(Uuid::new_v4(), "v1", Utc::now());
+        create(&db, &original).await;
+        let mut current = original.clone();
+        for (title, edited_by) in [("v2", 1), ("v3", 2)] {
+            let edited = Post {
+                title: title.to_string(),
+                ..current.clone()
+            };
+            current = db
+                .posts()
+                .update(&edited, Some(edited_by))
+                .await
+                .unwrap()
+                .unwrap();
+        }
+        // Сохранение без изменений правкой не считается
+        db.posts().update(&current, Some(1)).await.unwrap().unwrap();
+        let mut revisions = Vec::new();
+        let mut page_token = None;
+        loop {
+            let page = db
+                .posts()
+                .list_revisions(original.id, 1, page_token.as_ref())
+                .await
+                .unwrap();
+            revisions.extend(page.revisions);
+            let Some(token) = page.next_page_token else {
+                break;
+            };
+            page_token = Some(token);
+        }
+        // Правки от новых к старым
+        let changes = revisions
+            .iter()
+            .map(|r| (r.before.title.as_str(), r.after.title.as_str(), r.edited_by))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [("v2", "v3", Some(2)), ("v1", "v2", Some(1))],
+            "{name}"
+        );
+        let revision = db
+            .posts()
+            .get_revision(revisions[1].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(revision.post_id, original.id, "{name}");
+        assert_eq!(revision.before.title, "v1", "{name}");
+        // История других постов не смешивается
+        let other = db
+            .posts()
+            .list_revisions(Uuid::new_v4(), 10, None)
+            .await
+            .unwrap();
+        assert!(other.revisions.is_empty(), "{name}");
+    }
+}
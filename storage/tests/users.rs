@@ -0,0 +1,25 @@
+// Сценарии хранилища пользователей, общие для всех бэкендов
+use storage::Storage;
+use uuid::Uuid;
+
+async fn backends() -> Vec<(&'static str, Storage)> {
+    // Отдельная база в памяти для каждого теста, общая для соединений пула
+    let uri = format!("sqlite:file:{}?mode=memory&cache=shared", Uuid::new_v4());
+    let sqlite = Storage::sql(&uri).await.unwrap();
+    vec![("memory", Storage::memory()), ("sqlite", sqlite)]
+}
+
+#[tokio::test]
+async fn first_admin_claimed_once() {
+    for (name, db) in backends().await {
+        // Одновременные регистрации: назначение достается только одной
+        let users = db.users();
+        let claims = futures::future::join_all(
+            (1..=8).map(|telegram_id| users.claim_first_admin(telegram_id)),
+        )
+        .await;
+        let granted = claims.into_iter().filter(|c| *c.as_ref().unwrap()).count();
+        assert_eq!(granted, 1, "{name}");
+        assert!(!db.users().claim_first_admin(9).await.unwrap(), "{name}");
+    }
+}
@@ -17,6 +17,23 @@ const POSTS_PREVIOUS_PAGE: &str = "⏮️ Предыдущая";
 const DRAFTS: &str = "Черновики";
 const PENDING: &str = "В очереди";
 const PUBLISHED: &str = "Опубликованные";
+const FAILED: &str = "Ошибки";
+const RETRY_POST: &str = "Повторить";
+const CONFIRM_DELETE_POST: &str = "В корзину";
+const RESTORE_POST: &str = "Восстановить";
+const TRASH_PAGE: &str = "Еще ⏭️";
+const POST_HISTORY: &str = "История";
+const RESTORE_REVISION: &str = "Вернуть";
+const SUBMIT_POST: &str = "Отправить на проверку";
+// Данные кнопки ограничены 64 байтами: полная надпись вместе с UUID не помещается
+const SUBMIT_POST_DATA: &str = "На проверку";
+const APPROVE_POST: &str = "Одобрить";
+const REJECT_POST: &str = "Отклонить";
+const UNSCHEDULE_POST: &str = "В черновики";
+const ABANDON_POST: &str = "Отменить пост";
+const REOPEN_POST: &str = "Возобновить";
+const UNPUBLISH_POST: &str = "Снять с площадок";
+const UNPUBLISH_POST_DATA: &str = "Снять";
 
 #[derive(Debug, Clone)]
 pub enum MyCallback {
@@ -39,6 +56,46 @@ pub enum MyCallback {
     SetPublishDate {
         id: Uuid,
     },
+    RetryPost {
+        id: Uuid,
+    },
+    ConfirmDeletePost {
+        id: Uuid,
+    },
+    RestorePost {
+        id: Uuid,
+    },
+    TrashPage {
+        author_id: i64,
+        page: u32,
+    },
+    PostHistory {
+        id: Uuid,
+    },
+    RestoreRevision {
+        id: Uuid,
+    },
+    SubmitPost {
+        id: Uuid,
+    },
+    ApprovePost {
+        id: Uuid,
+    },
+    RejectPost {
+        id: Uuid,
+    },
+    UnschedulePost {
+        id: Uuid,
+    },
+    AbandonPost {
+        id: Uuid,
+    },
+    ReopenPost {
+        id: Uuid,
+    },
+    UnpublishPost {
+        id: Uuid,
+    },
     PostsNextPage {
         author_id: i64,
         status: Status,
@@ -58,6 +115,9 @@ pub enum MyCallback {
     Published {
         author_id: i64,
     },
+    Failed {
+        author_id: i64,
+    },
 }
 impl MyCallback {
     pub fn data(&self) -> String {
@@ -69,18 +129,68 @@ impl MyCallback {
             MyCallback::Drafts { author_id } => format!("{self}:{author_id}"),
             MyCallback::Pending { author_id } => format!("{self}:{author_id}"),
             MyCallback::Published { author_id } => format!("{self}:{author_id}"),
+            MyCallback::Failed { author_id } => format!("{self}:{author_id}"),
             MyCallback::PublishNow { id } => {
-                let id = id.clone();
+                let id = *id;
                 format!("{self}:{id}")
             }
             MyCallback::DeletePost { id } => {
-                let id = id.clone();
+                let id = *id;
                 format!("{self}:{id}")
             }
             MyCallback::SetPublishDate { id } => {
-                let id = id.clone();
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::RetryPost { id } => {
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::ConfirmDeletePost { id } => {
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::RestorePost { id } => {
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::TrashPage { author_id, page } => format!("{self}:{author_id}:{page}"),
+            MyCallback::PostHistory { id } => {
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::RestoreRevision { id } => {
+                let id = *id;
                 format!("{self}:{id}")
             }
+            MyCallback::SubmitPost { id } => {
+                let id = *id;
+                format!("{SUBMIT_POST_DATA}:{id}")
+            }
+            MyCallback::ApprovePost { id } => {
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::RejectPost { id } => {
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::UnschedulePost { id } => {
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::AbandonPost { id } => {
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::ReopenPost { id } => {
+                let id = *id;
+                format!("{self}:{id}")
+            }
+            MyCallback::UnpublishPost { id } => {
+                let id = *id;
+                format!("{UNPUBLISH_POST_DATA}:{id}")
+            }
             MyCallback::PostsNextPage {
                 author_id,
                 status,
@@ -103,7 +213,10 @@ impl MyCallback {
                 MyCallback::Drafts { author_id: id }.into(),
                 MyCallback::Pending { author_id: id }.into(),
             ])
-            .append_row(vec![MyCallback::Published { author_id: id }.into()])
+            .append_row(vec![
+                MyCallback::Published { author_id: id }.into(),
+                MyCallback::Failed { author_id: id }.into(),
+            ])
     }
     pub fn editor_kb(id: i64) -> InlineKeyboardMarkup {
         InlineKeyboardMarkup::default()
@@ -115,7 +228,10 @@ impl MyCallback {
                 MyCallback::Drafts { author_id: id }.into(),
                 MyCallback::Pending { author_id: id }.into(),
             ])
-            .append_row(vec![MyCallback::Published { author_id: id }.into()])
+            .append_row(vec![
+                MyCallback::Published { author_id: id }.into(),
+                MyCallback::Failed { author_id: id }.into(),
+            ])
     }
     pub fn not_published_kb(id: Uuid) -> InlineKeyboardMarkup {
         InlineKeyboardMarkup::default()
@@ -123,10 +239,98 @@ impl MyCallback {
                 MyCallback::PublishNow { id }.into(),
                 MyCallback::SetPublishDate { id }.into(),
             ])
-            .append_row(vec![MyCallback::DeletePost { id }.into()])
+            .append_row(vec![MyCallback::AbandonPost { id }.into()])
+            .append_row(vec![
+                MyCallback::PostHistory { id }.into(),
+                MyCallback::DeletePost { id }.into(),
+            ])
+    }
+    pub fn draft_kb(id: Uuid) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::default()
+            .append_row(vec![
+                MyCallback::PublishNow { id }.into(),
+                MyCallback::SetPublishDate { id }.into(),
+            ])
+            .append_row(vec![
+                MyCallback::SubmitPost { id }.into(),
+                MyCallback::AbandonPost { id }.into(),
+            ])
+            .append_row(vec![
+                MyCallback::PostHistory { id }.into(),
+                MyCallback::DeletePost { id }.into(),
+            ])
+    }
+    pub fn pending_kb(id: Uuid) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::default()
+            .append_row(vec![
+                MyCallback::PublishNow { id }.into(),
+                MyCallback::SetPublishDate { id }.into(),
+            ])
+            .append_row(vec![
+                MyCallback::UnschedulePost { id }.into(),
+                MyCallback::AbandonPost { id }.into(),
+            ])
+            .append_row(vec![
+                MyCallback::PostHistory { id }.into(),
+                MyCallback::DeletePost { id }.into(),
+            ])
+    }
+    pub fn abandoned_kb(id: Uuid) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::default()
+            .append_row(vec![MyCallback::ReopenPost { id }.into()])
+            .append_row(vec![
+                MyCallback::PostHistory { id }.into(),
+                MyCallback::DeletePost { id }.into(),
+            ])
+    }
+    pub fn in_review_kb(id: Uuid) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::default()
+            .append_row(vec![
+                MyCallback::ApprovePost { id }.into(),
+                MyCallback::RejectPost { id }.into(),
+            ])
+            .append_row(vec![
+                MyCallback::PostHistory { id }.into(),
+                MyCallback::DeletePost { id }.into(),
+            ])
+    }
+    pub fn failed_kb(id: Uuid) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::default()
+            .append_row(vec![
+                MyCallback::RetryPost { id }.into(),
+                MyCallback::SetPublishDate { id }.into(),
+            ])
+            .append_row(vec![MyCallback::AbandonPost { id }.into()])
+            .append_row(vec![
+                MyCallback::PostHistory { id }.into(),
+                MyCallback::DeletePost { id }.into(),
+            ])
     }
     pub fn published_kb(id: Uuid) -> InlineKeyboardMarkup {
-        InlineKeyboardMarkup::default().append_row(vec![MyCallback::DeletePost { id }.into()])
+        InlineKeyboardMarkup::default()
+            .append_row(vec![MyCallback::UnpublishPost { id }.into()])
+            .append_row(vec![
+                MyCallback::PostHistory { id }.into(),
+                MyCallback::DeletePost { id }.into(),
+            ])
+    }
+    pub fn revision_kb(id: Uuid) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::default().append_row(vec![MyCallback::RestoreRevision { id }.into()])
+    }
+    pub fn confirm_delete_kb(id: Uuid) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::default().append_row(vec![
+            MyCallback::ConfirmDeletePost { id }.into(),
+            MyCallback::Cancel.into(),
+        ])
+    }
+    pub fn deleted_kb(id: Uuid) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::default().append_row(vec![MyCallback::RestorePost { id }.into()])
+    }
+    pub fn trash_has_next_kb(author_id: i64, page: u32) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::default().append_row(vec![
+            MyCallback::Cancel.into(),
+            MyCallback::TrashPage { author_id, page }.into(),
+        ])
     }
     pub fn has_next_kb(author_id: i64, status: Status, page: u32) -> InlineKeyboardMarkup {
         InlineKeyboardMarkup::default().append_row(vec![
@@ -188,11 +392,25 @@ impl Display for MyCallback {
             MyCallback::PublishNow { .. } => PUBLISH_NOW,
             MyCallback::DeletePost { .. } => DELETE_POST,
             MyCallback::SetPublishDate { .. } => SET_PUBLISH_DATE,
+            MyCallback::RetryPost { .. } => RETRY_POST,
+            MyCallback::ConfirmDeletePost { .. } => CONFIRM_DELETE_POST,
+            MyCallback::RestorePost { .. } => RESTORE_POST,
+            MyCallback::TrashPage { .. } => TRASH_PAGE,
+            MyCallback::PostHistory { .. } => POST_HISTORY,
+            MyCallback::RestoreRevision { .. } => RESTORE_REVISION,
+            MyCallback::SubmitPost { .. } => SUBMIT_POST,
+            MyCallback::ApprovePost { .. } => APPROVE_POST,
+            MyCallback::RejectPost { .. } => REJECT_POST,
+            MyCallback::UnschedulePost { .. } => UNSCHEDULE_POST,
+            MyCallback::AbandonPost { .. } => ABANDON_POST,
+            MyCallback::ReopenPost { .. } => REOPEN_POST,
+            MyCallback::UnpublishPost { .. } => UNPUBLISH_POST,
             MyCallback::PostsNextPage { .. } => POSTS_NEXT_PAGE,
             MyCallback::PostsPreviousPage { .. } => POSTS_PREVIOUS_PAGE,
             MyCallback::Drafts { .. } => DRAFTS,
             MyCallback::Pending { .. } => PENDING,
             MyCallback::Published { .. } => PUBLISHED,
+            MyCallback::Failed { .. } => FAILED,
         };
         write!(f, "{s}")
     }
@@ -235,6 +453,61 @@ impl FromStr for MyCallback {
                 let id = data.parse()?;
                 Ok(Self::SetPublishDate { id })
             }
+            RETRY_POST => {
+                let id = data.parse()?;
+                Ok(Self::RetryPost { id })
+            }
+            CONFIRM_DELETE_POST => {
+                let id = data.parse()?;
+                Ok(Self::ConfirmDeletePost { id })
+            }
+            RESTORE_POST => {
+                let id = data.parse()?;
+                Ok(Self::RestorePost { id })
+            }
+            POST_HISTORY => {
+                let id = data.parse()?;
+                Ok(Self::PostHistory { id })
+            }
+            RESTORE_REVISION => {
+                let id = data.parse()?;
+                Ok(Self::RestoreRevision { id })
+            }
+            SUBMIT_POST_DATA => {
+                let id = data.parse()?;
+                Ok(Self::SubmitPost { id })
+            }
+            APPROVE_POST => {
+                let id = data.parse()?;
+                Ok(Self::ApprovePost { id })
+            }
+            REJECT_POST => {
+                let id = data.parse()?;
+                Ok(Self::RejectPost { id })
+            }
+            UNSCHEDULE_POST => {
+                let id = data.parse()?;
+                Ok(Self::UnschedulePost { id })
+            }
+            ABANDON_POST => {
+                let id = data.parse()?;
+                Ok(Self::AbandonPost { id })
+            }
+            REOPEN_POST => {
+                let id = data.parse()?;
+                Ok(Self::ReopenPost { id })
+            }
+            UNPUBLISH_POST_DATA => {
+                let id = data.parse()?;
+                Ok(Self::UnpublishPost { id })
+            }
+            TRASH_PAGE => {
+                let (author_id, page) = data.split_once(':').ok_or(anyhow!("not a callback"))?;
+                Ok(Self::TrashPage {
+                    author_id: author_id.parse()?,
+                    page: page.parse()?,
+                })
+            }
             DRAFTS => {
                 let author_id = data.parse()?;
                 Ok(Self::Drafts { author_id })
@@ -247,6 +520,10 @@ impl FromStr for MyCallback {
                 let author_id = data.parse()?;
                 Ok(Self::Published { author_id })
             }
+            FAILED => {
+                let author_id = data.parse()?;
+                Ok(Self::Failed { author_id })
+            }
             POSTS_NEXT_PAGE => {
                 let s = data.split(':').collect::<Vec<_>>();
                 if s.len() != 3 {
@@ -8,4 +8,6 @@ pub enum Command {
     Help,
     /// Вызвать меню
     Start,
+    /// Найти посты по словам: /search <запрос>
+    Search(String),
 }
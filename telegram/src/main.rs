@@ -1,16 +1,18 @@
 mod commands;
 pub use commands::Command;
 mod state;
-use publisher::Publisher;
-use shared::models::{Post, Status};
+use publisher::{Publisher, TelegramPlatform, VkPlatform};
+use shared::models::{Post, PostField, PostRevision, Status};
 pub use state::State;
 mod callback;
 pub use callback::MyCallback;
 mod text_commands;
 pub use text_commands::TextCommand;
+mod pages;
 mod router;
 use anyhow::Result;
 use clap::Parser;
+pub use pages::PageTokens;
 use teloxide::{
     dispatching::dialogue::InMemStorage, dptree::deps, payloads::DeleteWebhookSetters, prelude::*,
     types::InputFile, utils::command::BotCommands,
@@ -22,9 +24,30 @@ struct Cli {
     /// Define port to serve
     #[arg(short, long)]
     port: Option<u16>,
-    /// Bearer token
+    /// Server host name or address (defaults to ::1)
+    #[arg(long)]
+    host: Option<String>,
+    /// Connect to the server over TLS
+    #[arg(long)]
+    tls: bool,
+    /// PEM CA certificate of the server (system roots by default)
+    #[arg(long)]
+    tls_ca: Option<std::path::PathBuf>,
+    /// PEM client certificate for mutual TLS
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<std::path::PathBuf>,
+    /// PEM private key of the client certificate
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<std::path::PathBuf>,
+    /// Server name in its certificate, if it differs from the host
+    #[arg(long)]
+    tls_domain: Option<String>,
+    /// API key token with bot and publisher scopes
     #[arg(short, long)]
-    bearer: Option<String>,
+    bearer: String,
+    /// Secret for acting user signatures, shared with the server
+    #[arg(long)]
+    signing_key: String,
     /// Telegram bot token
     #[arg(long)]
     tgtoken: String,
@@ -48,14 +71,31 @@ async fn main() -> Result<()> {
     // config
     let cli = Cli::parse();
     let port = cli.port.unwrap_or(50052);
-    let bearer = cli.bearer.unwrap_or("some-secret-token".into());
+    let bearer = cli.bearer;
     let tg_token = cli.tgtoken;
-    let tg_channel = cli.tgchannel * -1;
+    let tg_channel = -cli.tgchannel;
     let vk_token = cli.vktoken;
     let vk_group = cli.vkgroup;
 
     // rpc
-    let rpc_client = client::Client::new(port, bearer).await?;
+    let host = cli.host.unwrap_or(String::from("::1"));
+    // Любой из параметров TLS включает шифрование соединения
+    let tls = if cli.tls || cli.tls_ca.is_some() || cli.tls_cert.is_some() {
+        let identity = match (cli.tls_cert, cli.tls_key) {
+            (Some(cert), Some(key)) => Some((std::fs::read(cert)?, std::fs::read(key)?)),
+            _ => None,
+        };
+        Some(client::TlsOptions {
+            ca_cert: cli.tls_ca.map(std::fs::read).transpose()?,
+            identity,
+            domain: cli.tls_domain,
+        })
+    } else {
+        None
+    };
+    let rpc_client = client::Client::connect(&host, port, bearer, tls)
+        .await?
+        .with_signing_key(&cli.signing_key);
 
     // tg
     let bot = teloxide::Bot::new(tg_token);
@@ -68,17 +108,21 @@ async fn main() -> Result<()> {
 
     // publisher
 
-    let publisher = Publisher::new(
-        bot.clone(),
-        tg_channel,
-        rpc_client.clone(),
-        vk_client.clone(),
-    );
-    tokio::spawn(publisher.run());
+    let publisher = Publisher::new(rpc_client.clone())
+        .with_platform(VkPlatform::new(vk_client.clone()))
+        .with_platform(TelegramPlatform::new(bot.clone(), tg_channel));
+    let waker = publisher.waker();
+    tokio::spawn(publisher.clone().run());
 
     // bot
     Dispatcher::builder(bot, router::master())
-        .dependencies(deps![InMemStorage::<State>::new(), rpc_client, vk_client])
+        .dependencies(deps![
+            InMemStorage::<State>::new(),
+            PageTokens::new(),
+            rpc_client,
+            publisher,
+            waker
+        ])
         .default_handler(|upd| async move {
             tracing::warn!("Unhandled update: {upd:?}");
         })
@@ -106,7 +150,101 @@ pub fn to_utc(string: &str) -> Result<chrono::DateTime<chrono::Utc>> {
     Ok(moscow_dt.with_timezone(&chrono::Utc))
 }
 
+// Сообщение пользователю об ошибке сервера
+pub fn error_text(error: &client::Error) -> String {
+    match error {
+        client::Error::NotFound { resource } => match resource.as_str() {
+            "post" => String::from("Пост не найден, возможно он уже удален"),
+            "user" => String::from("Пользователь не найден"),
+            "author" => String::from("Автор не найден"),
+            "revision" => String::from("Правка не найдена"),
+            _ => String::from("Не найдено"),
+        },
+        client::Error::InvalidArgument { violations, .. } if !violations.is_empty() => {
+            let fields = violations
+                .iter()
+                .map(|v| {
+                    format!(
+                        "\n{field}: {description}",
+                        field = v.field,
+                        description = v.description
+                    )
+                })
+                .collect::<String>();
+            format!("Некорректные данные:{fields}")
+        }
+        client::Error::InvalidArgument { message, .. } => format!("Некорректные данные: {message}"),
+        client::Error::InvalidTransition {
+            from: Some(from),
+            to: Some(to),
+            ..
+        } => format!("Нельзя перевести пост из статуса {from} в {to}"),
+        client::Error::InvalidTransition { .. } => {
+            String::from("Действие недоступно для поста в текущем статусе")
+        }
+        client::Error::PermissionDenied(_) | client::Error::Unauthenticated(_) => {
+            String::from("У вас нет доступа")
+        }
+        client::Error::Conflict(_) => String::from("Данные были изменены, попробуйте еще раз"),
+        client::Error::ReviewRequired(_) => {
+            String::from("Пост нужно отправить на проверку и дождаться одобрения администратора")
+        }
+        e if e.is_transient() => String::from("Сервер недоступен, попробуйте позже"),
+        _ => String::from("Произошла ошибка, попробуйте позже"),
+    }
+}
+
+// Описание правки поста: кто и когда изменил пост и его содержимое до правки
+pub fn revision_text(revision: &PostRevision, editor: Option<&str>) -> String {
+    let changes = revision
+        .changes()
+        .iter()
+        .map(|field| match field {
+            PostField::Title => "заголовок",
+            PostField::Content => "текст",
+            PostField::Media => "фото или видео",
+            PostField::Status => "статус",
+            PostField::PublishDatetime => "время публикации",
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let editor = editor
+        .map(|e| format!("\nИзменил: {}", teloxide::utils::html::escape(e)))
+        .unwrap_or_default();
+    let before = &revision.before;
+    let publish_datetime = before
+        .publish_datetime
+        .map(moscow)
+        .unwrap_or_else(|| String::from("не задано"));
+    format!(
+        "Правка от <code>{date}</code>{editor}\nИзменено: {changes}\n\nДо правки:\n\
+         <b>{title}</b>\n{content}\nСтатус: {status}\nВремя публикации: <code>{publish_datetime}</code>",
+        date = moscow(revision.created_at),
+        title = before.title,
+        content = before.content,
+        status = before.status,
+    )
+}
+
 pub async fn send_post(bot: &Bot, msg: &Message, post: &Post) -> Result<()> {
+    send_post_by(bot, msg, post, None).await
+}
+// Отправляет пост с именем автора (для списков постов всех авторов)
+pub async fn send_post_by(
+    bot: &Bot,
+    msg: &Message,
+    post: &Post,
+    author: Option<&str>,
+) -> Result<()> {
+    send_post_to(bot, msg.chat.id, post, author).await
+}
+// Отправляет пост в заданный чат (например, уведомление администратору)
+pub async fn send_post_to(
+    bot: &Bot,
+    chat_id: ChatId,
+    post: &Post,
+    author: Option<&str>,
+) -> Result<()> {
     let text = match post.status {
         shared::models::Status::Pending => {
             format!(
@@ -124,6 +262,46 @@ pub async fn send_post(bot: &Bot, msg: &Message, post: &Post) -> Result<()> {
                 date = moscow(post.publish_datetime.unwrap_or_default()),
             )
         }
+        shared::models::Status::Failed => {
+            format!(
+                "<b>{title}</b>\n{content}\nОшибка публикации: <code>{error}</code>",
+                title = post.title,
+                content = post.content,
+                error =
+                    teloxide::utils::html::escape(post.last_error.as_deref().unwrap_or_default()),
+            )
+        }
+        shared::models::Status::Abandoned => {
+            format!(
+                "<b>{title}</b>\n{content}\nОтменен",
+                title = post.title,
+                content = post.content,
+            )
+        }
+        shared::models::Status::InReview => {
+            format!(
+                "<b>{title}</b>\n{content}\nНа проверке",
+                title = post.title,
+                content = post.content,
+            )
+        }
+        shared::models::Status::Approved => {
+            format!(
+                "<b>{title}</b>\n{content}\nОдобрен",
+                title = post.title,
+                content = post.content,
+            )
+        }
+        shared::models::Status::Rejected => {
+            format!(
+                "<b>{title}</b>\n{content}\nОтклонен: <i>{comment}</i>",
+                title = post.title,
+                content = post.content,
+                comment = teloxide::utils::html::escape(
+                    post.review_comment.as_deref().unwrap_or("без комментария")
+                ),
+            )
+        }
         _ => {
             format!(
                 "<b>{title}</b>\n{content}",
@@ -132,27 +310,53 @@ pub async fn send_post(bot: &Bot, msg: &Message, post: &Post) -> Result<()> {
             )
         }
     };
-    let mu = if post.status == Status::Published {
-        MyCallback::published_kb(post.id)
-    } else {
-        MyCallback::not_published_kb(post.id)
+    let deliveries = post
+        .deliveries
+        .iter()
+        .map(|d| match (d.permalink.as_ref(), d.error.as_ref()) {
+            (_, Some(_)) => format!("\n{platform}: ❌", platform = d.platform),
+            (Some(link), None) => format!(
+                "\n{platform}: <a href=\"{link}\">✅</a>",
+                platform = d.platform
+            ),
+            (None, None) => format!("\n{platform}: ✅", platform = d.platform),
+        })
+        .collect::<String>();
+    let author = author
+        .map(|a| format!("\nАвтор: {}", teloxide::utils::html::escape(a)))
+        .unwrap_or_default();
+    let deleted = post
+        .deleted_at
+        .map(|d| format!("\nВ корзине с: <code>{date}</code>", date = moscow(d)))
+        .unwrap_or_default();
+    let text = format!("{text}{author}{deliveries}{deleted}");
+    let mu = match post.status {
+        // Пост из корзины можно только восстановить
+        _ if post.deleted_at.is_some() => MyCallback::deleted_kb(post.id),
+        Status::Published => MyCallback::published_kb(post.id),
+        Status::Failed => MyCallback::failed_kb(post.id),
+        Status::Pending => MyCallback::pending_kb(post.id),
+        Status::Abandoned => MyCallback::abandoned_kb(post.id),
+        Status::Draft | Status::Rejected => MyCallback::draft_kb(post.id),
+        Status::InReview => MyCallback::in_review_kb(post.id),
+        _ => MyCallback::not_published_kb(post.id),
     };
     if let Some(p) = post.tg_photo_file_id.as_ref() {
         let photo = InputFile::file_id(p.to_string().into());
-        bot.send_photo(msg.chat.id, photo)
+        bot.send_photo(chat_id, photo)
             .caption(text)
             .reply_markup(mu)
             .parse_mode(teloxide::types::ParseMode::Html)
             .await?;
     } else if let Some(v) = post.tg_video_file_id.as_ref() {
         let video = InputFile::file_id(v.to_string().into());
-        bot.send_video(msg.chat.id, video)
+        bot.send_video(chat_id, video)
             .caption(text)
             .reply_markup(mu)
             .parse_mode(teloxide::types::ParseMode::Html)
             .await?;
     } else {
-        bot.send_message(msg.chat.id, text)
+        bot.send_message(chat_id, text)
             .reply_markup(mu)
             .parse_mode(teloxide::types::ParseMode::Html)
             .await?;
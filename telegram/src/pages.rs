@@ -0,0 +1,96 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Result, anyhow};
+use client::{Client, PostsPage};
+use shared::models::{PostSortField, Status};
+use teloxide::types::ChatId;
+
+// Список постов в чате: посты в заданном статусе или корзина
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum List {
+    Status(i32),
+    Trash,
+}
+
+// Чат, автор (0 - все авторы), список и номер страницы списка постов
+type PageKey = (ChatId, i64, List, u32);
+
+// Токены страниц списков постов, показанных в чатах. Токен не помещается
+// в данные кнопки (до 64 байт), поэтому кнопка несет номер страницы, а токен
+// хранится здесь. Без токена (например, после перезапуска бота) страница
+// запрашивается по номеру
+#[derive(Debug, Clone, Default)]
+pub struct PageTokens {
+    tokens: Arc<Mutex<HashMap<PageKey, String>>>,
+}
+impl PageTokens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // Возвращает страницу постов автора (0 - всех авторов)
+    pub async fn posts(
+        &self,
+        rpc_client: &mut Client,
+        chat_id: ChatId,
+        author_id: i64,
+        status: Status,
+        page: u32,
+    ) -> Result<PostsPage> {
+        let list = List::Status(i32::from(status));
+        let page_token = self.token(chat_id, author_id, list, page)?;
+        // Очередь показывается в порядке публикации
+        let sort_by = match status {
+            Status::Pending => PostSortField::PublishDatetime,
+            _ => PostSortField::CreatedAt,
+        };
+        let posts_page = rpc_client
+            .list_posts(author_id, status, sort_by, page, page_token)
+            .await?;
+        self.remember(chat_id, author_id, list, page, &posts_page)?;
+        Ok(posts_page)
+    }
+    // Возвращает страницу постов автора (0 - всех авторов) в корзине
+    pub async fn trash(
+        &self,
+        rpc_client: &mut Client,
+        chat_id: ChatId,
+        author_id: i64,
+        page: u32,
+    ) -> Result<PostsPage> {
+        let page_token = self.token(chat_id, author_id, List::Trash, page)?;
+        let posts_page = rpc_client.list_trash(author_id, page, page_token).await?;
+        self.remember(chat_id, author_id, List::Trash, page, &posts_page)?;
+        Ok(posts_page)
+    }
+    fn token(
+        &self,
+        chat_id: ChatId,
+        author_id: i64,
+        list: List,
+        page: u32,
+    ) -> Result<Option<String>> {
+        let mut tokens = self.tokens.lock().map_err(|e| anyhow!("{e}"))?;
+        // Новый просмотр списка начинается с первой страницы
+        if page == 1 {
+            tokens.retain(|(chat, ..), _| *chat != chat_id);
+        }
+        Ok(tokens.get(&(chat_id, author_id, list, page)).cloned())
+    }
+    fn remember(
+        &self,
+        chat_id: ChatId,
+        author_id: i64,
+        list: List,
+        page: u32,
+        posts_page: &PostsPage,
+    ) -> Result<()> {
+        if let Some(token) = posts_page.next_page_token.clone() {
+            let mut tokens = self.tokens.lock().map_err(|e| anyhow!("{e}"))?;
+            tokens.insert((chat_id, author_id, list, page + 1), token);
+        }
+        Ok(())
+    }
+}
@@ -1,15 +1,21 @@
 use std::str::FromStr;
 
 use anyhow::{Result, anyhow};
-use client::Client;
+use client::{Client, PostsPage};
 use dptree::case;
+use publisher::{Publisher, Waker};
 use shared::models::{Role, Status};
 use teloxide::{
-    dispatching::DpHandlerDescription, prelude::*, sugar::bot::BotMessagesExt,
-    types::KeyboardRemove,
+    dispatching::DpHandlerDescription,
+    prelude::*,
+    sugar::bot::BotMessagesExt,
+    types::{KeyboardRemove, ReplyParameters},
 };
 
-use crate::{MyCallback, MyDialogue, TextCommand, moscow, send_post};
+use crate::{
+    MyCallback, MyDialogue, PageTokens, State, TextCommand, error_text, moscow, revision_text,
+    send_post, send_post_by, send_post_to,
+};
 
 pub(super) fn router() -> Handler<'static, Result<()>, DpHandlerDescription> {
     Update::filter_callback_query()
@@ -24,10 +30,24 @@ pub(super) fn router() -> Handler<'static, Result<()>, DpHandlerDescription> {
         .branch(case![MyCallback::Drafts { author_id }].endpoint(users_drafts))
         .branch(case![MyCallback::Pending { author_id }].endpoint(users_pending))
         .branch(case![MyCallback::Published { author_id }].endpoint(users_published))
+        .branch(case![MyCallback::Failed { author_id }].endpoint(users_failed))
         // Posts
         .branch(case![MyCallback::PublishNow { id }].endpoint(publish_post))
         .branch(case![MyCallback::SetPublishDate { id }].endpoint(set_publish_date))
+        .branch(case![MyCallback::RetryPost { id }].endpoint(retry_post))
         .branch(case![MyCallback::DeletePost { id }].endpoint(delete_post))
+        .branch(case![MyCallback::ConfirmDeletePost { id }].endpoint(confirm_delete_post))
+        .branch(case![MyCallback::RestorePost { id }].endpoint(restore_post))
+        .branch(case![MyCallback::TrashPage { author_id, page }].endpoint(trash_page))
+        .branch(case![MyCallback::PostHistory { id }].endpoint(post_history))
+        .branch(case![MyCallback::RestoreRevision { id }].endpoint(restore_revision))
+        .branch(case![MyCallback::SubmitPost { id }].endpoint(submit_post))
+        .branch(case![MyCallback::ApprovePost { id }].endpoint(approve_post))
+        .branch(case![MyCallback::RejectPost { id }].endpoint(reject_post))
+        .branch(case![MyCallback::UnschedulePost { id }].endpoint(unschedule_post))
+        .branch(case![MyCallback::AbandonPost { id }].endpoint(abandon_post))
+        .branch(case![MyCallback::ReopenPost { id }].endpoint(reopen_post))
+        .branch(case![MyCallback::UnpublishPost { id }].endpoint(unpublish_post))
         .branch(
             case![MyCallback::PostsNextPage {
                 author_id,
@@ -61,7 +81,7 @@ async fn make_editor(
             .await?
             .map(|u| u.role)
             .unwrap_or(Role::Guest);
-        if role != Role::Guest {
+        if role == Role::Admin {
             if let MyCallback::MakeUserEditor { id } = cb {
                 let mut existing = rpc_client
                     .get_user(id)
@@ -106,7 +126,7 @@ async fn make_guest(
             .await?
             .map(|u| u.role)
             .unwrap_or(Role::Guest);
-        if role != Role::Guest {
+        if role == Role::Admin {
             if let MyCallback::MakeUserGuest { id } = cb {
                 let mut existing = rpc_client
                     .get_user(id)
@@ -152,7 +172,7 @@ async fn delete_user(
             .await?
             .map(|u| u.role)
             .unwrap_or(Role::Guest);
-        if role != Role::Guest {
+        if role == Role::Admin {
             if let MyCallback::DeleteUser { id } = cb {
                 let existing = rpc_client
                     .get_user(id)
@@ -213,14 +233,55 @@ async fn delete_post(
             .unwrap_or(Role::Guest);
         if role != Role::Guest {
             if let MyCallback::DeletePost { id } = cb {
-                rpc_client.delete_post(id).await?;
+                // Подтверждение отправляется ответом на сообщение с постом
+                bot.send_message(msg.chat.id, "Переместить пост в корзину?")
+                    .reply_parameters(ReplyParameters::new(msg.id))
+                    .reply_markup(MyCallback::confirm_delete_kb(id))
+                    .await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+async fn confirm_delete_post(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+    waker: Waker,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::ConfirmDeletePost { id } = cb {
+                // Пост в корзине остается на площадках, чтобы его можно было
+                // восстановить; снимается он отдельным действием
+                if let Err(e) = rpc_client.delete_post(id, from).await {
+                    bot.send_message(msg.chat.id, error_text(&e)).await?;
+                    return Ok(());
+                }
+                waker.wake();
+                // Удаляем подтверждение и сообщение с постом, если оно еще есть
+                if let Some(post_msg) = msg.reply_to_message() {
+                    bot.delete_message(msg.chat.id, post_msg.id).await.ok();
+                }
                 bot.delete_message(msg.chat.id, msg.id).await?;
                 let mu = if role == Role::Admin {
                     TextCommand::admin_keyboard()
                 } else {
                     TextCommand::editor_keyboard()
                 };
-                bot.send_message(msg.chat.id, "Пост удален")
+                bot.send_message(msg.chat.id, "Пост перемещен в корзину")
                     .reply_markup(mu)
                     .await?;
             }
@@ -232,11 +293,500 @@ async fn delete_post(
     }
     Ok(())
 }
+async fn restore_post(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+    waker: Waker,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::RestorePost { id } = cb {
+                let post = match rpc_client.undelete_post(id).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                // Восстановленный отложенный пост снова попадает в очередь
+                waker.wake();
+                bot.delete_message(msg.chat.id, msg.id).await?;
+                bot.send_message(msg.chat.id, "Пост восстановлен").await?;
+                send_post(&bot, msg, &post).await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+async fn trash_page(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from: i64 = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::TrashPage { author_id, page } = cb {
+                // Корзина всех авторов (author_id 0) доступна только администраторам
+                let posts_page = if author_id == 0 && role != Role::Admin {
+                    PostsPage::default()
+                } else {
+                    page_tokens
+                        .trash(&mut rpc_client, msg.chat.id, author_id, page)
+                        .await?
+                };
+                let has_next = posts_page.has_next();
+                for post in &posts_page.posts {
+                    let author = posts_page
+                        .authors
+                        .get(&post.author_id)
+                        .filter(|_| author_id == 0);
+                    send_post_by(&bot, msg, post, author.map(String::as_str)).await?;
+                }
+                if has_next {
+                    bot.send_message(msg.chat.id, "Это не все")
+                        .reply_markup(MyCallback::trash_has_next_kb(author_id, page + 1))
+                        .await?;
+                } else {
+                    bot.send_message(msg.chat.id, "Это все")
+                        .reply_markup(MyCallback::cancel_button())
+                        .await?;
+                }
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+async fn post_history(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::PostHistory { id } = cb {
+                let page = match rpc_client.list_post_revisions(id, None).await {
+                    Ok(page) => page,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                if page.revisions.is_empty() {
+                    bot.send_message(msg.chat.id, "Пост еще не изменялся")
+                        .await?;
+                }
+                for revision in &page.revisions {
+                    let editor = match revision.edited_by {
+                        Some(tg_id) => rpc_client.get_user(tg_id).await?.map(|u| u.full_name()),
+                        None => None,
+                    };
+                    bot.send_message(msg.chat.id, revision_text(revision, editor.as_deref()))
+                        .reply_markup(MyCallback::revision_kb(revision.id))
+                        .parse_mode(teloxide::types::ParseMode::Html)
+                        .await?;
+                }
+                if page.has_next() {
+                    bot.send_message(msg.chat.id, "Показаны последние правки поста")
+                        .await?;
+                }
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+async fn restore_revision(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+    publisher: Publisher,
+    waker: Waker,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::RestoreRevision { id } = cb {
+                let post = match rpc_client.restore_post_revision(id, from).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                // Возврат может изменить время публикации
+                waker.wake();
+                // Опубликованный пост обновляется на площадках
+                if post.status == Status::Published
+                    && let Err(e) = publisher.edit(&post).await
+                {
+                    tracing::error!("Error editing post {id} on platforms: {e:?}", id = post.id);
+                    bot.send_message(msg.chat.id, "Не удалось обновить пост на площадках")
+                        .await?;
+                }
+                bot.send_message(msg.chat.id, "Пост возвращен к версии до правки")
+                    .await?;
+                send_post(&bot, msg, &post).await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+// Отправляет пост на проверку и уведомляет администраторов
+async fn submit_post(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let Some(user) = rpc_client.get_user(from).await? else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+            return Ok(());
+        };
+        if user.role != Role::Guest {
+            if let MyCallback::SubmitPost { id } = cb {
+                let post = match rpc_client.submit_post(id, from).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                bot.send_message(msg.chat.id, "Пост отправлен на проверку")
+                    .await?;
+                send_post(&bot, msg, &post).await?;
+                let name = user.full_name();
+                // Пост уже на проверке: ошибка уведомления одного администратора
+                // не мешает уведомить остальных
+                let admins = match rpc_client.list_admins().await {
+                    Ok(admins) => admins,
+                    Err(e) => {
+                        tracing::error!("Error listing admins: {e:?}");
+                        Vec::new()
+                    }
+                };
+                for admin in admins {
+                    if admin.telegram_id == from {
+                        continue;
+                    }
+                    let chat_id = ChatId(admin.telegram_id);
+                    let notified: Result<()> = async {
+                        bot.send_message(chat_id, "Новый пост на проверку").await?;
+                        send_post_to(&bot, chat_id, &post, Some(&name)).await
+                    }
+                    .await;
+                    if let Err(e) = notified {
+                        tracing::error!(
+                            "Error notifying admin {id} about post {post_id}: {e:?}",
+                            id = admin.telegram_id,
+                            post_id = post.id
+                        );
+                    }
+                }
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+// Одобряет пост и уведомляет автора
+async fn approve_post(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role == Role::Admin {
+            if let MyCallback::ApprovePost { id } = cb {
+                let (post, author) = match rpc_client.review_post(id, from, true, None).await {
+                    Ok(reviewed) => reviewed,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                bot.send_message(msg.chat.id, "Пост одобрен").await?;
+                send_post(&bot, msg, &post).await?;
+                if let Some(author) = author.filter(|a| *a != from) {
+                    let chat_id = ChatId(author);
+                    bot.send_message(chat_id, "Пост одобрен, можно запланировать публикацию")
+                        .await?;
+                    send_post_to(&bot, chat_id, &post, None).await?;
+                }
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа").await?;
+        }
+    }
+    Ok(())
+}
+// Запрашивает у администратора причину отклонения поста
+async fn reject_post(
+    bot: Bot,
+    q: CallbackQuery,
+    dialogue: MyDialogue,
+    cb: MyCallback,
+    mut rpc_client: Client,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role == Role::Admin {
+            if let MyCallback::RejectPost { id } = cb {
+                bot.send_message(msg.chat.id, "Пришлите причину отклонения поста")
+                    .reply_markup(MyCallback::cancel_button())
+                    .await?;
+                dialogue
+                    .update(State::RejectCommentReceive { post_id: id })
+                    .await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа").await?;
+        }
+    }
+    Ok(())
+}
+// Снимает пост с очереди публикации
+async fn unschedule_post(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+    waker: Waker,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::UnschedulePost { id } = cb {
+                let post = match rpc_client.unschedule_post(id, from).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                waker.wake();
+                bot.delete_message(msg.chat.id, msg.id).await?;
+                bot.send_message(msg.chat.id, "Пост снят с очереди и возвращен в черновики")
+                    .await?;
+                send_post(&bot, msg, &post).await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+// Отменяет пост
+async fn abandon_post(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+    waker: Waker,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::AbandonPost { id } = cb {
+                let post = match rpc_client.abandon_post(id, from).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                // Отмененный пост уходит из очереди публикации
+                waker.wake();
+                bot.delete_message(msg.chat.id, msg.id).await?;
+                bot.send_message(msg.chat.id, "Пост отменен").await?;
+                send_post(&bot, msg, &post).await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+// Снимает опубликованный пост с площадок и отменяет его
+async fn unpublish_post(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+    publisher: Publisher,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::UnpublishPost { id } = cb {
+                let Some(post) = rpc_client.get_post(id).await? else {
+                    bot.send_message(msg.chat.id, "Пост не найден").await?;
+                    return Ok(());
+                };
+                if post.status != Status::Published {
+                    bot.send_message(msg.chat.id, "Пост не опубликован").await?;
+                    return Ok(());
+                }
+                if let Err(e) = publisher.delete(&post).await {
+                    tracing::error!("Error deleting post {id} from platforms: {e:?}");
+                    bot.send_message(msg.chat.id, "Не удалось снять пост с площадок")
+                        .await?;
+                    return Ok(());
+                }
+                let post = match rpc_client.abandon_post(id, from).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                bot.delete_message(msg.chat.id, msg.id).await?;
+                bot.send_message(msg.chat.id, "Пост снят с площадок")
+                    .await?;
+                send_post(&bot, msg, &post).await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+// Возвращает отмененный пост в черновики
+async fn reopen_post(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::ReopenPost { id } = cb {
+                let post = match rpc_client.restore_post(id, from).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                bot.delete_message(msg.chat.id, msg.id).await?;
+                bot.send_message(msg.chat.id, "Пост возвращен в черновики")
+                    .await?;
+                send_post(&bot, msg, &post).await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
 async fn posts_page(
     bot: Bot,
     q: CallbackQuery,
     cb: MyCallback,
     mut rpc_client: Client,
+    page_tokens: PageTokens,
 ) -> Result<()> {
     bot.answer_callback_query(q.id.clone()).await?;
     if let Some(msg) = q.regular_message() {
@@ -258,18 +808,30 @@ async fn posts_page(
                     status,
                     page,
                 } => {
-                    let (posts, has_next) = match status {
-                        shared::models::Status::Draft => rpc_client.drafts(author_id, page).await?,
-                        shared::models::Status::Pending => {
-                            rpc_client.pending(author_id, page).await?
+                    // Посты всех авторов (author_id 0) доступны только администраторам
+                    let posts_page = match status {
+                        _ if author_id == 0 && role != Role::Admin => PostsPage::default(),
+                        Status::Draft
+                        | Status::Pending
+                        | Status::Published
+                        | Status::Failed
+                        | Status::InReview
+                        | Status::Approved
+                        | Status::Rejected
+                        | Status::Abandoned => {
+                            page_tokens
+                                .posts(&mut rpc_client, msg.chat.id, author_id, status, page)
+                                .await?
                         }
-                        shared::models::Status::Published => {
-                            rpc_client.published(author_id, page).await?
-                        }
-                        shared::models::Status::Abandoned => (Vec::new(), false),
+                        Status::Publishing => PostsPage::default(),
                     };
-                    for post in posts {
-                        send_post(&bot, msg, &post).await?;
+                    let has_next = posts_page.has_next();
+                    for post in &posts_page.posts {
+                        let author = posts_page
+                            .authors
+                            .get(&post.author_id)
+                            .filter(|_| author_id == 0);
+                        send_post_by(&bot, msg, post, author.map(String::as_str)).await?;
                     }
                     if page != 1 {
                         if has_next {
@@ -314,6 +876,7 @@ async fn users_drafts(
     q: CallbackQuery,
     cb: MyCallback,
     mut rpc_client: Client,
+    page_tokens: PageTokens,
 ) -> Result<()> {
     bot.answer_callback_query(q.id.clone()).await?;
     if let Some(msg) = q.regular_message() {
@@ -325,11 +888,13 @@ async fn users_drafts(
             .unwrap_or(Role::Guest);
         if role == Role::Admin {
             if let MyCallback::Drafts { author_id } = cb {
-                let (posts, has_next) = rpc_client.drafts(author_id, 1).await?;
-                for post in posts {
-                    send_post(&bot, msg, &post).await?;
+                let posts_page = page_tokens
+                    .posts(&mut rpc_client, msg.chat.id, author_id, Status::Draft, 1)
+                    .await?;
+                for post in &posts_page.posts {
+                    send_post(&bot, msg, post).await?;
                 }
-                if has_next {
+                if posts_page.has_next() {
                     bot.send_message(msg.chat.id, "Это не все")
                         .reply_markup(MyCallback::has_next_kb(author_id, Status::Draft, 2))
                         .await?;
@@ -352,6 +917,7 @@ async fn users_pending(
     q: CallbackQuery,
     cb: MyCallback,
     mut rpc_client: Client,
+    page_tokens: PageTokens,
 ) -> Result<()> {
     bot.answer_callback_query(q.id.clone()).await?;
     if let Some(msg) = q.regular_message() {
@@ -363,11 +929,13 @@ async fn users_pending(
             .unwrap_or(Role::Guest);
         if role == Role::Admin {
             if let MyCallback::Pending { author_id } = cb {
-                let (posts, has_next) = rpc_client.pending(author_id, 1).await?;
-                for post in posts {
-                    send_post(&bot, msg, &post).await?;
+                let posts_page = page_tokens
+                    .posts(&mut rpc_client, msg.chat.id, author_id, Status::Pending, 1)
+                    .await?;
+                for post in &posts_page.posts {
+                    send_post(&bot, msg, post).await?;
                 }
-                if has_next {
+                if posts_page.has_next() {
                     bot.send_message(msg.chat.id, "Это не все")
                         .reply_markup(MyCallback::has_next_kb(author_id, Status::Pending, 2))
                         .await?;
@@ -390,6 +958,7 @@ async fn users_published(
     q: CallbackQuery,
     cb: MyCallback,
     mut rpc_client: Client,
+    page_tokens: PageTokens,
 ) -> Result<()> {
     bot.answer_callback_query(q.id.clone()).await?;
     if let Some(msg) = q.regular_message() {
@@ -401,11 +970,19 @@ async fn users_published(
             .unwrap_or(Role::Guest);
         if role == Role::Admin {
             if let MyCallback::Published { author_id } = cb {
-                let (posts, has_next) = rpc_client.published(author_id, 1).await?;
-                for post in posts {
-                    send_post(&bot, msg, &post).await?;
+                let posts_page = page_tokens
+                    .posts(
+                        &mut rpc_client,
+                        msg.chat.id,
+                        author_id,
+                        Status::Published,
+                        1,
+                    )
+                    .await?;
+                for post in &posts_page.posts {
+                    send_post(&bot, msg, post).await?;
                 }
-                if has_next {
+                if posts_page.has_next() {
                     bot.send_message(msg.chat.id, "Это не все")
                         .reply_markup(MyCallback::has_next_kb(author_id, Status::Published, 2))
                         .await?;
@@ -423,11 +1000,53 @@ async fn users_published(
     }
     Ok(())
 }
+async fn users_failed(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role == Role::Admin {
+            if let MyCallback::Failed { author_id } = cb {
+                let posts_page = page_tokens
+                    .posts(&mut rpc_client, msg.chat.id, author_id, Status::Failed, 1)
+                    .await?;
+                for post in &posts_page.posts {
+                    send_post(&bot, msg, post).await?;
+                }
+                if posts_page.has_next() {
+                    bot.send_message(msg.chat.id, "Это не все")
+                        .reply_markup(MyCallback::has_next_kb(author_id, Status::Failed, 2))
+                        .await?;
+                } else {
+                    bot.send_message(msg.chat.id, "Это все")
+                        .reply_markup(MyCallback::cancel_button())
+                        .await?;
+                }
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
 async fn publish_post(
     bot: Bot,
     q: CallbackQuery,
     cb: MyCallback,
     mut rpc_client: Client,
+    waker: Waker,
 ) -> Result<()> {
     bot.answer_callback_query(q.id.clone()).await?;
     if let Some(msg) = q.regular_message() {
@@ -440,10 +1059,14 @@ async fn publish_post(
         if role != Role::Guest {
             if let MyCallback::PublishNow { id } = cb {
                 let now = chrono::Utc::now();
-                let post = rpc_client
-                    .set_publish_date(id, now)
-                    .await?
-                    .ok_or(anyhow!("Error publishing post"))?;
+                let post = match rpc_client.schedule_post(id, now, from).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                waker.wake();
                 let text = format!(
                     "<b>{title}</b>\n{content}\nОпубликован: {date}",
                     title = post.title,
@@ -506,3 +1129,58 @@ async fn set_publish_date(
     }
     Ok(())
 }
+async fn retry_post(
+    bot: Bot,
+    q: CallbackQuery,
+    cb: MyCallback,
+    mut rpc_client: Client,
+    waker: Waker,
+) -> Result<()> {
+    bot.answer_callback_query(q.id.clone()).await?;
+    if let Some(msg) = q.regular_message() {
+        let from = q.from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(from)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            if let MyCallback::RetryPost { id } = cb {
+                let now = chrono::Utc::now();
+                let post = match rpc_client.schedule_post(id, now, from).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                waker.wake();
+                let text = format!(
+                    "<b>{title}</b>\n{content}\nОпубликую: {date}",
+                    title = post.title,
+                    content = post.content,
+                    date = moscow(post.publish_datetime.unwrap_or_default()),
+                );
+                let mu = MyCallback::pending_kb(post.id);
+                if bot
+                    .edit_message_text(msg.chat.id, msg.id, &text)
+                    .reply_markup(mu.clone())
+                    .parse_mode(teloxide::types::ParseMode::Html)
+                    .await
+                    .is_err()
+                {
+                    bot.edit_caption(msg)
+                        .caption(text)
+                        .reply_markup(mu)
+                        .parse_mode(teloxide::types::ParseMode::Html)
+                        .await?;
+                }
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
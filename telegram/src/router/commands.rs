@@ -2,17 +2,17 @@ use anyhow::{Result, anyhow};
 use dptree::case;
 use teloxide::{dispatching::DpHandlerDescription, prelude::*, utils::command::BotCommands};
 
-use crate::{Command, TextCommand};
+use crate::{Command, TextCommand, send_post_by};
 
 pub(super) fn router() -> Handler<'static, Result<()>, DpHandlerDescription> {
     teloxide::filter_command::<Command, _>()
         .branch(case![Command::Start].endpoint(start))
         .branch(case![Command::Help].endpoint(help))
+        .branch(case![Command::Search(query)].endpoint(search))
 }
 async fn start(bot: Bot, msg: Message, mut rpc_client: client::Client) -> Result<()> {
     let from = msg.from.ok_or(anyhow!("no field 'from' on message"))?;
     let id = from.id.0.try_into()?;
-    let has_admin = rpc_client.has_admin().await?;
     match rpc_client.get_user(id).await? {
         Some(existing) => {
             let name = if let Some(last) = existing.last_name {
@@ -48,7 +48,8 @@ async fn start(bot: Bot, msg: Message, mut rpc_client: client::Client) -> Result
             }
         }
         None => {
-            let mut created = rpc_client
+            // Первого зарегистрированного пользователя сервер делает администратором
+            let created = rpc_client
                 .create_user(
                     id,
                     from.first_name,
@@ -58,16 +59,11 @@ async fn start(bot: Bot, msg: Message, mut rpc_client: client::Client) -> Result
                 )
                 .await?
                 .ok_or(anyhow!("error creating new user"))?;
-            if !has_admin {
-                created.role = shared::models::Role::Admin;
-                let updated = rpc_client
-                    .update_user(created)
-                    .await?
-                    .ok_or(anyhow!("error updating user role to admin"))?;
-                let name = if let Some(last) = updated.last_name {
-                    format!("{first} {last}", first = updated.first_name)
+            if created.role == shared::models::Role::Admin {
+                let name = if let Some(last) = created.last_name {
+                    format!("{first} {last}", first = created.first_name)
                 } else {
-                    updated.first_name
+                    created.first_name
                 };
                 let text =
                     format!("Добро пожаловать, <b>{name}</b>! Вы теперь <i>администратор</i>.");
@@ -95,3 +91,57 @@ async fn help(bot: Bot, msg: Message) -> Result<()> {
         .await?;
     Ok(())
 }
+// Администратор ищет среди постов всех авторов, редактор - среди своих
+async fn search(
+    bot: Bot,
+    msg: Message,
+    query: String,
+    mut rpc_client: client::Client,
+) -> Result<()> {
+    let from = msg
+        .from
+        .as_ref()
+        .ok_or(anyhow!("no field 'from' on message"))?;
+    let id = from.id.0.try_into()?;
+    let role = rpc_client
+        .get_user(id)
+        .await?
+        .map(|u| u.role)
+        .unwrap_or(shared::models::Role::Guest);
+    let author_tg_ids = match role {
+        shared::models::Role::Guest => {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+            return Ok(());
+        }
+        shared::models::Role::Editor => vec![id],
+        shared::models::Role::Admin => Vec::new(),
+    };
+    if query.trim().is_empty() {
+        bot.send_message(msg.chat.id, "Укажите слова для поиска: /search <запрос>")
+            .await?;
+        return Ok(());
+    }
+    let found = rpc_client
+        .search_posts(query, author_tg_ids, None, None)
+        .await?;
+    if found.posts.is_empty() {
+        bot.send_message(msg.chat.id, "Ничего не найдено").await?;
+    }
+    for post in &found.posts {
+        let author = found
+            .authors
+            .get(&post.author_id)
+            .filter(|_| role == shared::models::Role::Admin);
+        send_post_by(&bot, &msg, post, author.map(String::as_str)).await?;
+    }
+    if found.has_next() {
+        bot.send_message(
+            msg.chat.id,
+            "Показаны самые новые из найденных постов, уточните запрос",
+        )
+        .await?;
+    }
+    Ok(())
+}
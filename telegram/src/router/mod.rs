@@ -15,9 +15,18 @@ use teloxide::{
 use crate::State;
 
 pub fn master() -> UpdateHandler<Error> {
-    dialogue::enter::<Update, InMemStorage<State>, State, _>()
-        .branch(message_router())
-        .branch(callback::router())
+    dptree::map(acting_client).chain(
+        dialogue::enter::<Update, InMemStorage<State>, State, _>()
+            .branch(message_router())
+            .branch(callback::router()),
+    )
+}
+// Запросы к серверу выполняются от имени пользователя, приславшего обновление
+fn acting_client(upd: Update, rpc_client: client::Client) -> client::Client {
+    match upd.from().and_then(|u| i64::try_from(u.id.0).ok()) {
+        Some(id) => rpc_client.acting_as(id),
+        None => rpc_client,
+    }
 }
 fn message_router() -> Handler<'static, Result<()>, DpHandlerDescription> {
     Update::filter_message()
@@ -1,11 +1,14 @@
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use client::Client;
 use dptree::case;
+use publisher::{MediaKind, Publisher, Waker};
 use shared::models::Role;
-use teloxide::{dispatching::DpHandlerDescription, net::Download, prelude::*};
+use teloxide::{dispatching::DpHandlerDescription, net::Download, prelude::*, types::FileId};
 use tracing::instrument;
 
-use crate::{MyCallback, MyDialogue, State, TextCommand, send_post, to_utc};
+use crate::{
+    MyCallback, MyDialogue, State, TextCommand, error_text, send_post, send_post_to, to_utc,
+};
 
 pub(super) fn router() -> Handler<'static, Result<()>, DpHandlerDescription> {
     Update::filter_message()
@@ -13,6 +16,7 @@ pub(super) fn router() -> Handler<'static, Result<()>, DpHandlerDescription> {
         .branch(case![State::ContentReceive { title }].endpoint(content_received))
         .branch(case![State::MediaReceive { title, content }].endpoint(media_received))
         .branch(case![State::PublishDateReceive { post_id }].endpoint(publish_date_received))
+        .branch(case![State::RejectCommentReceive { post_id }].endpoint(reject_comment_received))
 }
 #[instrument(name = "title received", skip(bot, msg, dialogue, rpc_client))]
 async fn title_received(
@@ -37,9 +41,7 @@ async fn title_received(
                 bot.send_message(msg.chat.id, text)
                     .reply_markup(MyCallback::cancel_button())
                     .await?;
-                dialogue
-                    .update(State::ContentReceive { title: title })
-                    .await?;
+                dialogue.update(State::ContentReceive { title }).await?;
             }
         } else {
             bot.send_message(msg.chat.id, "У вас нет доступа")
@@ -65,23 +67,20 @@ async fn content_received(
             .map(|u| u.role)
             .unwrap_or(Role::Guest);
         if role != Role::Guest {
-            if let Some(State::ContentReceive { title }) = dialogue.get().await? {
-                if let Some(message_text) = msg.text().as_ref() {
-                    let content = message_text.to_string();
-                    // TODO: CHECK CONTENT!!!
-                    let text = format!(
-                        "Заголовок: {title}\nСодержание:{content}\nПришлите медиа для поста или любое текст, чтобы сохранить без медиа"
-                    );
-                    bot.send_message(msg.chat.id, text)
-                        .reply_markup(MyCallback::cancel_button())
-                        .await?;
-                    dialogue
-                        .update(State::MediaReceive {
-                            title: title,
-                            content: content,
-                        })
-                        .await?;
-                }
+            if let Some(State::ContentReceive { title }) = dialogue.get().await?
+                && let Some(message_text) = msg.text().as_ref()
+            {
+                let content = message_text.to_string();
+                // TODO: CHECK CONTENT!!!
+                let text = format!(
+                    "Заголовок: {title}\nСодержание:{content}\nПришлите медиа для поста или любое текст, чтобы сохранить без медиа"
+                );
+                bot.send_message(msg.chat.id, text)
+                    .reply_markup(MyCallback::cancel_button())
+                    .await?;
+                dialogue
+                    .update(State::MediaReceive { title, content })
+                    .await?;
             }
         } else {
             bot.send_message(msg.chat.id, "У вас нет доступа")
@@ -98,7 +97,7 @@ async fn media_received(
     msg: Message,
     dialogue: MyDialogue,
     mut rpc_client: Client,
-    vk_client: vk::VKClient,
+    publisher: Publisher,
 ) -> Result<()> {
     if let Some(from) = msg.from.as_ref() {
         let id = from.id.0.try_into()?;
@@ -115,34 +114,24 @@ async fn media_received(
                     .photo()
                     .and_then(|s| s.first())
                     .map(|f| f.file.id.0.clone());
-                let mut vk_photo_file_id = None;
-                if let Some(ps) = msg.photo().and_then(|p| p.last()) {
-                    let file = bot.get_file(ps.file.id.clone()).await?;
-                    let extension = file.path.split('.').last().unwrap_or_default();
-                    let path = format!("/tmp/photo.{extension}");
-                    let mut dst = tokio::fs::File::create(&path).await?;
-                    bot.download_file(&file.path, &mut dst).await?;
-                    match vk_client.get_photo_id(path).await {
-                        Ok(id) => vk_photo_file_id = Some(id),
-                        Err(e) => tracing::error!("{e:?}"),
+                let vk_photo_file_id = match msg.photo().and_then(|p| p.last()) {
+                    Some(ps) => {
+                        prepare_media(&bot, &publisher, MediaKind::Photo, ps.file.id.clone())
+                            .await?
                     }
-                }
+                    None => None,
+                };
                 let tg_video_file_id = msg.video().map(|v| v.file.id.0.clone());
-                let mut vk_video_file_id = None;
-                if let Some(vs) = msg.video() {
-                    let file = bot.get_file(vs.file.id.clone()).await?;
-                    let extension = file.path.split('.').last().unwrap_or_default();
-                    let path = format!("/tmp/video.{extension}");
-                    let mut dst = tokio::fs::File::create(&path).await?;
-                    bot.download_file(&file.path, &mut dst).await?;
-                    match vk_client.get_video_id(path).await {
-                        Ok(id) => vk_video_file_id = Some(id),
-                        Err(e) => tracing::error!("{e:?}"),
+                let vk_video_file_id = match msg.video() {
+                    Some(vs) => {
+                        prepare_media(&bot, &publisher, MediaKind::Video, vs.file.id.clone())
+                            .await?
                     }
-                }
+                    None => None,
+                };
 
                 bot.delete_message(msg.chat.id, msg.id).await?;
-                let post = rpc_client
+                let created = rpc_client
                     .create_post(
                         id,
                         title,
@@ -152,16 +141,20 @@ async fn media_received(
                         tg_video_file_id,
                         vk_video_file_id,
                     )
-                    .await?;
-                send_post(&bot, &msg, &post).await?;
+                    .await;
+                let text = match created {
+                    Ok(post) => {
+                        send_post(&bot, &msg, &post).await?;
+                        String::from("Могу я еще чем-то помочь?")
+                    }
+                    Err(e) => format!("Не удалось создать пост. {}", error_text(&e)),
+                };
                 let mu = if role == Role::Admin {
                     TextCommand::admin_keyboard()
                 } else {
                     TextCommand::editor_keyboard()
                 };
-                bot.send_message(msg.chat.id, "Могу я еще чем-то помочь?")
-                    .reply_markup(mu)
-                    .await?;
+                bot.send_message(msg.chat.id, text).reply_markup(mu).await?;
             }
         } else {
             bot.send_message(msg.chat.id, "У вас нет доступа")
@@ -172,12 +165,39 @@ async fn media_received(
 
     Ok(())
 }
-#[instrument(name = "publish date received", skip(bot, msg, dialogue, rpc_client))]
+// Скачивает медиафайл из Telegram во временный файл и загружает его на
+// площадки публикации. Возвращает идентификатор для VK: Telegram использует
+// исходный файл
+async fn prepare_media(
+    bot: &Bot,
+    publisher: &Publisher,
+    kind: MediaKind,
+    file_id: FileId,
+) -> Result<Option<String>> {
+    let file = bot.get_file(file_id).await?;
+    let extension = file.path.split('.').next_back().unwrap_or_default();
+    let path = std::env::temp_dir().join(format!("{}.{extension}", uuid::Uuid::new_v4()));
+    let mut dst = tokio::fs::File::create(&path).await?;
+    bot.download_file(&file.path, &mut dst).await?;
+    let prepared = publisher.prepare_media(kind, &path.to_string_lossy()).await;
+    if let Err(e) = tokio::fs::remove_file(&path).await {
+        tracing::warn!("Error removing {path:?}: {e}");
+    }
+    Ok(prepared
+        .into_iter()
+        .find(|(platform, _)| *platform == shared::models::Platform::Vk)
+        .map(|(_, id)| id))
+}
+#[instrument(
+    name = "publish date received",
+    skip(bot, msg, dialogue, rpc_client, waker)
+)]
 async fn publish_date_received(
     bot: Bot,
     msg: Message,
     dialogue: MyDialogue,
     mut rpc_client: Client,
+    waker: Waker,
 ) -> Result<()> {
     if let Some(from) = msg.from.as_ref() {
         let id = from.id.0.try_into()?;
@@ -187,14 +207,66 @@ async fn publish_date_received(
             .map(|u| u.role)
             .unwrap_or(Role::Guest);
         if role != Role::Guest {
-            if let Some(message_text) = msg.text().as_ref() {
-                if let Some(State::PublishDateReceive { post_id }) = dialogue.get().await? {
-                    let date = to_utc(&message_text)?;
-                    let post = rpc_client
-                        .set_publish_date(post_id, date)
-                        .await?
-                        .ok_or(anyhow!("Error setting post publish date"))?;
-                    send_post(&bot, &msg, &post).await?;
+            if let Some(message_text) = msg.text().as_ref()
+                && let Some(State::PublishDateReceive { post_id }) = dialogue.get().await?
+            {
+                let date = to_utc(message_text)?;
+                let post = match rpc_client.schedule_post(post_id, date, id).await {
+                    Ok(post) => post,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                waker.wake();
+                send_post(&bot, &msg, &post).await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+#[instrument(name = "reject comment received", skip(bot, msg, dialogue, rpc_client))]
+async fn reject_comment_received(
+    bot: Bot,
+    msg: Message,
+    dialogue: MyDialogue,
+    mut rpc_client: Client,
+) -> Result<()> {
+    if let Some(from) = msg.from.as_ref() {
+        let id = from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(id)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role == Role::Admin {
+            if let Some(message_text) = msg.text().as_ref()
+                && let Some(State::RejectCommentReceive { post_id }) = dialogue.get().await?
+            {
+                let comment = Some(message_text.to_string());
+                let (post, author) = match rpc_client.review_post(post_id, id, false, comment).await
+                {
+                    Ok(reviewed) => reviewed,
+                    Err(e) => {
+                        bot.send_message(msg.chat.id, error_text(&e)).await?;
+                        return Ok(());
+                    }
+                };
+                dialogue.exit().await?;
+                bot.send_message(msg.chat.id, "Пост отклонен")
+                    .reply_markup(TextCommand::admin_keyboard())
+                    .await?;
+                send_post(&bot, &msg, &post).await?;
+                if let Some(author) = author.filter(|a| *a != id) {
+                    let chat_id = ChatId(author);
+                    bot.send_message(chat_id, "Пост отклонен администратором")
+                        .await?;
+                    send_post_to(&bot, chat_id, &post, None).await?;
                 }
             }
         } else {
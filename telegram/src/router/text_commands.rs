@@ -6,7 +6,7 @@ use dptree::case;
 use shared::models::{Role, Status};
 use teloxide::{dispatching::DpHandlerDescription, prelude::*, types::KeyboardRemove};
 
-use crate::{MyCallback, MyDialogue, TextCommand, send_post};
+use crate::{MyCallback, MyDialogue, PageTokens, TextCommand, send_post, send_post_by};
 
 pub(super) fn router() -> Handler<'static, Result<()>, DpHandlerDescription> {
     Update::filter_message()
@@ -19,7 +19,12 @@ pub(super) fn router() -> Handler<'static, Result<()>, DpHandlerDescription> {
         .branch(case![TextCommand::Drafts].endpoint(drafts))
         .branch(case![TextCommand::Pending].endpoint(pending))
         .branch(case![TextCommand::Published].endpoint(published))
+        .branch(case![TextCommand::Failed].endpoint(failed))
         .branch(case![TextCommand::RequestAccess].endpoint(request_access))
+        .branch(case![TextCommand::Queue].endpoint(queue))
+        .branch(case![TextCommand::Trash].endpoint(trash))
+        .branch(case![TextCommand::Review].endpoint(review))
+        .branch(case![TextCommand::Abandoned].endpoint(abandoned))
 }
 
 async fn users(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
@@ -30,7 +35,7 @@ async fn users(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
             .map(|u| u.role)
             .unwrap_or(Role::Guest);
         if role == Role::Admin {
-            let (users, _has_next) = rpc_client.list_users(1).await?;
+            let (users, _next_page_token) = rpc_client.list_users(None).await?;
             for user in users {
                 if user.role == Role::Admin {
                     continue;
@@ -82,7 +87,12 @@ async fn new_post(
     }
     Ok(())
 }
-async fn drafts(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
+async fn drafts(
+    bot: Bot,
+    msg: Message,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
     if let Some(from) = msg.from.as_ref() {
         let id = from.id.0.try_into()?;
         let role = rpc_client
@@ -91,11 +101,13 @@ async fn drafts(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
             .map(|u| u.role)
             .unwrap_or(Role::Guest);
         if role != Role::Guest {
-            let (posts, has_next) = rpc_client.drafts(id, 1).await?;
-            for post in posts {
-                send_post(&bot, &msg, &post).await?;
+            let posts_page = page_tokens
+                .posts(&mut rpc_client, msg.chat.id, id, Status::Draft, 1)
+                .await?;
+            for post in &posts_page.posts {
+                send_post(&bot, &msg, post).await?;
             }
-            if has_next {
+            if posts_page.has_next() {
                 bot.send_message(msg.chat.id, "Это не все")
                     .reply_markup(MyCallback::has_next_kb(id, Status::Draft, 2))
                     .await?;
@@ -109,7 +121,12 @@ async fn drafts(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
 
     Ok(())
 }
-async fn pending(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
+async fn pending(
+    bot: Bot,
+    msg: Message,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
     if let Some(from) = msg.from.as_ref() {
         let id = from.id.0.try_into()?;
         let role = rpc_client
@@ -118,11 +135,13 @@ async fn pending(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
             .map(|u| u.role)
             .unwrap_or(Role::Guest);
         if role != Role::Guest {
-            let (posts, has_next) = rpc_client.pending(id, 1).await?;
-            for post in posts {
-                send_post(&bot, &msg, &post).await?;
+            let posts_page = page_tokens
+                .posts(&mut rpc_client, msg.chat.id, id, Status::Pending, 1)
+                .await?;
+            for post in &posts_page.posts {
+                send_post(&bot, &msg, post).await?;
             }
-            if has_next {
+            if posts_page.has_next() {
                 bot.send_message(msg.chat.id, "Это не все")
                     .reply_markup(MyCallback::has_next_kb(id, Status::Pending, 2))
                     .await?;
@@ -136,7 +155,12 @@ async fn pending(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
 
     Ok(())
 }
-async fn published(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
+async fn published(
+    bot: Bot,
+    msg: Message,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
     if let Some(from) = msg.from.as_ref() {
         let id = from.id.0.try_into()?;
         let role = rpc_client
@@ -145,11 +169,13 @@ async fn published(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()>
             .map(|u| u.role)
             .unwrap_or(Role::Guest);
         if role != Role::Guest {
-            let (posts, has_next) = rpc_client.published(id, 1).await?;
-            for post in posts {
-                send_post(&bot, &msg, &post).await?;
+            let posts_page = page_tokens
+                .posts(&mut rpc_client, msg.chat.id, id, Status::Published, 1)
+                .await?;
+            for post in &posts_page.posts {
+                send_post(&bot, &msg, post).await?;
             }
-            if has_next {
+            if posts_page.has_next() {
                 bot.send_message(msg.chat.id, "Это не все")
                     .reply_markup(MyCallback::has_next_kb(id, Status::Published, 2))
                     .await?;
@@ -163,6 +189,210 @@ async fn published(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()>
 
     Ok(())
 }
+async fn failed(
+    bot: Bot,
+    msg: Message,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
+    if let Some(from) = msg.from.as_ref() {
+        let id = from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(id)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            let posts_page = page_tokens
+                .posts(&mut rpc_client, msg.chat.id, id, Status::Failed, 1)
+                .await?;
+            for post in &posts_page.posts {
+                send_post(&bot, &msg, post).await?;
+            }
+            if posts_page.has_next() {
+                bot.send_message(msg.chat.id, "Это не все")
+                    .reply_markup(MyCallback::has_next_kb(id, Status::Failed, 2))
+                    .await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+async fn abandoned(
+    bot: Bot,
+    msg: Message,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
+    if let Some(from) = msg.from.as_ref() {
+        let id = from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(id)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            let posts_page = page_tokens
+                .posts(&mut rpc_client, msg.chat.id, id, Status::Abandoned, 1)
+                .await?;
+            for post in &posts_page.posts {
+                send_post(&bot, &msg, post).await?;
+            }
+            if posts_page.has_next() {
+                bot.send_message(msg.chat.id, "Это не все")
+                    .reply_markup(MyCallback::has_next_kb(id, Status::Abandoned, 2))
+                    .await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+// Посты всех авторов в очереди на публикацию (только для администраторов)
+async fn queue(
+    bot: Bot,
+    msg: Message,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
+    if let Some(from) = msg.from.as_ref() {
+        let id = from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(id)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role == Role::Admin {
+            let posts_page = page_tokens
+                .posts(&mut rpc_client, msg.chat.id, 0, Status::Pending, 1)
+                .await?;
+            if posts_page.posts.is_empty() {
+                bot.send_message(msg.chat.id, "Очередь пуста").await?;
+            }
+            for post in &posts_page.posts {
+                let author = posts_page.authors.get(&post.author_id);
+                send_post_by(&bot, &msg, post, author.map(String::as_str)).await?;
+            }
+            if posts_page.has_next() {
+                bot.send_message(msg.chat.id, "Это не все")
+                    .reply_markup(MyCallback::has_next_kb(0, Status::Pending, 2))
+                    .await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа").await?;
+        }
+    }
+
+    Ok(())
+}
+// Посты в корзине: администратор видит корзину всех авторов, редактор - свою
+async fn trash(
+    bot: Bot,
+    msg: Message,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
+    if let Some(from) = msg.from.as_ref() {
+        let id = from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(id)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            let author_id = if role == Role::Admin { 0 } else { id };
+            let posts_page = page_tokens
+                .trash(&mut rpc_client, msg.chat.id, author_id, 1)
+                .await?;
+            if posts_page.posts.is_empty() {
+                bot.send_message(msg.chat.id, "Корзина пуста").await?;
+            }
+            for post in &posts_page.posts {
+                let author = posts_page
+                    .authors
+                    .get(&post.author_id)
+                    .filter(|_| author_id == 0);
+                send_post_by(&bot, &msg, post, author.map(String::as_str)).await?;
+            }
+            if posts_page.has_next() {
+                bot.send_message(msg.chat.id, "Это не все")
+                    .reply_markup(MyCallback::trash_has_next_kb(author_id, 2))
+                    .await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+// Посты на проверке: администратор видит посты всех авторов, ожидающие
+// решения, редактор - свои посты на проверке, одобренные и отклоненные
+async fn review(
+    bot: Bot,
+    msg: Message,
+    mut rpc_client: Client,
+    page_tokens: PageTokens,
+) -> Result<()> {
+    if let Some(from) = msg.from.as_ref() {
+        let id = from.id.0.try_into()?;
+        let role = rpc_client
+            .get_user(id)
+            .await?
+            .map(|u| u.role)
+            .unwrap_or(Role::Guest);
+        if role != Role::Guest {
+            let (author_id, statuses) = if role == Role::Admin {
+                (0, vec![Status::InReview])
+            } else {
+                (
+                    id,
+                    vec![Status::InReview, Status::Approved, Status::Rejected],
+                )
+            };
+            let mut empty = true;
+            for status in statuses {
+                let posts_page = page_tokens
+                    .posts(&mut rpc_client, msg.chat.id, author_id, status, 1)
+                    .await?;
+                empty &= posts_page.posts.is_empty();
+                for post in &posts_page.posts {
+                    let author = posts_page
+                        .authors
+                        .get(&post.author_id)
+                        .filter(|_| author_id == 0);
+                    send_post_by(&bot, &msg, post, author.map(String::as_str)).await?;
+                }
+                if posts_page.has_next() {
+                    bot.send_message(msg.chat.id, "Это не все")
+                        .reply_markup(MyCallback::has_next_kb(author_id, status, 2))
+                        .await?;
+                }
+            }
+            if empty {
+                bot.send_message(msg.chat.id, "Нет постов на проверке")
+                    .await?;
+            }
+        } else {
+            bot.send_message(msg.chat.id, "У вас нет доступа")
+                .reply_markup(TextCommand::guest_keyboard())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
 async fn request_access(bot: Bot, msg: Message, mut rpc_client: Client) -> Result<()> {
     if let Some(from) = msg.from.as_ref() {
         let id = from.id.0.try_into()?;
@@ -170,16 +400,15 @@ async fn request_access(bot: Bot, msg: Message, mut rpc_client: Client) -> Resul
             .get_user(id)
             .await?
             .ok_or(anyhow!("user not found"))?;
-        let mut page = 1;
+        let mut page_token = None;
         let mut users = Vec::new();
         loop {
-            let (current_users, has_next) = rpc_client.list_users(page).await?;
+            let (current_users, next_page_token) = rpc_client.list_users(page_token).await?;
             users.extend(current_users);
-            if has_next {
-                page += 1;
-            } else {
+            if next_page_token.is_none() {
                 break;
             }
+            page_token = next_page_token;
         }
         for user in users {
             if user.role == Role::Admin {
@@ -15,4 +15,7 @@ pub enum State {
     PublishDateReceive {
         post_id: Uuid,
     },
+    RejectCommentReceive {
+        post_id: Uuid,
+    },
 }
@@ -8,7 +8,12 @@ const CREATE_POST: &str = "➕ Новый пост";
 const DRAFTS: &str = "✍️ Черновики";
 const PENDING: &str = "⌛ В очереди";
 const PUBLISHED: &str = "✔️ Опубликованные";
+const FAILED: &str = "❗ Ошибки";
 const REQUEST_ACCESS: &str = "🙏 Запросить доступ";
+const QUEUE: &str = "📋 Очередь канала";
+const TRASH: &str = "🗑️ Корзина";
+const REVIEW: &str = "🔍 Проверка";
+const ABANDONED: &str = "🚫 Отмененные";
 
 #[derive(Clone)]
 pub enum TextCommand {
@@ -17,12 +22,17 @@ pub enum TextCommand {
     Drafts,
     Pending,
     Published,
+    Failed,
     RequestAccess,
+    Queue,
+    Trash,
+    Review,
+    Abandoned,
 }
 impl TextCommand {
     pub fn admin_keyboard() -> KeyboardMarkup {
         KeyboardMarkup::default()
-            .append_row(vec![TextCommand::Users.into()])
+            .append_row(vec![TextCommand::Users.into(), TextCommand::Queue.into()])
             .append_row(vec![
                 TextCommand::CreatePost.into(),
                 TextCommand::Drafts.into(),
@@ -31,6 +41,11 @@ impl TextCommand {
                 TextCommand::Pending.into(),
                 TextCommand::Published.into(),
             ])
+            .append_row(vec![TextCommand::Failed.into(), TextCommand::Trash.into()])
+            .append_row(vec![
+                TextCommand::Review.into(),
+                TextCommand::Abandoned.into(),
+            ])
             .resize_keyboard()
     }
     pub fn editor_keyboard() -> KeyboardMarkup {
@@ -43,6 +58,11 @@ impl TextCommand {
                 TextCommand::Pending.into(),
                 TextCommand::Published.into(),
             ])
+            .append_row(vec![TextCommand::Failed.into(), TextCommand::Trash.into()])
+            .append_row(vec![
+                TextCommand::Review.into(),
+                TextCommand::Abandoned.into(),
+            ])
             .resize_keyboard()
     }
     pub fn guest_keyboard() -> KeyboardMarkup {
@@ -60,7 +80,12 @@ impl FromStr for TextCommand {
             DRAFTS => Ok(Self::Drafts),
             PENDING => Ok(Self::Pending),
             PUBLISHED => Ok(Self::Published),
+            FAILED => Ok(Self::Failed),
             REQUEST_ACCESS => Ok(Self::RequestAccess),
+            QUEUE => Ok(Self::Queue),
+            TRASH => Ok(Self::Trash),
+            REVIEW => Ok(Self::Review),
+            ABANDONED => Ok(Self::Abandoned),
             _ => Err(anyhow!("not a text command")),
         }
     }
@@ -73,7 +98,12 @@ impl Display for TextCommand {
             TextCommand::Drafts => DRAFTS,
             TextCommand::Pending => PENDING,
             TextCommand::Published => PUBLISHED,
+            TextCommand::Failed => FAILED,
             TextCommand::RequestAccess => REQUEST_ACCESS,
+            TextCommand::Queue => QUEUE,
+            TextCommand::Trash => TRASH,
+            TextCommand::Review => REVIEW,
+            TextCommand::Abandoned => ABANDONED,
         };
         write!(f, "{s}")
     }
@@ -54,7 +54,7 @@ impl VKClient {
         }
     }
     #[instrument(name = "publish post", skip(self))]
-    pub async fn publish(&self, post: &Post) -> Result<()> {
+    pub async fn publish(&self, post: &Post) -> Result<WallPostResponse> {
         let response = if let Some(photo) = post.vk_photo_file_id.as_ref() {
             // Has photo
             let url = format!(
@@ -82,13 +82,83 @@ impl VKClient {
             );
             self.client.get(url).bearer_auth(&self.token).send().await?
         };
-        if response.status().is_success() {
-            Ok(())
-        } else {
+        if !response.status().is_success() {
             let err = response.text().await?;
-            Err(anyhow!(err))
+            return Err(anyhow!(err));
+        }
+        let response: serde_json::Value = response.json().await?;
+        match serde_json::from_value::<ApiResponse<WallPostResponse>>(response.clone()) {
+            Ok(res) => Ok(res.response),
+            Err(e) => {
+                let err = format!("Error: {e:?}\nResponse:\n{response:#?}");
+                Err(anyhow!(err))
+            }
+        }
+    }
+    #[instrument(name = "edit post", skip(self))]
+    pub async fn edit(&self, post_id: i64, post: &Post) -> Result<()> {
+        let url = format!("{BASE_URL}/wall.edit");
+        let mut query = vec![
+            ("owner_id", self.owner_id()),
+            ("post_id", post_id.to_string()),
+            ("message", post.content.clone()),
+            ("v", self.version.clone()),
+        ];
+        if let Some(attachment) = post
+            .vk_photo_file_id
+            .as_ref()
+            .or(post.vk_video_file_id.as_ref())
+        {
+            query.push(("attachments", attachment.clone()));
+        }
+        let response: serde_json::Value = self
+            .client
+            .get(url)
+            .query(&query)
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        match serde_json::from_value::<ApiResponse<WallPostResponse>>(response.clone()) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let err = format!("Error: {e:?}\nResponse:\n{response:#?}");
+                Err(anyhow!(err))
+            }
+        }
+    }
+    #[instrument(name = "delete post", skip(self))]
+    pub async fn delete(&self, post_id: i64) -> Result<()> {
+        let url = format!("{BASE_URL}/wall.delete");
+        let query = [
+            ("owner_id", self.owner_id()),
+            ("post_id", post_id.to_string()),
+            ("v", self.version.clone()),
+        ];
+        let response: serde_json::Value = self
+            .client
+            .get(url)
+            .query(&query)
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        match serde_json::from_value::<ApiResponse<i64>>(response.clone()) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let err = format!("Error: {e:?}\nResponse:\n{response:#?}");
+                Err(anyhow!(err))
+            }
         }
     }
+    pub fn owner_id(&self) -> String {
+        format!("-{gid}", gid = self.group_id)
+    }
+    pub fn wall_url(&self, post_id: i64) -> String {
+        format!("https://vk.com/wall-{gid}_{post_id}", gid = self.group_id)
+    }
     #[instrument(name = "get vk photo id", skip(self))]
     pub async fn get_photo_id(&self, file_path: String) -> Result<String> {
         let upload_url = self.get_photo_upload_url().await?;
@@ -247,6 +317,11 @@ pub struct ApiResponse<T> {
     pub response: T,
 }
 
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WallPostResponse {
+    pub post_id: i64,
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AlbumsResponse {
     pub count: i64,